@@ -13,11 +13,16 @@ use core_foundation::dictionary::CFDictionary;
 use core_foundation::number::CFNumber;
 use core_foundation::string::CFString;
 use core_graphics::window::CGWindowID;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
+use crate::config::PickerOrder;
+pub use crate::direction::Direction;
+use crate::direction::FocusStrategy;
 use crate::error::PixieError;
+use crate::geometry::Rect;
+use crate::tiling::TileLayout;
 
 static APP_NAME_CACHE: OnceLock<Mutex<HashMap<i32, String>>> = OnceLock::new();
 
@@ -295,6 +300,10 @@ pub fn focus_window(element: &AXUIElement) -> Result<(), PixieError> {
 
     let _ = element.perform_action(&CFString::new("AXRaise"));
 
+    if let Ok(window_id) = get_window_id(element) {
+        crate::recent::touch((pid, window_id));
+    }
+
     Ok(())
 }
 
@@ -433,18 +442,51 @@ fn executable_path_for_pid(pid: i32) -> Option<String> {
 }
 
 fn get_app_icon_path(pid: i32) -> Option<String> {
-    use std::path::Path;
-
     let executable_path = executable_path_for_pid(pid)?;
     let bundle_path = bundle_path_from_executable(&executable_path)?;
-    let resources_path = Path::new(&bundle_path).join("Contents").join("Resources");
+    bundle_icon_path(&bundle_path, &format!("pid-{pid}"))
+}
+
+/// Icon of an app bundle that isn't necessarily running, as a file the picker can show.
+/// Lookups are cached, as the launcher asks for every installed app each time it opens.
+pub fn get_bundle_icon_path(bundle_path: &str) -> Option<String> {
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+
+    static BUNDLE_ICONS: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+
+    let cache = BUNDLE_ICONS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(icon_path) = cache.lock().unwrap().get(bundle_path) {
+        return icon_path.clone();
+    }
+
+    let bundle_name = std::path::Path::new(bundle_path)
+        .file_stem()
+        .map(|stem| {
+            stem.to_string_lossy()
+                .replace(|c: char| !c.is_alphanumeric(), "-")
+        })
+        .unwrap_or_default();
+    let icon_path = bundle_icon_path(bundle_path, &format!("app-{bundle_name}"));
+    cache
+        .lock()
+        .unwrap()
+        .insert(bundle_path.to_string(), icon_path.clone());
+    icon_path
+}
+
+/// `cache_name` names the PNG converted from an `.icns` icon.
+fn bundle_icon_path(bundle_path: &str, cache_name: &str) -> Option<String> {
+    use std::path::Path;
+
+    let resources_path = Path::new(bundle_path).join("Contents").join("Resources");
     if !resources_path.exists() {
         return None;
     }
 
-    let icon_name = read_bundle_icon_name(&bundle_path);
+    let icon_name = read_bundle_icon_name(bundle_path);
     let icon_path = resolve_bundle_icon_path(&resources_path, icon_name.as_deref())?;
-    picker_compatible_icon_path(pid, &icon_path)
+    picker_compatible_icon_path(cache_name, &icon_path)
 }
 
 fn read_bundle_icon_name(bundle_path: &str) -> Option<String> {
@@ -515,7 +557,7 @@ fn resolve_bundle_icon_path(
     fallback_icons.into_iter().next()
 }
 
-fn picker_compatible_icon_path(pid: i32, icon_path: &std::path::Path) -> Option<String> {
+fn picker_compatible_icon_path(cache_name: &str, icon_path: &std::path::Path) -> Option<String> {
     use std::process::Command;
 
     let extension = icon_path
@@ -529,9 +571,9 @@ fn picker_compatible_icon_path(pid: i32, icon_path: &std::path::Path) -> Option<
 
     let output_dir = std::env::temp_dir().join("pixie-app-icons");
     let output_path = if std::fs::create_dir_all(&output_dir).is_ok() {
-        output_dir.join(format!("pid-{pid}.png"))
+        output_dir.join(format!("{cache_name}.png"))
     } else {
-        std::env::temp_dir().join(format!("pixie-app-icon-{pid}.png"))
+        std::env::temp_dir().join(format!("pixie-app-icon-{cache_name}.png"))
     };
 
     if !output_path.exists() {
@@ -602,14 +644,6 @@ pub fn find_window_by_id(pid: i32, window_id: CGWindowID) -> Result<AXUIElement,
     Err(PixieError::WindowNotFound)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
-
 #[derive(Debug, Clone)]
 pub struct WindowRect {
     pub x: f64,
@@ -620,6 +654,12 @@ pub struct WindowRect {
     pub window_id: Option<u32>,
 }
 
+impl WindowRect {
+    pub fn frame(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
+}
+
 pub fn get_window_rect(element: &AXUIElement) -> Result<WindowRect, PixieError> {
     use accessibility_sys::AXValueGetValue;
     use core_graphics::geometry::{CGPoint, CGRect, CGSize};
@@ -661,7 +701,35 @@ pub fn get_window_rect(element: &AXUIElement) -> Result<WindowRect, PixieError>
 pub fn find_window_in_direction(
     from: &WindowRect,
     direction: Direction,
+    strategy: FocusStrategy,
 ) -> Result<AXUIElement, PixieError> {
+    let mut windows = onscreen_windows()?;
+
+    let from_index = match windows
+        .iter()
+        .position(|(key, _)| Some(*key) == from.window_id.map(|id| (from.pid, id)))
+    {
+        Some(index) => index,
+        None => {
+            windows.insert(0, ((from.pid, from.window_id.unwrap_or(0)), from.frame()));
+            0
+        }
+    };
+
+    let frames: Vec<Rect> = windows.iter().map(|(_, frame)| *frame).collect();
+    let screens: Vec<Rect> = get_screens()?.iter().map(Screen::frame).collect();
+
+    match crate::direction::pick_window(&frames, from_index, &screens, direction, strategy) {
+        Some(index) => {
+            let (pid, window_id) = windows[index].0;
+            find_window_element_by_id(pid, window_id)
+        }
+        None => Err(PixieError::WindowNotFound),
+    }
+}
+
+/// Normal-layer on-screen windows in front-to-back order, as ((pid, window_id), frame)
+fn onscreen_windows() -> Result<Vec<((i32, u32), Rect)>, PixieError> {
     use core_foundation::number::CFNumber;
     use core_graphics::window::{
         create_description_from_array, create_window_list, kCGNullWindowID, kCGWindowBounds,
@@ -681,7 +749,7 @@ pub fn find_window_in_direction(
     let bounds_key = unsafe { CFString::wrap_under_get_rule(kCGWindowBounds) };
     let window_number_key = CFString::new("kCGWindowNumber");
 
-    let mut scored_candidates: Vec<(f64, usize, i32, u32)> = Vec::new();
+    let mut windows: Vec<((i32, u32), Rect)> = Vec::new();
 
     for i in 0..descriptions.len() {
         let Some(window_desc) = descriptions.get(i) else {
@@ -705,18 +773,14 @@ pub fn find_window_in_direction(
             continue;
         };
 
-        let window_id: Option<u32> = window_desc
+        let Some(window_id) = window_desc
             .find(&window_number_key)
             .and_then(|v| v.downcast::<CFNumber>())
             .and_then(|n| n.to_i64())
-            .map(|n| n as u32);
-
-        if pid == from.pid
-            && let (Some(wid), Some(from_wid)) = (window_id, from.window_id)
-            && wid == from_wid
-        {
+            .map(|n| n as u32)
+        else {
             continue;
-        }
+        };
 
         // Get bounds from the window description
         let bounds_value = window_desc.find(&bounds_key);
@@ -734,24 +798,10 @@ pub fn find_window_in_direction(
         let width = get_dict_f64(&bounds_dict, "Width");
         let height = get_dict_f64(&bounds_dict, "Height");
 
-        if let Some(score) =
-            calculate_direction_score_simple(from, (x, y, width, height), direction)
-            && let Some(wid) = window_id
-        {
-            scored_candidates.push((score, i as usize, pid, wid));
-        }
+        windows.push(((pid, window_id), Rect::new(x, y, width, height)));
     }
 
-    scored_candidates.sort_by(|a, b| match a.0.partial_cmp(&b.0) {
-        Some(std::cmp::Ordering::Equal) | None => a.1.cmp(&b.1),
-        Some(ord) => ord,
-    });
-
-    if let Some((_, _, pid, window_id)) = scored_candidates.into_iter().next() {
-        return find_window_element_by_id(pid, window_id);
-    }
-
-    Err(PixieError::WindowNotFound)
+    Ok(windows)
 }
 
 fn find_window_element_by_id(pid: i32, window_id: u32) -> Result<AXUIElement, PixieError> {
@@ -783,94 +833,6 @@ fn find_window_element_by_id(pid: i32, window_id: u32) -> Result<AXUIElement, Pi
     )))
 }
 
-fn calculate_direction_score_simple(
-    from: &WindowRect,
-    other_bounds: (f64, f64, f64, f64),
-    direction: Direction,
-) -> Option<f64> {
-    let (other_x, other_y, other_width, other_height) = other_bounds;
-
-    // Check if candidate window extends past the current window in the target direction
-    // This allows partially overlapping windows as long as they extend further in that direction
-    match direction {
-        Direction::Left => {
-            // Candidate's left edge must be left of current's left edge
-            if other_x >= from.x {
-                return None;
-            }
-        }
-        Direction::Right => {
-            // Candidate's right edge must be right of current's right edge
-            if other_x + other_width <= from.x + from.width {
-                return None;
-            }
-        }
-        Direction::Up => {
-            // Candidate's top edge must be above current's top edge
-            if other_y >= from.y {
-                return None;
-            }
-        }
-        Direction::Down => {
-            // Candidate's bottom edge must be below current's bottom edge
-            if other_y + other_height <= from.y + from.height {
-                return None;
-            }
-        }
-    }
-
-    // Calculate the distance from current window's edge to the candidate's edge
-    // For overlapping windows, distance can be negative, which we treat as 0
-    // (they're visually adjacent, just overlapping)
-    let (primary_distance, overlap) = match direction {
-        Direction::Left => {
-            // Distance from current left edge to candidate's right edge
-            let dist = from.x - (other_x + other_width);
-            let ov = overlap_amount_1d(
-                from.y,
-                from.y + from.height,
-                other_y,
-                other_y + other_height,
-            );
-            (dist, ov)
-        }
-        Direction::Right => {
-            // Distance from current right edge to candidate's left edge
-            let dist = other_x - (from.x + from.width);
-            let ov = overlap_amount_1d(
-                from.y,
-                from.y + from.height,
-                other_y,
-                other_y + other_height,
-            );
-            (dist, ov)
-        }
-        Direction::Up => {
-            // Distance from current top edge to candidate's bottom edge
-            let dist = from.y - (other_y + other_height);
-            let ov = overlap_amount_1d(from.x, from.x + from.width, other_x, other_x + other_width);
-            (dist, ov)
-        }
-        Direction::Down => {
-            // Distance from current bottom edge to candidate's top edge
-            let dist = other_y - (from.y + from.height);
-            let ov = overlap_amount_1d(from.x, from.x + from.width, other_x, other_x + other_width);
-            (dist, ov)
-        }
-    };
-
-    // For overlapping windows (negative distance), treat as distance 0
-    // They're still valid candidates since they extend in the target direction
-    let primary_distance = primary_distance.max(0.0);
-
-    let overlap_bonus = overlap * 100.0;
-    Some(primary_distance - overlap_bonus)
-}
-
-fn overlap_amount_1d(a1: f64, a2: f64, b1: f64, b2: f64) -> f64 {
-    (a2.min(b2) - a1.max(b1)).max(0.0)
-}
-
 fn get_dict_f64(dict: &CFDictionary, key: &str) -> f64 {
     let key = CFString::new(key);
     unsafe {
@@ -901,6 +863,13 @@ pub struct Screen {
     pub width: f64,
     pub height: f64,
     pub is_main: bool,
+    pub name: String,
+}
+
+impl Screen {
+    pub fn frame(&self) -> Rect {
+        Rect::new(self.x, self.y, self.width, self.height)
+    }
 }
 
 /// A window entry for the window picker
@@ -912,16 +881,20 @@ pub struct WindowEntry {
     pub title: String,
     pub app_icon_path: Option<String>,
     pub bounds: (f64, f64, f64, f64), // x, y, width, height
+    /// Minimized to the Dock
+    pub minimized: bool,
 }
 
+/// Get all active screens in stable display order (see [`crate::display`])
 pub fn get_screens() -> Result<Vec<Screen>, PixieError> {
     use core_graphics::display::CGDisplay;
 
     let display_ids = CGDisplay::active_displays().map_err(|e| {
         PixieError::Accessibility(format!("Failed to get active displays: {:?}", e))
     })?;
+    let names = display_names();
 
-    let screens: Vec<Screen> = display_ids
+    let mut screens: Vec<Screen> = display_ids
         .into_iter()
         .map(|id| {
             let display = CGDisplay::new(id);
@@ -932,6 +905,10 @@ pub fn get_screens() -> Result<Vec<Screen>, PixieError> {
                 width: bounds.size.width,
                 height: bounds.size.height,
                 is_main: display.is_main(),
+                name: names
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| format!("Display {}", id)),
             }
         })
         .collect();
@@ -942,15 +919,93 @@ pub fn get_screens() -> Result<Vec<Screen>, PixieError> {
         ));
     }
 
+    screens.sort_by(|a, b| crate::display::compare_position(&a.frame(), &b.frame()));
+
     Ok(screens)
 }
 
+/// Localized display names (e.g. "Built-in Retina Display") keyed by CGDirectDisplayID
+fn display_names() -> HashMap<u32, String> {
+    use cocoa::base::{BOOL, YES, id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    let mut names = HashMap::new();
+
+    unsafe {
+        let screens: id = msg_send![class!(NSScreen), screens];
+        if screens == nil {
+            return names;
+        }
+
+        let number_key = NSString::alloc(nil).init_str("NSScreenNumber");
+        let count: usize = msg_send![screens, count];
+        for index in 0..count {
+            let screen: id = msg_send![screens, objectAtIndex: index];
+            let has_name: BOOL = msg_send![screen, respondsToSelector: sel!(localizedName)];
+            if has_name != YES {
+                continue;
+            }
+
+            let description: id = msg_send![screen, deviceDescription];
+            let number: id = msg_send![description, objectForKey: number_key];
+            let name: id = msg_send![screen, localizedName];
+            if number == nil || name == nil {
+                continue;
+            }
+
+            let display_id: u32 = msg_send![number, unsignedIntValue];
+            let utf8: *const std::os::raw::c_char = msg_send![name, UTF8String];
+            if !utf8.is_null() {
+                let name = std::ffi::CStr::from_ptr(utf8)
+                    .to_string_lossy()
+                    .into_owned();
+                names.insert(display_id, name);
+            }
+        }
+        let _: () = msg_send![number_key, release];
+    }
+
+    names
+}
+
+/// Find the screen a [`DisplayTarget`](crate::config::DisplayTarget) refers to
+pub fn find_screen(target: &crate::config::DisplayTarget) -> Result<Screen, PixieError> {
+    let screens = get_screens()?;
+    let names: Vec<&str> = screens.iter().map(|screen| screen.name.as_str()).collect();
+
+    crate::display::find_display(&names, target)
+        .map(|index| screens[index].clone())
+        .ok_or_else(|| {
+            let target = match target {
+                crate::config::DisplayTarget::Index(number) => format!("number {}", number),
+                crate::config::DisplayTarget::Name(name) => format!("\"{}\"", name),
+            };
+            PixieError::Accessibility(format!(
+                "No display {} (available: {})",
+                target,
+                names.join(", ")
+            ))
+        })
+}
+
 /// Get all windows used by the picker (including off-screen/minimized)
-pub fn get_all_windows() -> Result<Vec<WindowEntry>, PixieError> {
-    get_picker_windows(None)
+pub fn get_all_windows(order: PickerOrder) -> Result<Vec<WindowEntry>, PixieError> {
+    get_picker_windows(None, order)
+}
+
+/// Get the on-screen windows whose center is on `screen`
+pub fn get_screen_windows(
+    screen: &Screen,
+    order: PickerOrder,
+) -> Result<Vec<WindowEntry>, PixieError> {
+    get_picker_windows(Some(screen), order)
 }
 
-fn get_picker_windows(screen: Option<&Screen>) -> Result<Vec<WindowEntry>, PixieError> {
+fn get_picker_windows(
+    screen: Option<&Screen>,
+    order: PickerOrder,
+) -> Result<Vec<WindowEntry>, PixieError> {
     use std::collections::HashMap;
 
     use core_foundation::number::CFNumber;
@@ -977,9 +1032,11 @@ fn get_picker_windows(screen: Option<&Screen>) -> Result<Vec<WindowEntry>, Pixie
     let bounds_key = unsafe { CFString::wrap_under_get_rule(kCGWindowBounds) };
     let window_number_key = CFString::new("kCGWindowNumber");
     let window_name_key = CFString::new("kCGWindowName");
+    let on_screen_key = CFString::new("kCGWindowIsOnscreen");
     let mut app_name_cache = HashMap::<i32, String>::new();
     let mut app_icon_cache = HashMap::<i32, Option<String>>::new();
     let mut ax_title_cache = HashMap::<i32, Option<HashMap<u32, String>>>::new();
+    let mut ax_minimized_cache = HashMap::<i32, HashSet<u32>>::new();
 
     let mut windows = Vec::new();
 
@@ -1083,6 +1140,18 @@ fn get_picker_windows(screen: Option<&Screen>) -> Result<Vec<WindowEntry>, Pixie
             }
         };
 
+        let on_screen = window_desc
+            .find(&on_screen_key)
+            .and_then(|v| v.downcast::<CFBoolean>())
+            .map(bool::from)
+            .unwrap_or(false);
+        // Off-screen windows may also be hidden or on another Space, so ask the app
+        let minimized = !on_screen
+            && ax_minimized_cache
+                .entry(pid)
+                .or_insert_with(|| get_minimized_window_ids(pid))
+                .contains(&window_id);
+
         windows.push(WindowEntry {
             pid,
             window_id,
@@ -1093,14 +1162,23 @@ fn get_picker_windows(screen: Option<&Screen>) -> Result<Vec<WindowEntry>, Pixie
                 .or_insert_with(|| get_app_icon_path(pid))
                 .clone(),
             bounds: (x, y, width, height),
+            minimized,
         });
     }
 
-    // Sort by app name, then by title
-    windows.sort_by(|a, b| match a.app_name.cmp(&b.app_name) {
-        std::cmp::Ordering::Equal => a.title.cmp(&b.title),
-        other => other,
-    });
+    match order {
+        PickerOrder::Alphabetical => {
+            // Sort by app name, then by title
+            windows.sort_by(|a, b| match a.app_name.cmp(&b.app_name) {
+                std::cmp::Ordering::Equal => a.title.cmp(&b.title),
+                other => other,
+            });
+        }
+        // Windows Pixie hasn't seen focused stay in front-to-back order
+        PickerOrder::Recent => {
+            crate::recent::sort_by_recency(&mut windows, |window| (window.pid, window.window_id))
+        }
+    }
 
     Ok(windows)
 }
@@ -1130,6 +1208,25 @@ fn get_window_titles_for_pid(
     Ok(titles)
 }
 
+/// IDs of the windows of `pid` that are minimized to the Dock
+fn get_minimized_window_ids(pid: i32) -> HashSet<u32> {
+    let Ok(windows) = AXUIElement::application(pid).windows() else {
+        return HashSet::new();
+    };
+
+    let mut minimized = HashSet::new();
+    for i in 0..windows.len() {
+        if let Some(win) = windows.get(i).map(|win| win.clone())
+            && win.minimized().is_ok_and(bool::from)
+            && let Ok(win_id) = get_window_id(&win)
+        {
+            minimized.insert(win_id);
+        }
+    }
+
+    minimized
+}
+
 pub fn get_screen_for_window(window_rect: &WindowRect) -> Result<Screen, PixieError> {
     let screens = get_screens()?;
 
@@ -1163,12 +1260,37 @@ pub fn get_screen_for_window(window_rect: &WindowRect) -> Result<Screen, PixieEr
     Ok(closest_screen)
 }
 
+/// Move and resize a window, recording the change so it can be undone
 pub fn set_window_rect(
     element: &AXUIElement,
     x: f64,
     y: f64,
     width: f64,
     height: f64,
+) -> Result<(), PixieError> {
+    let before = get_window_rect(element).ok();
+
+    write_window_rect(element, x, y, width, height)?;
+
+    if let Some(before) = before
+        && let Some(window_id) = before.window_id
+    {
+        crate::history::record(
+            (before.pid, window_id),
+            before.frame(),
+            Rect::new(x, y, width, height),
+        );
+    }
+
+    Ok(())
+}
+
+fn write_window_rect(
+    element: &AXUIElement,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
 ) -> Result<(), PixieError> {
     use accessibility_sys::AXValueCreate;
     use core_graphics::geometry::{CGPoint, CGSize};
@@ -1254,18 +1376,32 @@ pub fn minimize_window(element: &AXUIElement) -> Result<(), PixieError> {
     Ok(())
 }
 
+/// Close a window by pressing its close button, so the app can ask about unsaved changes.
+pub fn close_window(element: &AXUIElement) -> Result<(), PixieError> {
+    let close_button_attr: AXAttribute<AXUIElement> =
+        AXAttribute::new(&CFString::new("AXCloseButton"));
+    let close_button = element
+        .attribute(&close_button_attr)
+        .map_err(|e| PixieError::Accessibility(format!("Failed to get close button: {:?}", e)))?;
+
+    close_button
+        .perform_action(&CFString::new("AXPress"))
+        .map_err(|e| PixieError::Accessibility(format!("Failed to close window: {:?}", e)))
+}
+
 pub fn maximize_window(element: &AXUIElement) -> Result<(), PixieError> {
     let placement = crate::config::Placement {
         top: Some("0%".to_string()),
         left: Some("0%".to_string()),
         width: Some("100%".to_string()),
         height: Some("100%".to_string()),
+        ..Default::default()
     };
 
     apply_placement(element, &placement)
 }
 
-fn get_dock_height() -> Result<f64, PixieError> {
+pub fn get_dock_height() -> Result<f64, PixieError> {
     use std::process::Command;
 
     let output = Command::new("defaults")
@@ -1352,6 +1488,27 @@ pub fn move_window_to_monitor(
 
     let target_screen = find_adjacent_screen(&current_screen, &screens, direction)?;
 
+    move_window_to_screen(element, &window_rect, &current_screen, &target_screen)
+}
+
+/// Move a window to a display by number or name (preserves relative position)
+pub fn move_window_to_display(
+    element: &AXUIElement,
+    target: &crate::config::DisplayTarget,
+) -> Result<(), PixieError> {
+    let window_rect = get_window_rect(element)?;
+    let current_screen = get_screen_for_window(&window_rect)?;
+    let target_screen = find_screen(target)?;
+
+    move_window_to_screen(element, &window_rect, &current_screen, &target_screen)
+}
+
+fn move_window_to_screen(
+    element: &AXUIElement,
+    window_rect: &WindowRect,
+    current_screen: &Screen,
+    target_screen: &Screen,
+) -> Result<(), PixieError> {
     let rel_left = (window_rect.x - current_screen.x) / current_screen.width;
     let rel_top = (window_rect.y - current_screen.y) / current_screen.height;
     let rel_width = window_rect.width / current_screen.width;
@@ -1414,82 +1571,182 @@ fn find_adjacent_screen(
     fallback.ok_or_else(|| PixieError::Accessibility("No adjacent monitor found".to_string()))
 }
 
+/// The part of a screen windows can be placed in (below the menu bar on the main screen)
+fn usable_area(screen: &Screen) -> Rect {
+    let menu_bar_height = if screen.is_main { 25.0 } else { 0.0 };
+
+    Rect::new(
+        screen.x,
+        screen.y + menu_bar_height,
+        screen.width,
+        screen.height - menu_bar_height,
+    )
+}
+
 pub fn apply_placement(
     element: &AXUIElement,
     placement: &crate::config::Placement,
 ) -> Result<(), PixieError> {
     let window_rect = get_window_rect(element)?;
-    let screen = get_screen_for_window(&window_rect)?;
+    let current_screen = get_screen_for_window(&window_rect)?;
+    let screen = match &placement.display {
+        Some(target) => find_screen(target)?,
+        None => current_screen.clone(),
+    };
 
-    let menu_bar_height = if screen.is_main { 25.0 } else { 0.0 };
+    // Fields the placement leaves unset keep the window's offset within its screen
+    let mut current = window_rect.frame();
+    current.x += screen.x - current_screen.x;
+    current.y += screen.y - current_screen.y;
 
-    let available_x = screen.x;
-    let available_y = screen.y + menu_bar_height;
-    let available_width = screen.width;
-    let available_height = screen.height - menu_bar_height;
+    let frame = crate::placement::resolve(placement, usable_area(&screen), current)?;
 
-    let new_width = match &placement.width {
-        Some(w) => crate::config::parse_size_value(w, available_width)?,
-        None => window_rect.width,
-    };
+    set_window_rect(element, frame.x, frame.y, frame.width, frame.height)
+}
 
-    let new_height = match &placement.height {
-        Some(h) => crate::config::parse_size_value(h, available_height)?,
-        None => window_rect.height,
-    };
+/// Exchange the frames of a window and its neighbor in `direction`
+pub fn swap_window_in_direction(
+    element: &AXUIElement,
+    direction: Direction,
+    strategy: FocusStrategy,
+) -> Result<(), PixieError> {
+    let from = get_window_rect(element)?;
+    let neighbor = find_window_in_direction(&from, direction, strategy)?;
+    let to = get_window_rect(&neighbor)?;
 
-    let new_x = match &placement.left {
-        Some(l) => {
-            available_x + crate::config::parse_position_value(l, available_width, new_width)?
-        }
-        None => window_rect.x,
-    };
+    crate::history::batch(|| {
+        set_window_rect(element, to.x, to.y, to.width, to.height)?;
+        set_window_rect(&neighbor, from.x, from.y, from.width, from.height)
+    })
+}
 
-    let new_y = match &placement.top {
-        Some(t) => {
-            available_y + crate::config::parse_position_value(t, available_height, new_height)?
-        }
-        None => window_rect.y,
-    };
+/// Move a window in `direction` by a fixed step or to the next edge, within its screen
+pub fn move_window_in_direction(
+    element: &AXUIElement,
+    direction: Direction,
+    step: &crate::adjust::MoveStep,
+) -> Result<(), PixieError> {
+    let window_rect = get_window_rect(element)?;
+    let screen = get_screen_for_window(&window_rect)?;
+    let own_key = window_rect.window_id.map(|id| (window_rect.pid, id));
+    let others: Vec<Rect> = onscreen_windows()?
+        .into_iter()
+        .filter(|(key, _)| Some(*key) != own_key)
+        .map(|(_, frame)| frame)
+        .collect();
 
-    set_window_rect(element, new_x, new_y, new_width, new_height)
+    let frame = crate::adjust::move_window(
+        window_rect.frame(),
+        usable_area(&screen),
+        &others,
+        direction,
+        step,
+    );
+
+    set_window_rect(element, frame.x, frame.y, frame.width, frame.height)
 }
 
-/// Tile multiple windows in equal-width columns on a screen
-pub fn tile_windows_in_columns(
+/// Grow or shrink a window along `axis` by `amount`, within its screen
+pub fn resize_window(
+    element: &AXUIElement,
+    axis: crate::config::ResizeAxis,
+    amount: &crate::placement::Expr,
+    grow: bool,
+) -> Result<(), PixieError> {
+    let window_rect = get_window_rect(element)?;
+    let screen = get_screen_for_window(&window_rect)?;
+
+    let frame = crate::adjust::resize_window(
+        window_rect.frame(),
+        usable_area(&screen),
+        axis,
+        amount,
+        grow,
+    );
+
+    set_window_rect(element, frame.x, frame.y, frame.width, frame.height)
+}
+
+/// The part of a screen windows are tiled in: its usable area, above a Dock of `dock_height`
+pub fn tile_area(screen: &Screen, dock_height: f64) -> Rect {
+    let mut area = usable_area(screen);
+    area.height -= dock_height;
+    area
+}
+
+/// Tile multiple windows on a screen in `layout`, in the order given
+pub fn tile_windows(
     window_ids: &[(i32, u32)], // (pid, window_id) pairs
     screen: &Screen,
+    layout: TileLayout,
 ) -> Result<(), PixieError> {
     if window_ids.is_empty() {
         return Ok(());
     }
 
-    let count = window_ids.len();
-    let menu_bar_height = if screen.is_main { 25.0 } else { 0.0 };
-    let dock_height = get_dock_height()?;
+    let frames = layout.frames(window_ids.len(), tile_area(screen, get_dock_height()?));
 
-    let available_x = screen.x;
-    let available_y = screen.y + menu_bar_height;
-    let available_width = screen.width;
-    let available_height = screen.height - menu_bar_height - dock_height;
+    crate::history::batch(|| {
+        for ((pid, window_id), frame) in window_ids.iter().zip(frames) {
+            if let Ok(element) = find_window_element_by_id(*pid, *window_id) {
+                let _ = set_window_rect(&element, frame.x, frame.y, frame.width, frame.height);
+            }
+        }
+    });
 
-    let column_width = available_width / count as f64;
+    Ok(())
+}
 
-    for (i, (pid, window_id)) in window_ids.iter().enumerate() {
-        if let Ok(element) = find_window_element_by_id(*pid, *window_id) {
-            let x = available_x + (i as f64 * column_width);
-            let _ = set_window_rect(&element, x, available_y, column_width, available_height);
-        }
-    }
+/// Revert the most recent window geometry change (all windows of a tile at once)
+pub fn undo_geometry() -> Result<(), PixieError> {
+    let frames = crate::history::take_undo()
+        .ok_or_else(|| PixieError::Accessibility("Nothing to undo".to_string()))?;
+    apply_history_frames(&frames);
+    Ok(())
+}
 
+/// Reapply the most recently undone window geometry change
+pub fn redo_geometry() -> Result<(), PixieError> {
+    let frames = crate::history::take_redo()
+        .ok_or_else(|| PixieError::Accessibility("Nothing to redo".to_string()))?;
+    apply_history_frames(&frames);
     Ok(())
 }
 
+/// Return a window to the frame it had before Pixie first moved it
+pub fn restore_window(element: &AXUIElement) -> Result<(), PixieError> {
+    let window_rect = get_window_rect(element)?;
+    let window_id = window_rect
+        .window_id
+        .ok_or_else(|| PixieError::Accessibility("Window has no window ID".to_string()))?;
+    let frame = crate::history::original_frame((window_rect.pid, window_id)).ok_or_else(|| {
+        PixieError::Accessibility("Window has not been moved by Pixie".to_string())
+    })?;
+
+    set_window_rect(element, frame.x, frame.y, frame.width, frame.height)
+}
+
+fn apply_history_frames(frames: &[((i32, u32), Rect)]) {
+    for ((pid, window_id), frame) in frames {
+        match find_window_element_by_id(*pid, *window_id) {
+            Ok(element) => {
+                if let Err(e) =
+                    write_window_rect(&element, frame.x, frame.y, frame.width, frame.height)
+                {
+                    eprintln!("✗ Failed to restore window {}: {}", window_id, e);
+                }
+            }
+            Err(e) => eprintln!("✗ Window {} is gone: {}", window_id, e),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
 
     use super::get_all_windows;
+    use crate::config::PickerOrder;
 
     #[test]
     #[ignore]
@@ -1500,7 +1757,7 @@ mod tests {
 
         for _ in 0..runs {
             let started = Instant::now();
-            let windows = get_all_windows().expect("get_all_windows failed");
+            let windows = get_all_windows(PickerOrder::default()).expect("get_all_windows failed");
             let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
             total_windows += windows.len();
             durations_ms.push(elapsed_ms);
@@ -0,0 +1,299 @@
+//! Incremental window adjustments: stepping a window in a direction or snapping it to the next
+//! edge, and growing or shrinking it, always kept inside the usable screen area.
+
+use crate::config::ResizeAxis;
+use crate::direction::Direction;
+use crate::error::{PixieError, Result};
+use crate::geometry::Rect;
+use crate::placement::{EvalContext, Expr, FieldKind, parse_expr};
+
+/// Distances closer than this are treated as already aligned.
+const EPSILON: f64 = 0.5;
+
+/// Shrinking never makes a window smaller than this.
+const MIN_WINDOW_SIZE: f64 = 100.0;
+
+/// How far `move_*` actions move a window.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveStep {
+    /// To the next screen or window edge in the direction of travel.
+    Edge,
+    /// By a fixed length (`"50px"`, `"10%"` of the screen axis, ...).
+    Length(Expr),
+}
+
+impl MoveStep {
+    pub fn parse(value: &str) -> Result<Self> {
+        if value.trim().eq_ignore_ascii_case("edge") {
+            return Ok(MoveStep::Edge);
+        }
+
+        parse_length("move_step", value).map(MoveStep::Length)
+    }
+}
+
+/// Parse a step length like `"20px"` or `"5%"`; `name` identifies the setting in errors.
+pub fn parse_length(name: &str, value: &str) -> Result<Expr> {
+    parse_expr(value, FieldKind::Size)
+        .map_err(|e| PixieError::Config(format!("Invalid {} \"{}\": {}", name, value, e)))
+}
+
+/// Grow (or shrink) `window` by `amount` along `axis`, keeping its center where possible.
+///
+/// The result never exceeds `area` and is kept inside it; shrinking stops at a minimum size.
+pub fn resize_window(
+    window: Rect,
+    area: Rect,
+    axis: ResizeAxis,
+    amount: &Expr,
+    grow: bool,
+) -> Rect {
+    let resize = |start: f64, length: f64, area_start: f64, area_length: f64| {
+        let delta = amount.eval(EvalContext {
+            axis: area_length,
+            window: length,
+        });
+        let new_length = if grow {
+            (length + delta).min(area_length)
+        } else {
+            (length - delta).max(MIN_WINDOW_SIZE.min(length))
+        };
+        let new_start = (start - (new_length - length) / 2.0)
+            .min(area_start + area_length - new_length)
+            .max(area_start);
+        (new_start, new_length)
+    };
+
+    let (x, width) = match axis {
+        ResizeAxis::Width | ResizeAxis::Both => resize(window.x, window.width, area.x, area.width),
+        ResizeAxis::Height => (window.x, window.width),
+    };
+    let (y, height) = match axis {
+        ResizeAxis::Height | ResizeAxis::Both => {
+            resize(window.y, window.height, area.y, area.height)
+        }
+        ResizeAxis::Width => (window.y, window.height),
+    };
+
+    Rect::new(x, y, width, height)
+}
+
+/// Move `window` by `step` in `direction`, staying inside `area`.
+///
+/// `others` are the frames of the other windows, used as snapping edges.
+pub fn move_window(
+    window: Rect,
+    area: Rect,
+    others: &[Rect],
+    direction: Direction,
+    step: &MoveStep,
+) -> Rect {
+    let horizontal = matches!(direction, Direction::Left | Direction::Right);
+    let forward = matches!(direction, Direction::Right | Direction::Down);
+    let (start, length, area_start, area_length) = if horizontal {
+        (window.x, window.width, area.x, area.width)
+    } else {
+        (window.y, window.height, area.y, area.height)
+    };
+
+    let target = match step {
+        MoveStep::Length(expr) => {
+            let distance = expr.eval(EvalContext {
+                axis: area_length,
+                window: length,
+            });
+            if forward {
+                start + distance
+            } else {
+                start - distance
+            }
+        }
+        MoveStep::Edge => {
+            // Only windows sharing the band the window moves through provide edges
+            let edges: Vec<f64> = others
+                .iter()
+                .filter(|other| {
+                    if horizontal {
+                        other.y < window.y + window.height && window.y < other.y + other.height
+                    } else {
+                        other.x < window.x + window.width && window.x < other.x + other.width
+                    }
+                })
+                .flat_map(|other| {
+                    if horizontal {
+                        [other.x, other.x + other.width]
+                    } else {
+                        [other.y, other.y + other.height]
+                    }
+                })
+                .chain([area_start, area_start + area_length])
+                .collect();
+            next_edge(start, length, &edges, forward)
+        }
+    };
+
+    let clamped = target
+        .min(area_start + area_length - length)
+        .max(area_start);
+    if horizontal {
+        Rect::new(clamped, window.y, window.width, window.height)
+    } else {
+        Rect::new(window.x, clamped, window.width, window.height)
+    }
+}
+
+/// The nearest position in the direction of travel where either end of the window lines up
+/// with one of `edges`, or `start` if there is none.
+fn next_edge(start: f64, length: f64, edges: &[f64], forward: bool) -> f64 {
+    let positions = edges.iter().flat_map(|&edge| [edge, edge - length]);
+    if forward {
+        positions
+            .filter(|&position| position > start + EPSILON)
+            .min_by(f64::total_cmp)
+    } else {
+        positions
+            .filter(|&position| position < start - EPSILON)
+            .max_by(f64::total_cmp)
+    }
+    .unwrap_or(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 0.0,
+        y: 25.0,
+        width: 1000.0,
+        height: 800.0,
+    };
+
+    fn step(value: &str) -> MoveStep {
+        MoveStep::parse(value).unwrap()
+    }
+
+    #[test]
+    fn parses_move_steps() {
+        assert_eq!(step("edge"), MoveStep::Edge);
+        assert_eq!(step(" Edge "), MoveStep::Edge);
+        assert!(matches!(step("40px"), MoveStep::Length(_)));
+        assert!(
+            MoveStep::parse("fast")
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid move_step \"fast\"")
+        );
+    }
+
+    #[test]
+    fn moves_by_fixed_step_clamped_to_area() {
+        let window = Rect::new(100.0, 125.0, 400.0, 300.0);
+
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Right, &step("50px")),
+            Rect::new(150.0, 125.0, 400.0, 300.0)
+        );
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Up, &step("10%")),
+            Rect::new(100.0, 45.0, 400.0, 300.0)
+        );
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Left, &step("500px")),
+            Rect::new(0.0, 125.0, 400.0, 300.0)
+        );
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Down, &step("1000px")),
+            Rect::new(100.0, 525.0, 400.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn snaps_to_next_window_edge_in_band() {
+        let window = Rect::new(100.0, 125.0, 200.0, 300.0);
+        let others = [
+            // In the window's row: left edge at 450, right edge at 700
+            Rect::new(450.0, 200.0, 250.0, 300.0),
+            // Below the window's row, ignored for horizontal moves
+            Rect::new(320.0, 600.0, 100.0, 100.0),
+        ];
+
+        let right = |window| move_window(window, AREA, &others, Direction::Right, &MoveStep::Edge);
+        // Right edge meets 450, then left edge meets 450, then right edge meets 700, ...
+        let first = right(window);
+        assert_eq!(first.x, 250.0);
+        let second = right(first);
+        assert_eq!(second.x, 450.0);
+        let third = right(second);
+        assert_eq!(third.x, 500.0);
+        let fourth = right(third);
+        assert_eq!(fourth.x, 700.0);
+        let fifth = right(fourth);
+        assert_eq!(fifth.x, 800.0);
+        assert_eq!(right(fifth).x, 800.0);
+    }
+
+    #[test]
+    fn grows_around_center_within_area() {
+        let window = Rect::new(300.0, 225.0, 400.0, 300.0);
+        let by = parse_length("by", "100px").unwrap();
+
+        assert_eq!(
+            resize_window(window, AREA, ResizeAxis::Both, &by, true),
+            Rect::new(250.0, 175.0, 500.0, 400.0)
+        );
+        assert_eq!(
+            resize_window(window, AREA, ResizeAxis::Width, &by, true),
+            Rect::new(250.0, 225.0, 500.0, 300.0)
+        );
+
+        // Against the left edge the window grows to the right only
+        let at_edge = Rect::new(0.0, 225.0, 400.0, 300.0);
+        assert_eq!(
+            resize_window(at_edge, AREA, ResizeAxis::Width, &by, true),
+            Rect::new(0.0, 225.0, 500.0, 300.0)
+        );
+
+        // Never larger than the area
+        let huge = parse_length("by", "200%").unwrap();
+        assert_eq!(
+            resize_window(window, AREA, ResizeAxis::Height, &huge, true),
+            Rect::new(300.0, 25.0, 400.0, 800.0)
+        );
+    }
+
+    #[test]
+    fn shrinks_around_center_down_to_minimum() {
+        let window = Rect::new(300.0, 225.0, 400.0, 300.0);
+        let by = parse_length("by", "10%").unwrap();
+
+        assert_eq!(
+            resize_window(window, AREA, ResizeAxis::Both, &by, false),
+            Rect::new(350.0, 265.0, 300.0, 220.0)
+        );
+
+        let huge = parse_length("by", "1000px").unwrap();
+        assert_eq!(
+            resize_window(window, AREA, ResizeAxis::Width, &huge, false),
+            Rect::new(450.0, 225.0, MIN_WINDOW_SIZE, 300.0)
+        );
+    }
+
+    #[test]
+    fn snaps_to_screen_edges() {
+        let window = Rect::new(100.0, 125.0, 400.0, 300.0);
+
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Left, &MoveStep::Edge).x,
+            0.0
+        );
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Down, &MoveStep::Edge).y,
+            525.0
+        );
+        assert_eq!(
+            move_window(window, AREA, &[], Direction::Up, &MoveStep::Edge).y,
+            25.0
+        );
+    }
+}
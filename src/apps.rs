@@ -0,0 +1,206 @@
+//! Installed applications, for the app launcher.
+//!
+//! On macOS these are the `.app` bundles in `/Applications` and `~/Applications` (and one
+//! level of folders inside them, like `Utilities`). On Linux they are the desktop entries
+//! in the XDG data directories.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledApp {
+    pub name: String,
+    /// What [`crate::window::WindowManager::launch_app`] takes: the bundle path, or the
+    /// desktop entry id on Linux
+    pub launch: String,
+    /// The `.app` bundle or `.desktop` file
+    pub path: PathBuf,
+    /// Icon file named by a desktop entry; bundles are resolved by the picker
+    pub icon_path: Option<String>,
+}
+
+/// The parts of a desktop entry the launcher uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEntry {
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+/// Parse the `[Desktop Entry]` group of a `.desktop` file. Entries that aren't applications
+/// or are hidden from menus are skipped.
+pub fn parse_desktop_entry(contents: &str) -> Option<DesktopEntry> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut icon = None;
+    let mut is_application = false;
+
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match (key.trim(), value.trim()) {
+            ("Type", value) => is_application = value == "Application",
+            ("Name", value) => name = Some(value.to_string()),
+            ("Icon", value) if !value.is_empty() => icon = Some(value.to_string()),
+            ("NoDisplay" | "Hidden", "true") => return None,
+            _ => {}
+        }
+    }
+
+    let name = name.filter(|name| !name.is_empty())?;
+    is_application.then_some(DesktopEntry { name, icon })
+}
+
+/// Keep the first app of each name (earlier directories take precedence) and sort by name.
+pub fn dedup_by_name(apps: Vec<InstalledApp>) -> Vec<InstalledApp> {
+    let mut seen = HashSet::new();
+    let mut apps: Vec<_> = apps
+        .into_iter()
+        .filter(|app| seen.insert(app.name.to_lowercase()))
+        .collect();
+    apps.sort_by_key(|app| app.name.to_lowercase());
+    apps
+}
+
+/// All installed apps, sorted by name.
+pub fn installed_apps() -> Vec<InstalledApp> {
+    let mut apps = Vec::new();
+    for dir in app_dirs() {
+        if cfg!(target_os = "linux") {
+            scan_desktop_entries(&dir, &mut apps);
+        } else {
+            scan_app_bundles(&dir, true, &mut apps);
+        }
+    }
+    dedup_by_name(apps)
+}
+
+fn app_dirs() -> Vec<PathBuf> {
+    let home = dirs::home_dir();
+    if cfg!(target_os = "linux") {
+        // The user's entries override the system ones
+        let mut data: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+        let data_dirs = std::env::var("XDG_DATA_DIRS")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+        data.extend(data_dirs.split(':').map(PathBuf::from));
+        data.into_iter()
+            .map(|dir| dir.join("applications"))
+            .collect()
+    } else {
+        let mut bundles = vec![PathBuf::from("/Applications")];
+        bundles.extend(home.map(|home| home.join("Applications")));
+        bundles
+    }
+}
+
+/// Add the `.app` bundles in `dir`, and in its subfolders when `recurse` is set.
+fn scan_app_bundles(dir: &Path, recurse: bool, apps: &mut Vec<InstalledApp>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().is_some_and(|ext| ext == "app") {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                apps.push(InstalledApp {
+                    name: name.to_string(),
+                    launch: path.to_string_lossy().into_owned(),
+                    path: path.clone(),
+                    icon_path: None,
+                });
+            }
+        } else if recurse && path.is_dir() {
+            scan_app_bundles(&path, false, apps);
+        }
+    }
+}
+
+fn scan_desktop_entries(dir: &Path, apps: &mut Vec<InstalledApp>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.extension().is_none_or(|ext| ext != "desktop") {
+            continue;
+        }
+        let Some(entry) = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| parse_desktop_entry(&contents))
+        else {
+            continue;
+        };
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        apps.push(InstalledApp {
+            name: entry.name,
+            launch: id.to_string(),
+            path: path.clone(),
+            // Theme icon names would need an icon theme lookup
+            icon_path: entry.icon.filter(|icon| Path::new(icon).is_absolute()),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_application_entries() {
+        let entry = parse_desktop_entry(
+            "# comment\n[Desktop Entry]\nType=Application\nName=Firefox\nIcon=firefox\n\
+             [Desktop Action new-window]\nName=New Window\n",
+        );
+        assert_eq!(
+            entry,
+            Some(DesktopEntry {
+                name: "Firefox".into(),
+                icon: Some("firefox".into()),
+            })
+        );
+
+        assert_eq!(
+            parse_desktop_entry("[Desktop Entry]\nType=Link\nName=Docs\n"),
+            None
+        );
+        assert_eq!(
+            parse_desktop_entry("[Desktop Entry]\nType=Application\nName=Helper\nNoDisplay=true\n"),
+            None
+        );
+        assert_eq!(
+            parse_desktop_entry("[Desktop Entry]\nType=Application\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn first_app_of_a_name_wins() {
+        let app = |name: &str, path: &str| InstalledApp {
+            name: name.into(),
+            launch: path.into(),
+            path: PathBuf::from(path),
+            icon_path: None,
+        };
+        let apps = dedup_by_name(vec![
+            app("Safari", "/Applications/Safari.app"),
+            app("notes", "/Applications/notes.app"),
+            app("Safari", "/Users/me/Applications/Safari.app"),
+        ]);
+        assert_eq!(
+            apps,
+            vec![
+                app("notes", "/Applications/notes.app"),
+                app("Safari", "/Applications/Safari.app"),
+            ]
+        );
+    }
+}
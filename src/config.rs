@@ -10,18 +10,115 @@ use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
 
+use crate::direction::{Direction, FocusStrategy, FocusWrap};
 use crate::error::{PixieError, Result};
+use crate::keymap::KeyMatching;
 
 bitflags::bitflags! {
+    /// Modifier keys of a keybind. The plain flags accept either side; the `LEFT_`/`RIGHT_`
+    /// flags require that specific key.
     #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
     pub struct Modifiers: u32 {
         const SUPER = 1 << 0;
         const ALT = 1 << 1;
         const SHIFT = 1 << 2;
         const CONTROL = 1 << 3;
+        const LEFT_SUPER = 1 << 4;
+        const RIGHT_SUPER = 1 << 5;
+        const LEFT_ALT = 1 << 6;
+        const RIGHT_ALT = 1 << 7;
+        const LEFT_SHIFT = 1 << 8;
+        const RIGHT_SHIFT = 1 << 9;
+        const LEFT_CONTROL = 1 << 10;
+        const RIGHT_CONTROL = 1 << 11;
     }
 }
 
+/// Each modifier as (either side, left key, right key).
+const MODIFIER_SIDES: [(Modifiers, Modifiers, Modifiers); 4] = [
+    (
+        Modifiers::SUPER,
+        Modifiers::LEFT_SUPER,
+        Modifiers::RIGHT_SUPER,
+    ),
+    (Modifiers::ALT, Modifiers::LEFT_ALT, Modifiers::RIGHT_ALT),
+    (
+        Modifiers::SHIFT,
+        Modifiers::LEFT_SHIFT,
+        Modifiers::RIGHT_SHIFT,
+    ),
+    (
+        Modifiers::CONTROL,
+        Modifiers::LEFT_CONTROL,
+        Modifiers::RIGHT_CONTROL,
+    ),
+];
+
+impl Modifiers {
+    /// Whether the keys `held` satisfy these modifiers, allowing other modifiers as well.
+    ///
+    /// `held` has the plain flag of every modifier that is down, plus the side flags the
+    /// keyboard reported.
+    pub fn held_in(self, held: Modifiers) -> bool {
+        MODIFIER_SIDES.iter().all(|&(any, left, right)| {
+            let wanted = self & (any | left | right);
+            wanted.is_empty()
+                || (held.contains(any) && (wanted.contains(any) || held.contains(wanted)))
+        })
+    }
+
+    /// Whether some combination of keys satisfies both `self` and `other` exactly.
+    ///
+    /// The left and right key of a modifier are treated as distinct.
+    pub fn overlaps(self, other: Modifiers) -> bool {
+        MODIFIER_SIDES.iter().all(|&(any, left, right)| {
+            let a = self & (any | left | right);
+            let b = other & (any | left | right);
+            if a.is_empty() || b.is_empty() {
+                a.is_empty() && b.is_empty()
+            } else {
+                a.contains(any) || b.contains(any) || a.intersects(b)
+            }
+        })
+    }
+
+    /// Like [`Modifiers::held_in`], but no other modifier may be down.
+    pub fn matches(self, held: Modifiers) -> bool {
+        self.held_in(held)
+            && MODIFIER_SIDES.iter().all(|&(any, left, right)| {
+                self.intersects(any | left | right) || !held.contains(any)
+            })
+    }
+}
+
+/// A single physical modifier key, for leaders like `double_tap:right_cmd`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ModifierKey {
+    LeftCmd,
+    RightCmd,
+    LeftAlt,
+    RightAlt,
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    Fn,
+}
+
+/// How leader mode is entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderKey {
+    /// Modifiers plus a key pressed together, like `cmd+shift+a`
+    Chord {
+        modifiers: Option<Modifiers>,
+        code: KeyCode,
+    },
+    /// Tapping a modifier key twice in quick succession, like `double_tap:right_cmd`
+    DoubleTap(ModifierKey),
+    /// Holding a modifier key down, like `hold:right_alt`
+    Hold(ModifierKey),
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KeyCode {
     KeyA,
@@ -72,6 +169,14 @@ pub enum KeyCode {
     F10,
     F11,
     F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
     Space,
     Escape,
     Enter,
@@ -97,6 +202,28 @@ pub enum KeyCode {
     Comma,
     Period,
     Slash,
+    Grave,
+    /// The `§` key left of `1` on ISO keyboards
+    Section,
+    Keypad0,
+    Keypad1,
+    Keypad2,
+    Keypad3,
+    Keypad4,
+    Keypad5,
+    Keypad6,
+    Keypad7,
+    Keypad8,
+    Keypad9,
+    KeypadDecimal,
+    KeypadPlus,
+    KeypadMinus,
+    KeypadMultiply,
+    KeypadDivide,
+    KeypadEquals,
+    KeypadEnter,
+    KeypadClear,
+    CapsLock,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -106,7 +233,24 @@ pub enum Action {
     FocusRight,
     FocusUp,
     FocusDown,
+    SwapLeft,
+    SwapRight,
+    SwapUp,
+    SwapDown,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Grow(Resize),
+    Shrink(Resize),
+    Nudge(Nudge),
+    /// Shorthand for `{ enter_mode = "resize" }`
+    ResizeMode,
+    /// Enter a sticky mode from `[modes]` (or a built-in one)
+    EnterMode(String),
     Minimize,
+    /// Close the window, like clicking its close button
+    Close,
     Maximize,
     Fullscreen,
     Center,
@@ -115,9 +259,99 @@ pub enum Action {
     MoveMonitorUp,
     MoveMonitorDown,
     Place(String),
+    MoveToDisplay(DisplayTarget),
     #[serde(rename = "tile")]
     Tile,
     Select,
+    /// Label the windows on screen and focus the one whose label is typed
+    Hints,
+    /// Open the window picker as a command palette
+    Palette,
+    /// Open the window picker listing windows and installed apps to launch
+    Launcher,
+    Undo,
+    Redo,
+    Restore,
+}
+
+/// What a key does to the focused row of the window picker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PickerAction {
+    Close,
+    Minimize,
+    /// Move the window to the display the picker is shown on
+    MoveHere,
+    Place(String),
+}
+
+impl PickerAction {
+    /// Short description for the picker's key hints
+    pub fn label(&self) -> String {
+        match self {
+            PickerAction::Close => "close".to_string(),
+            PickerAction::Minimize => "minimize".to_string(),
+            PickerAction::MoveHere => "move here".to_string(),
+            PickerAction::Place(name) => format!("place {}", name),
+        }
+    }
+}
+
+/// How the window picker orders windows within each monitor group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PickerOrder {
+    /// By app name, then title
+    #[default]
+    Alphabetical,
+    /// Most recently used first, as tracked by Pixie
+    Recent,
+}
+
+/// Which dimensions `grow`/`shrink` change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeAxis {
+    Width,
+    Height,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Resize {
+    #[serde(default)]
+    pub axis: ResizeAxis,
+    /// Length expression like `"50px"` or `"5%"` (of the screen axis)
+    #[serde(default = "default_resize_step")]
+    pub by: String,
+}
+
+impl Default for Resize {
+    fn default() -> Self {
+        Self {
+            axis: ResizeAxis::default(),
+            by: default_resize_step(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Nudge {
+    pub direction: Direction,
+    /// Length expression like `"20px"` or `"2%"` (of the screen axis)
+    #[serde(default = "default_nudge_step")]
+    pub by: String,
+}
+
+impl Nudge {
+    /// A nudge by the default step
+    pub fn towards(direction: Direction) -> Self {
+        Self {
+            direction,
+            by: default_nudge_step(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -130,6 +364,76 @@ pub struct Placement {
     pub width: Option<String>,
     #[serde(default)]
     pub height: Option<String>,
+    #[serde(default)]
+    pub right: Option<String>,
+    #[serde(default)]
+    pub bottom: Option<String>,
+    #[serde(default)]
+    pub aspect_ratio: Option<String>,
+    #[serde(default)]
+    pub grid: Option<String>,
+    #[serde(default)]
+    pub cell: Option<String>,
+    #[serde(default)]
+    pub span: Option<String>,
+    /// Display to place the window on instead of its current one
+    #[serde(default)]
+    pub display: Option<DisplayTarget>,
+}
+
+/// A display referred to by its 1-based number in display order, or by (part of) its name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DisplayTarget {
+    Index(usize),
+    Name(String),
+}
+
+/// A named placement: either a single placement, or a list of variants that repeated
+/// invocations on the same window cycle through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PlacementSpec {
+    Single(Box<Placement>),
+    Cycle(Vec<Placement>),
+}
+
+impl PlacementSpec {
+    pub fn variants(&self) -> &[Placement] {
+        match self {
+            PlacementSpec::Single(placement) => std::slice::from_ref(placement.as_ref()),
+            PlacementSpec::Cycle(variants) => variants,
+        }
+    }
+}
+
+/// A sticky layer of keybinds: once entered, its keys keep running their actions until
+/// Escape or until no key was pressed for `timeout` seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mode {
+    /// Keys without the `leader+` prefix, like `"left"` or `"shift+h"`
+    #[serde(default)]
+    pub keybinds: HashMap<String, Action>,
+
+    /// Seconds of inactivity before the mode exits on its own. `0` waits for Escape.
+    #[serde(default = "default_mode_timeout")]
+    pub timeout: u64,
+}
+
+impl Mode {
+    pub fn parsed_keybinds(&self) -> Vec<KeybindEntry> {
+        self.keybinds
+            .iter()
+            .filter_map(|(key, action)| {
+                parse_leader_key(key)
+                    .ok()
+                    .map(|(modifiers, code)| KeybindEntry {
+                        keybind: Keybind::Direct { modifiers, code },
+                        action: action.clone(),
+                    })
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -143,6 +447,30 @@ pub enum Keybind {
     },
 }
 
+impl Keybind {
+    /// Whether a single key press can trigger both keybinds.
+    pub fn overlaps(&self, other: &Keybind) -> bool {
+        match (self, other) {
+            (Keybind::LeaderPrefixed { code: a }, Keybind::LeaderPrefixed { code: b }) => a == b,
+            (
+                Keybind::Direct {
+                    modifiers: a,
+                    code: code_a,
+                },
+                Keybind::Direct {
+                    modifiers: b,
+                    code: code_b,
+                },
+            ) => {
+                code_a == code_b
+                    && a.unwrap_or(Modifiers::empty())
+                        .overlaps(b.unwrap_or(Modifiers::empty()))
+            }
+            _ => false,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct KeybindEntry {
     pub keybind: Keybind,
@@ -160,11 +488,60 @@ pub struct Config {
     #[serde(default = "default_timeout")]
     pub timeout: u64,
 
+    /// Longest press, and longest pause between presses, still counted as a double tap
+    /// for `double_tap:` leaders; how long a `hold:` leader is held before it activates
+    #[serde(default = "default_leader_tap_interval_ms")]
+    pub leader_tap_interval_ms: u64,
+
     #[serde(default)]
     pub keybinds: HashMap<String, Action>,
 
     #[serde(default)]
-    pub placements: HashMap<String, Placement>,
+    pub placements: HashMap<String, PlacementSpec>,
+
+    /// Repeating a cycling placement on the same window within this many milliseconds
+    /// advances to its next variant. `0` disables cycling.
+    #[serde(default = "default_placement_cycle_timeout_ms")]
+    pub placement_cycle_timeout_ms: u64,
+
+    /// What directional focus does when there is no window in the requested direction
+    #[serde(default)]
+    pub focus_wrap: FocusWrap,
+
+    /// Skip windows hidden behind others when focusing in a direction
+    #[serde(default = "default_focus_skip_occluded")]
+    pub focus_skip_occluded: bool,
+
+    /// How far `move_*` actions move a window: `"edge"` to snap to the next screen or window
+    /// edge, or a length like `"50px"` or `"10%"`
+    #[serde(default = "default_move_step")]
+    pub move_step: String,
+
+    /// How much arrow keys resize (and shift+arrows move) the window in resize mode
+    #[serde(default = "default_resize_step")]
+    pub resize_step: String,
+
+    /// Match keybind keys and slot letters by the character they type in the current
+    /// keyboard layout (`"character"`) or by their US ANSI position (`"position"`)
+    #[serde(default)]
+    pub key_matching: KeyMatching,
+
+    /// Names for modifier combinations usable in keybinds, like `nav = "ctrl+alt"` for
+    /// `"nav+h"`
+    #[serde(default)]
+    pub modifier_groups: HashMap<String, String>,
+
+    /// Named sticky modes, entered with `{ enter_mode = "name" }`
+    #[serde(default)]
+    pub modes: HashMap<String, Mode>,
+
+    /// Single characters mapped to actions on the focused window picker row, on top of the
+    /// built-in ones
+    #[serde(default)]
+    pub picker_keybinds: HashMap<String, PickerAction>,
+
+    #[serde(default)]
+    pub picker_order: PickerOrder,
 
     #[serde(default = "default_menubar_icon")]
     pub menubar_icon: bool,
@@ -181,6 +558,34 @@ fn default_timeout() -> u64 {
     2
 }
 
+fn default_leader_tap_interval_ms() -> u64 {
+    300
+}
+
+fn default_placement_cycle_timeout_ms() -> u64 {
+    1500
+}
+
+fn default_focus_skip_occluded() -> bool {
+    true
+}
+
+fn default_move_step() -> String {
+    "edge".to_string()
+}
+
+fn default_resize_step() -> String {
+    "5%".to_string()
+}
+
+fn default_nudge_step() -> String {
+    "20px".to_string()
+}
+
+fn default_mode_timeout() -> u64 {
+    10
+}
+
 fn default_menubar_icon() -> bool {
     true
 }
@@ -191,8 +596,19 @@ impl Default for Config {
             leader_key: default_leader_key(),
             autostart: false,
             timeout: default_timeout(),
+            leader_tap_interval_ms: default_leader_tap_interval_ms(),
             keybinds: HashMap::new(),
             placements: HashMap::new(),
+            placement_cycle_timeout_ms: default_placement_cycle_timeout_ms(),
+            focus_wrap: FocusWrap::default(),
+            focus_skip_occluded: default_focus_skip_occluded(),
+            move_step: default_move_step(),
+            resize_step: default_resize_step(),
+            key_matching: KeyMatching::default(),
+            modifier_groups: HashMap::new(),
+            modes: HashMap::new(),
+            picker_keybinds: HashMap::new(),
+            picker_order: PickerOrder::default(),
             menubar_icon: default_menubar_icon(),
             menubar_active_color: None,
         }
@@ -212,11 +628,58 @@ impl Config {
         }
     }
 
+    /// Check that modifier group names don't shadow a built-in modifier and that groups
+    /// only list built-in modifiers, not other groups.
+    pub fn check_modifier_groups(&self) -> Result<()> {
+        let mut names: Vec<&String> = self.modifier_groups.keys().collect();
+        names.sort();
+        for name in names {
+            if parse_modifier(&name.trim().to_lowercase()).is_ok() {
+                return Err(PixieError::Config(format!(
+                    "Modifier group \"{}\" has the name of a built-in modifier",
+                    name
+                )));
+            }
+            for part in self.modifier_groups[name].split('+') {
+                if parse_modifier(&part.trim().to_lowercase()).is_err() {
+                    return Err(PixieError::Config(format!(
+                        "Modifier group \"{}\": \"{}\" is not a built-in modifier (groups can't use other groups)",
+                        name,
+                        part.trim()
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace modifier group names in `key` with the modifiers they stand for.
+    pub fn expand_modifier_groups(&self, key: &str) -> String {
+        let parts: Vec<&str> = key.split('+').collect();
+        let last = parts.len() - 1;
+        parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                let group = self
+                    .modifier_groups
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(part.trim()));
+                match group {
+                    Some((_, modifiers)) if i != last => modifiers.as_str(),
+                    _ => part,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
     pub fn parsed_keybinds(&self) -> Vec<KeybindEntry> {
         self.keybinds
             .iter()
             .filter_map(|(key, action)| {
-                Self::parse_keybind(key).ok().map(|keybind| KeybindEntry {
+                let key = self.expand_modifier_groups(key);
+                Self::parse_keybind(&key).ok().map(|keybind| KeybindEntry {
                     keybind,
                     action: action.clone(),
                 })
@@ -224,11 +687,134 @@ impl Config {
             .collect()
     }
 
-    pub fn get_placements(&self) -> HashMap<String, Placement> {
+    pub fn focus_strategy(&self) -> FocusStrategy {
+        FocusStrategy {
+            wrap: self.focus_wrap,
+            skip_occluded: self.focus_skip_occluded,
+        }
+    }
+
+    pub fn get_placements(&self) -> HashMap<String, PlacementSpec> {
         let mut placements = builtin_placements();
         placements.extend(self.placements.clone());
         placements
     }
+
+    /// All modes, with modifier groups in their keys already expanded.
+    pub fn get_modes(&self) -> HashMap<String, Mode> {
+        let mut modes = builtin_modes(&self.resize_step);
+        modes.extend(self.modes.clone());
+        for mode in modes.values_mut() {
+            mode.keybinds = mode
+                .keybinds
+                .drain()
+                .map(|(key, action)| (self.expand_modifier_groups(&key), action))
+                .collect();
+        }
+        modes
+    }
+
+    /// Window picker keys and their actions, sorted by key. Keys that aren't a single
+    /// character or that the picker already uses are left out.
+    pub fn get_picker_keybinds(&self) -> Vec<(char, PickerAction)> {
+        let mut keybinds = builtin_picker_keybinds();
+        keybinds.extend(
+            self.picker_keybinds
+                .iter()
+                .filter_map(|(key, action)| picker_key(key).map(|key| (key, action.clone()))),
+        );
+        let mut keybinds: Vec<_> = keybinds.into_iter().collect();
+        keybinds.sort_by_key(|(key, _)| *key);
+        keybinds
+    }
+
+    /// Describe every pair of keybinds that the same key press would trigger, including
+    /// keybinds that shadow the leader key and overlapping keys within a mode.
+    pub fn keybind_conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        let mut check = |scope: &str, keys: Vec<(String, Keybind)>| {
+            for (i, (first, a)) in keys.iter().enumerate() {
+                for (second, b) in &keys[i + 1..] {
+                    if a.overlaps(b) {
+                        conflicts.push(format!(
+                            "{}\"{}\" and \"{}\" are triggered by the same key",
+                            scope, first, second
+                        ));
+                    }
+                }
+            }
+        };
+
+        let mut keys = parsed_keys(self, &self.keybinds);
+        let leader = parse_leader(&self.expand_modifier_groups(&self.leader_key));
+        if let Ok(LeaderKey::Chord { modifiers, code }) = leader {
+            keys.insert(
+                0,
+                (
+                    "leader key".to_string(),
+                    Keybind::Direct { modifiers, code },
+                ),
+            );
+        }
+        check("", keys);
+
+        let mut modes: Vec<_> = self.get_modes().into_iter().collect();
+        modes.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, mode) in modes {
+            check(
+                &format!("Mode '{}': ", name),
+                parsed_keys(self, &mode.keybinds),
+            );
+        }
+
+        conflicts
+    }
+
+    /// Describe every `[picker_keybinds]` key that [`Config::get_picker_keybinds`] leaves
+    /// out, sorted by key.
+    pub fn ignored_picker_keybinds(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self.picker_keybinds.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .filter(|key| picker_key(key).is_none())
+            .map(|key| {
+                if key.chars().count() == 1 {
+                    format!(
+                        "Picker key \"{}\" is reserved by the picker and was ignored",
+                        key
+                    )
+                } else {
+                    format!(
+                        "Picker key \"{}\" is not a single character and was ignored",
+                        key
+                    )
+                }
+            })
+            .collect()
+    }
+}
+
+/// The character of a `[picker_keybinds]` key, if it is one the picker leaves free.
+fn picker_key(key: &str) -> Option<char> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(key), None) if !crate::picker::RESERVED_KEYS.contains(&key) => Some(key),
+        _ => None,
+    }
+}
+
+/// The valid keys of `keybinds` with their parsed form, sorted by key.
+fn parsed_keys(config: &Config, keybinds: &HashMap<String, Action>) -> Vec<(String, Keybind)> {
+    let mut keys: Vec<(String, Keybind)> = keybinds
+        .keys()
+        .filter_map(|key| {
+            Config::parse_keybind(&config.expand_modifier_groups(key))
+                .ok()
+                .map(|keybind| (key.clone(), keybind))
+        })
+        .collect();
+    keys.sort_by(|a, b| a.0.cmp(&b.0));
+    keys
 }
 
 pub fn config_path() -> PathBuf {
@@ -284,141 +870,216 @@ pub fn load() -> Result<Config> {
     let path = config_path();
 
     match fs::read_to_string(&path) {
-        Ok(content) => toml::from_str(&content).map_err(|e| {
-            PixieError::Config(format!(
-                "Failed to parse config file at {:?}:\n  {}",
-                path, e
-            ))
-        }),
+        Ok(content) => {
+            let config: Config = toml::from_str(&content).map_err(|e| {
+                PixieError::Config(format!(
+                    "Failed to parse config file at {:?}:\n  {}",
+                    path, e
+                ))
+            })?;
+            config.check_modifier_groups()?;
+            Ok(config)
+        }
         Err(_) => Ok(Config::default()),
     }
 }
 
-pub fn builtin_placements() -> HashMap<String, Placement> {
+/// Widths (or heights) that the builtin half placements cycle through.
+const HALF_THIRD_TWO_THIRDS: [&str; 3] = ["50%", "calc(100% / 3)", "calc(200% / 3)"];
+
+fn size_cycle(placement: impl Fn(&str) -> Placement) -> PlacementSpec {
+    PlacementSpec::Cycle(
+        HALF_THIRD_TWO_THIRDS
+            .iter()
+            .map(|size| placement(size))
+            .collect(),
+    )
+}
+
+pub fn builtin_picker_keybinds() -> HashMap<char, PickerAction> {
+    HashMap::from([
+        ('x', PickerAction::Close),
+        ('m', PickerAction::Minimize),
+        ('h', PickerAction::MoveHere),
+        ('[', PickerAction::Place("left".to_string())),
+        (']', PickerAction::Place("right".to_string())),
+    ])
+}
+
+pub fn builtin_placements() -> HashMap<String, PlacementSpec> {
     let mut placements = HashMap::new();
 
     placements.insert(
         "left".to_string(),
-        Placement {
+        size_cycle(|width| Placement {
             left: Some("0%".to_string()),
-            width: Some("50%".to_string()),
+            width: Some(width.to_string()),
             height: Some("100%".to_string()),
-            top: None,
-        },
+            ..Default::default()
+        }),
     );
 
     placements.insert(
         "right".to_string(),
-        Placement {
-            left: Some("50%".to_string()),
-            width: Some("50%".to_string()),
+        size_cycle(|width| Placement {
+            right: Some("0%".to_string()),
+            width: Some(width.to_string()),
             height: Some("100%".to_string()),
-            top: None,
-        },
+            ..Default::default()
+        }),
     );
 
     placements.insert(
         "top".to_string(),
-        Placement {
+        size_cycle(|height| Placement {
             top: Some("0%".to_string()),
+            height: Some(height.to_string()),
             width: Some("100%".to_string()),
-            height: Some("50%".to_string()),
-            left: None,
-        },
+            ..Default::default()
+        }),
     );
 
     placements.insert(
         "bottom".to_string(),
-        Placement {
-            top: Some("50%".to_string()),
+        size_cycle(|height| Placement {
+            bottom: Some("0%".to_string()),
+            height: Some(height.to_string()),
             width: Some("100%".to_string()),
-            height: Some("50%".to_string()),
-            left: None,
-        },
+            ..Default::default()
+        }),
     );
 
     placements.insert(
         "top_left".to_string(),
-        Placement {
+        PlacementSpec::Single(Box::new(Placement {
             top: Some("0%".to_string()),
             left: Some("0%".to_string()),
             width: Some("50%".to_string()),
             height: Some("50%".to_string()),
-        },
+            ..Default::default()
+        })),
     );
 
     placements.insert(
         "top_right".to_string(),
-        Placement {
+        PlacementSpec::Single(Box::new(Placement {
             top: Some("0%".to_string()),
             left: Some("50%".to_string()),
             width: Some("50%".to_string()),
             height: Some("50%".to_string()),
-        },
+            ..Default::default()
+        })),
     );
 
     placements.insert(
         "bottom_left".to_string(),
-        Placement {
+        PlacementSpec::Single(Box::new(Placement {
             top: Some("50%".to_string()),
             left: Some("0%".to_string()),
             width: Some("50%".to_string()),
             height: Some("50%".to_string()),
-        },
+            ..Default::default()
+        })),
     );
 
     placements.insert(
         "bottom_right".to_string(),
-        Placement {
+        PlacementSpec::Single(Box::new(Placement {
             top: Some("50%".to_string()),
             left: Some("50%".to_string()),
             width: Some("50%".to_string()),
             height: Some("50%".to_string()),
-        },
+            ..Default::default()
+        })),
     );
 
     placements.insert(
         "center".to_string(),
-        Placement {
+        PlacementSpec::Single(Box::new(Placement {
             top: Some("center".to_string()),
             left: Some("center".to_string()),
-            width: None,
-            height: None,
-        },
+            ..Default::default()
+        })),
     );
 
     placements
 }
 
-pub fn parse_percentage(s: &str) -> Result<f64> {
-    let s = s.trim();
-    if !s.ends_with('%') {
-        return Err(PixieError::Config(format!(
-            "Invalid percentage format: {}",
-            s
-        )));
-    }
-
-    let num_str = &s[..s.len() - 1];
-    let num: f64 = num_str
-        .parse()
-        .map_err(|e| PixieError::Config(format!("Invalid percentage number: {}", e)))?;
-
-    Ok(num / 100.0)
+/// The `resize` mode: arrows resize the focused window by `step`, shift+arrows move it.
+pub fn builtin_modes(step: &str) -> HashMap<String, Mode> {
+    let resize = |axis, grow| {
+        let resize = Resize {
+            axis,
+            by: step.to_string(),
+        };
+        if grow {
+            Action::Grow(resize)
+        } else {
+            Action::Shrink(resize)
+        }
+    };
+    let nudge = |direction| {
+        Action::Nudge(Nudge {
+            direction,
+            by: step.to_string(),
+        })
+    };
+
+    let keybinds = HashMap::from([
+        ("left".to_string(), resize(ResizeAxis::Width, false)),
+        ("right".to_string(), resize(ResizeAxis::Width, true)),
+        ("up".to_string(), resize(ResizeAxis::Height, true)),
+        ("down".to_string(), resize(ResizeAxis::Height, false)),
+        ("shift+left".to_string(), nudge(Direction::Left)),
+        ("shift+right".to_string(), nudge(Direction::Right)),
+        ("shift+up".to_string(), nudge(Direction::Up)),
+        ("shift+down".to_string(), nudge(Direction::Down)),
+    ]);
+
+    HashMap::from([(
+        "resize".to_string(),
+        Mode {
+            keybinds,
+            timeout: default_mode_timeout(),
+        },
+    )])
 }
 
-pub fn parse_position_value(s: &str, screen_size: f64, window_size: f64) -> Result<f64> {
-    let s = s.trim();
-    if s == "center" {
-        return Ok((screen_size - window_size) / 2.0);
+/// Parse a `leader_key` setting: a chord, `double_tap:<modifier key>` or `hold:<modifier key>`.
+pub fn parse_leader(key: &str) -> Result<LeaderKey> {
+    let key_lower = key.trim().to_lowercase();
+
+    if let Some(rest) = key_lower.strip_prefix("double_tap:") {
+        Ok(LeaderKey::DoubleTap(parse_modifier_key(rest.trim())?))
+    } else if let Some(rest) = key_lower.strip_prefix("hold:") {
+        Ok(LeaderKey::Hold(parse_modifier_key(rest.trim())?))
+    } else {
+        let (modifiers, code) = parse_leader_key(key)?;
+        Ok(LeaderKey::Chord { modifiers, code })
     }
-    let pct = parse_percentage(s)?;
-    Ok(pct * screen_size)
 }
 
-pub fn parse_size_value(s: &str, screen_size: f64) -> Result<f64> {
-    let pct = parse_percentage(s)?;
-    Ok(pct * screen_size)
+fn parse_modifier_key(s: &str) -> Result<ModifierKey> {
+    match s {
+        "left_cmd" | "left_super" => Ok(ModifierKey::LeftCmd),
+        "right_cmd" | "right_super" => Ok(ModifierKey::RightCmd),
+        "left_alt" | "left_option" => Ok(ModifierKey::LeftAlt),
+        "right_alt" | "right_option" => Ok(ModifierKey::RightAlt),
+        "left_shift" => Ok(ModifierKey::LeftShift),
+        "right_shift" => Ok(ModifierKey::RightShift),
+        "left_ctrl" | "left_control" => Ok(ModifierKey::LeftCtrl),
+        "right_ctrl" | "right_control" => Ok(ModifierKey::RightCtrl),
+        // Caps lock toggles on every press, so the system state would flip with each use
+        "capslock" | "caps_lock" => Err(PixieError::Config(
+            "Caps lock can't be a double_tap or hold leader because every press toggles it. \
+             Remap it to F18 with `hidutil property --set '{\"UserKeyMapping\":[{\
+             \"HIDKeyboardModifierMappingSrc\":0x700000039,\
+             \"HIDKeyboardModifierMappingDst\":0x70000006D}]}'` and use leader_key = \"f18\""
+                .to_string(),
+        )),
+        "fn" => Ok(ModifierKey::Fn),
+        _ => Err(PixieError::Config(format!("Unknown modifier key: {}", s))),
+    }
 }
 
 pub fn parse_leader_key(key: &str) -> Result<(Option<Modifiers>, KeyCode)> {
@@ -461,16 +1122,27 @@ pub fn parse_leader_key(key: &str) -> Result<(Option<Modifiers>, KeyCode)> {
 
 fn parse_modifier(s: &str) -> Result<Modifiers> {
     match s {
+        "hyper" => Ok(Modifiers::SUPER | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT),
+        "meh" => Ok(Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT),
         "cmd" | "super" => Ok(Modifiers::SUPER),
         "alt" | "option" => Ok(Modifiers::ALT),
         "shift" => Ok(Modifiers::SHIFT),
         "ctrl" | "control" => Ok(Modifiers::CONTROL),
+        "lcmd" | "lsuper" => Ok(Modifiers::LEFT_SUPER),
+        "rcmd" | "rsuper" => Ok(Modifiers::RIGHT_SUPER),
+        "lalt" | "loption" => Ok(Modifiers::LEFT_ALT),
+        "ralt" | "roption" => Ok(Modifiers::RIGHT_ALT),
+        "lshift" => Ok(Modifiers::LEFT_SHIFT),
+        "rshift" => Ok(Modifiers::RIGHT_SHIFT),
+        "lctrl" | "lcontrol" => Ok(Modifiers::LEFT_CONTROL),
+        "rctrl" | "rcontrol" => Ok(Modifiers::RIGHT_CONTROL),
         _ => Err(PixieError::Config(format!("Unknown modifier: {}", s))),
     }
 }
 
 fn special_key_to_code(s: &str) -> Option<KeyCode> {
-    match s.to_lowercase().as_str() {
+    // `page_up` and `pageup` both work
+    match s.to_lowercase().replace('_', "").as_str() {
         "space" => Some(KeyCode::Space),
         "escape" | "esc" => Some(KeyCode::Escape),
         "enter" | "return" => Some(KeyCode::Enter),
@@ -486,6 +1158,27 @@ fn special_key_to_code(s: &str) -> Option<KeyCode> {
         "down" => Some(KeyCode::ArrowDown),
         "left" => Some(KeyCode::ArrowLeft),
         "right" => Some(KeyCode::ArrowRight),
+        "grave" | "backtick" => Some(KeyCode::Grave),
+        "section" => Some(KeyCode::Section),
+        "capslock" => Some(KeyCode::CapsLock),
+        "keypad0" => Some(KeyCode::Keypad0),
+        "keypad1" => Some(KeyCode::Keypad1),
+        "keypad2" => Some(KeyCode::Keypad2),
+        "keypad3" => Some(KeyCode::Keypad3),
+        "keypad4" => Some(KeyCode::Keypad4),
+        "keypad5" => Some(KeyCode::Keypad5),
+        "keypad6" => Some(KeyCode::Keypad6),
+        "keypad7" => Some(KeyCode::Keypad7),
+        "keypad8" => Some(KeyCode::Keypad8),
+        "keypad9" => Some(KeyCode::Keypad9),
+        "keypaddecimal" | "keypadperiod" => Some(KeyCode::KeypadDecimal),
+        "keypadplus" => Some(KeyCode::KeypadPlus),
+        "keypadminus" => Some(KeyCode::KeypadMinus),
+        "keypadmultiply" => Some(KeyCode::KeypadMultiply),
+        "keypaddivide" => Some(KeyCode::KeypadDivide),
+        "keypadequals" => Some(KeyCode::KeypadEquals),
+        "keypadenter" => Some(KeyCode::KeypadEnter),
+        "keypadclear" => Some(KeyCode::KeypadClear),
         _ => None,
     }
 }
@@ -500,8 +1193,8 @@ fn parse_key_code_with_shift(s: &str) -> Result<(KeyCode, bool)> {
         return Ok((code, false));
     }
 
-    if s.len() == 1 {
-        let c = s.chars().next().unwrap();
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
         if c.is_ascii_lowercase() {
             return char_to_code(c).map(|code| (code, false));
         }
@@ -539,6 +1232,10 @@ fn parse_key_code_with_shift(s: &str) -> Result<(KeyCode, bool)> {
             '*' => Ok((KeyCode::Digit8, true)),
             '(' => Ok((KeyCode::Digit9, true)),
             ')' => Ok((KeyCode::Digit0, true)),
+            '`' => Ok((KeyCode::Grave, false)),
+            '~' => Ok((KeyCode::Grave, true)),
+            '§' => Ok((KeyCode::Section, false)),
+            '±' => Ok((KeyCode::Section, true)),
             _ => Err(PixieError::Config(format!("Unknown key: {}", s))),
         };
     }
@@ -552,6 +1249,7 @@ fn parse_key_code_with_shift(s: &str) -> Result<(KeyCode, bool)> {
         "greater" | "gt" => return Ok((KeyCode::Period, true)),
         "question" => return Ok((KeyCode::Slash, true)),
         "bang" => return Ok((KeyCode::Digit1, true)),
+        "tilde" => return Ok((KeyCode::Grave, true)),
         _ => {}
     }
 
@@ -624,6 +1322,14 @@ fn function_key_to_code(s: &str) -> Result<KeyCode> {
         "F10" => Ok(KeyCode::F10),
         "F11" => Ok(KeyCode::F11),
         "F12" => Ok(KeyCode::F12),
+        "F13" => Ok(KeyCode::F13),
+        "F14" => Ok(KeyCode::F14),
+        "F15" => Ok(KeyCode::F15),
+        "F16" => Ok(KeyCode::F16),
+        "F17" => Ok(KeyCode::F17),
+        "F18" => Ok(KeyCode::F18),
+        "F19" => Ok(KeyCode::F19),
+        "F20" => Ok(KeyCode::F20),
         _ => Err(PixieError::Config(format!("Invalid function key: {}", s))),
     }
 }
@@ -686,6 +1392,399 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_display_targets() {
+        let config: Config = toml::from_str(
+            r#"
+            [keybinds]
+            "leader+1" = { move_to_display = 1 }
+            "leader+2" = { move_to_display = "LG UltraFine" }
+
+            [placements.slack]
+            display = "Built-in"
+            left = "67%"
+            width = "33%"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.keybinds["leader+1"],
+            Action::MoveToDisplay(DisplayTarget::Index(1))
+        );
+        assert_eq!(
+            config.keybinds["leader+2"],
+            Action::MoveToDisplay(DisplayTarget::Name("LG UltraFine".to_string()))
+        );
+        assert_eq!(
+            config.placements["slack"].variants()[0].display,
+            Some(DisplayTarget::Name("Built-in".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_resize_actions_with_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            [keybinds]
+            "leader+=" = { grow = {} }
+            "leader+-" = { shrink = { axis = "width", by = "40px" } }
+            "leader+l" = { nudge = { direction = "right" } }
+            "leader+r" = "resize_mode"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.keybinds["leader+="],
+            Action::Grow(Resize {
+                axis: ResizeAxis::Both,
+                by: "5%".to_string()
+            })
+        );
+        assert_eq!(
+            config.keybinds["leader+-"],
+            Action::Shrink(Resize {
+                axis: ResizeAxis::Width,
+                by: "40px".to_string()
+            })
+        );
+        assert_eq!(
+            config.keybinds["leader+l"],
+            Action::Nudge(Nudge {
+                direction: Direction::Right,
+                by: "20px".to_string()
+            })
+        );
+        assert_eq!(config.keybinds["leader+r"], Action::ResizeMode);
+    }
+
+    #[test]
+    fn parses_modes_and_keeps_builtin_resize_mode() {
+        let config: Config = toml::from_str(
+            r#"
+            resize_step = "30px"
+
+            [keybinds]
+            "leader+m" = { enter_mode = "move" }
+
+            [modes.move]
+            timeout = 0
+
+            [modes.move.keybinds]
+            "h" = "move_left"
+            "shift+l" = "move_right"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.keybinds["leader+m"],
+            Action::EnterMode("move".to_string())
+        );
+
+        let modes = config.get_modes();
+        let move_mode = &modes["move"];
+        assert_eq!(move_mode.timeout, 0);
+        let mut keybinds: Vec<_> = move_mode
+            .parsed_keybinds()
+            .into_iter()
+            .map(|entry| (entry.keybind, entry.action))
+            .collect();
+        keybinds.sort_by_key(|(_, action)| format!("{:?}", action));
+        assert_eq!(
+            keybinds,
+            vec![
+                (
+                    Keybind::Direct {
+                        modifiers: None,
+                        code: KeyCode::KeyH
+                    },
+                    Action::MoveLeft
+                ),
+                (
+                    Keybind::Direct {
+                        modifiers: Some(Modifiers::SHIFT),
+                        code: KeyCode::KeyL
+                    },
+                    Action::MoveRight
+                ),
+            ]
+        );
+
+        let resize_mode = &modes["resize"];
+        assert_eq!(resize_mode.timeout, 10);
+        assert_eq!(
+            resize_mode.keybinds["right"],
+            Action::Grow(Resize {
+                axis: ResizeAxis::Width,
+                by: "30px".to_string()
+            })
+        );
+        assert_eq!(resize_mode.parsed_keybinds().len(), 8);
+    }
+
+    #[test]
+    fn parses_tap_and_hold_leaders() {
+        assert_eq!(
+            parse_leader("double_tap:right_cmd").unwrap(),
+            LeaderKey::DoubleTap(ModifierKey::RightCmd)
+        );
+        assert_eq!(
+            parse_leader("Hold:Left_Ctrl").unwrap(),
+            LeaderKey::Hold(ModifierKey::LeftCtrl)
+        );
+        assert!(
+            parse_leader("hold:capslock")
+                .unwrap_err()
+                .to_string()
+                .contains("leader_key = \"f18\"")
+        );
+        assert_eq!(
+            parse_leader("cmd+shift+a").unwrap(),
+            LeaderKey::Chord {
+                modifiers: Some(Modifiers::SUPER | Modifiers::SHIFT),
+                code: KeyCode::KeyA
+            }
+        );
+        assert!(
+            parse_leader("double_tap:cmd")
+                .unwrap_err()
+                .to_string()
+                .contains("Unknown modifier key: cmd")
+        );
+    }
+
+    #[test]
+    fn parses_sided_modifiers() {
+        assert_eq!(
+            Config::parse_keybind("ralt+e").unwrap(),
+            Keybind::Direct {
+                modifiers: Some(Modifiers::RIGHT_ALT),
+                code: KeyCode::KeyE
+            }
+        );
+        assert_eq!(
+            Config::parse_keybind("lcmd+rshift+k").unwrap(),
+            Keybind::Direct {
+                modifiers: Some(Modifiers::LEFT_SUPER | Modifiers::RIGHT_SHIFT),
+                code: KeyCode::KeyK
+            }
+        );
+    }
+
+    #[test]
+    fn parses_extended_keys() {
+        let parse = |key| parse_key_code_with_shift(key).unwrap();
+
+        assert_eq!(parse("f18"), (KeyCode::F18, false));
+        assert_eq!(parse("F20"), (KeyCode::F20, false));
+        assert_eq!(parse("keypad7"), (KeyCode::Keypad7, false));
+        assert_eq!(parse("keypad_plus"), (KeyCode::KeypadPlus, false));
+        assert_eq!(parse("keypadenter"), (KeyCode::KeypadEnter, false));
+        assert_eq!(parse("`"), (KeyCode::Grave, false));
+        assert_eq!(parse("~"), (KeyCode::Grave, true));
+        assert_eq!(parse("§"), (KeyCode::Section, false));
+        assert_eq!(parse("±"), (KeyCode::Section, true));
+        assert_eq!(parse("caps_lock"), (KeyCode::CapsLock, false));
+        assert!(parse_key_code_with_shift("volume_up").is_err());
+        assert!(parse_key_code_with_shift("f21").is_err());
+        assert_eq!(
+            Config::parse_keybind("hyper+f13").unwrap(),
+            Keybind::Direct {
+                modifiers: Some(
+                    Modifiers::SUPER | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT
+                ),
+                code: KeyCode::F13
+            }
+        );
+    }
+
+    #[test]
+    fn sided_modifiers_only_match_their_key() {
+        let left_alt = Modifiers::ALT | Modifiers::LEFT_ALT;
+        let right_alt = Modifiers::ALT | Modifiers::RIGHT_ALT;
+
+        assert!(Modifiers::RIGHT_ALT.matches(right_alt));
+        assert!(!Modifiers::RIGHT_ALT.matches(left_alt));
+        assert!(Modifiers::ALT.matches(left_alt));
+        assert!(Modifiers::ALT.matches(right_alt));
+
+        // Other modifiers must not be down for an exact match, but may be for held_in
+        let with_cmd = right_alt | Modifiers::SUPER | Modifiers::LEFT_SUPER;
+        assert!(!Modifiers::RIGHT_ALT.matches(with_cmd));
+        assert!(Modifiers::RIGHT_ALT.held_in(with_cmd));
+        assert!((Modifiers::RIGHT_ALT | Modifiers::SUPER).matches(with_cmd));
+        assert!(!Modifiers::RIGHT_SUPER.held_in(with_cmd));
+
+        // Both sides of the same modifier
+        let both = Modifiers::LEFT_SHIFT | Modifiers::RIGHT_SHIFT;
+        assert!(both.matches(Modifiers::SHIFT | both));
+        assert!(!both.matches(Modifiers::SHIFT | Modifiers::LEFT_SHIFT));
+        assert!(Modifiers::empty().matches(Modifiers::empty()));
+        assert!(!Modifiers::empty().matches(Modifiers::SHIFT));
+    }
+
+    #[test]
+    fn merges_picker_keybinds_with_builtins() {
+        let config: Config = toml::from_str(
+            r#"
+            [picker_keybinds]
+            x = "minimize"
+            c = { place = "center" }
+            j = "close"
+            xx = "close"
+            "#,
+        )
+        .unwrap();
+
+        let keybinds = config.get_picker_keybinds();
+        assert_eq!(
+            keybinds,
+            vec![
+                ('[', PickerAction::Place("left".into())),
+                (']', PickerAction::Place("right".into())),
+                ('c', PickerAction::Place("center".into())),
+                ('h', PickerAction::MoveHere),
+                ('m', PickerAction::Minimize),
+                ('x', PickerAction::Minimize),
+            ]
+        );
+        assert_eq!(
+            config.ignored_picker_keybinds(),
+            vec![
+                "Picker key \"j\" is reserved by the picker and was ignored",
+                "Picker key \"xx\" is not a single character and was ignored",
+            ]
+        );
+        assert!(config.keybind_conflicts().is_empty());
+    }
+
+    #[test]
+    fn parses_hyper_meh_and_modifier_groups() {
+        let config: Config = toml::from_str(
+            r#"
+            [modifier_groups]
+            nav = "ctrl+alt"
+            Win = "rcmd+lshift"
+
+            [keybinds]
+            "hyper+h" = "focus_left"
+            "meh+l" = "focus_right"
+            "nav+k" = "focus_up"
+            "win+j" = "focus_down"
+            "#,
+        )
+        .unwrap();
+
+        let mut keybinds: Vec<_> = config
+            .parsed_keybinds()
+            .into_iter()
+            .map(|entry| (entry.action, entry.keybind))
+            .collect();
+        keybinds.sort_by_key(|(action, _)| format!("{:?}", action));
+        let direct = |modifiers, code| Keybind::Direct {
+            modifiers: Some(modifiers),
+            code,
+        };
+        assert_eq!(
+            keybinds,
+            vec![
+                (
+                    Action::FocusDown,
+                    direct(
+                        Modifiers::RIGHT_SUPER | Modifiers::LEFT_SHIFT,
+                        KeyCode::KeyJ
+                    )
+                ),
+                (
+                    Action::FocusLeft,
+                    direct(
+                        Modifiers::SUPER | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT,
+                        KeyCode::KeyH
+                    )
+                ),
+                (
+                    Action::FocusRight,
+                    direct(
+                        Modifiers::CONTROL | Modifiers::ALT | Modifiers::SHIFT,
+                        KeyCode::KeyL
+                    )
+                ),
+                (
+                    Action::FocusUp,
+                    direct(Modifiers::CONTROL | Modifiers::ALT, KeyCode::KeyK)
+                ),
+            ]
+        );
+        // The last part is always the key, even if it names a group
+        assert_eq!(config.expand_modifier_groups("cmd+nav"), "cmd+nav");
+        assert!(config.check_modifier_groups().is_ok());
+    }
+
+    #[test]
+    fn rejects_modifier_groups_shadowing_or_nesting() {
+        let check = |groups: &str| {
+            toml::from_str::<Config>(&format!("[modifier_groups]\n{}", groups))
+                .unwrap()
+                .check_modifier_groups()
+                .map_err(|e| e.to_string())
+        };
+
+        let err = check("Hyper = \"ctrl+alt\"").unwrap_err();
+        assert!(
+            err.contains("\"Hyper\" has the name of a built-in modifier"),
+            "{}",
+            err
+        );
+        let err = check("nav = \"ctrl+alt\"\nwin = \"nav+cmd\"").unwrap_err();
+        assert!(
+            err.contains("\"nav\" is not a built-in modifier"),
+            "{}",
+            err
+        );
+        assert!(check("nav = \"ctrl + lalt\"").is_ok());
+    }
+
+    #[test]
+    fn reports_keybind_conflicts() {
+        let config: Config = toml::from_str(
+            r#"
+            leader_key = "meh+space"
+
+            [modifier_groups]
+            nav = "ctrl+alt"
+
+            [keybinds]
+            "hyper+h" = "focus_left"
+            "cmd+ctrl+alt+shift+h" = "maximize"
+            "nav+k" = "focus_up"
+            "ctrl+alt+shift+space" = "tile"
+            "alt+e" = "center"
+            "ralt+e" = "maximize"
+            "lalt+f" = "center"
+            "ralt+f" = "maximize"
+            "leader+m" = "minimize"
+
+            [modes.move.keybinds]
+            "h" = "move_left"
+            "lshift+h" = "move_left"
+            "shift+h" = "move_right"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.keybind_conflicts(),
+            vec![
+                "\"leader key\" and \"ctrl+alt+shift+space\" are triggered by the same key",
+                "\"alt+e\" and \"ralt+e\" are triggered by the same key",
+                "\"cmd+ctrl+alt+shift+h\" and \"hyper+h\" are triggered by the same key",
+                "Mode 'move': \"lshift+h\" and \"shift+h\" are triggered by the same key",
+            ]
+        );
+    }
+
     #[test]
     fn launch_agent_plist_uses_runtime_binary_path() {
         let plist = render_launch_agent_plist(Path::new("/tmp/Pixie & Tools/pixie"));
@@ -0,0 +1,496 @@
+//! Directional focus: picking the window to the left/right/above/below another one.
+//!
+//! Windows are given front-to-back, in CG window list order. Candidates are scored by the
+//! distance from the focused window's edge and by how much of them overlaps it across the
+//! direction of travel; only the visible part of a candidate counts, so windows buried under
+//! others lose to visible ones and fully hidden windows are skipped. When nothing is found, the
+//! search can wrap to the opposite edge of the screen or continue on the next display.
+
+use serde::{Deserialize, Serialize};
+
+use crate::geometry::Rect;
+
+/// Candidates with less than this fraction of their area visible are treated as hidden.
+const MIN_VISIBLE_FRACTION: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// What directional focus does when there is no window in the requested direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusWrap {
+    /// Do nothing.
+    #[default]
+    None,
+    /// Continue from the opposite edge of the same screen.
+    Screen,
+    /// Continue on the next display in that direction, cycling around at the last one.
+    Display,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusStrategy {
+    pub wrap: FocusWrap,
+    /// Weigh candidates by their visible area and skip fully hidden ones.
+    pub skip_occluded: bool,
+}
+
+impl Default for FocusStrategy {
+    fn default() -> Self {
+        Self {
+            wrap: FocusWrap::None,
+            skip_occluded: true,
+        }
+    }
+}
+
+/// Pick the window in `direction` from `windows[from]`.
+///
+/// `windows` are front-to-back; `screens` are the display frames. Returns an index into
+/// `windows`.
+pub fn pick_window(
+    windows: &[Rect],
+    from: usize,
+    screens: &[Rect],
+    direction: Direction,
+    strategy: FocusStrategy,
+) -> Option<usize> {
+    let origin = windows[from];
+    let visible: Vec<f64> = (0..windows.len())
+        .map(|index| {
+            if strategy.skip_occluded {
+                visible_fraction(windows[index], &windows[..index])
+            } else {
+                1.0
+            }
+        })
+        .collect();
+    let candidates: Vec<usize> = (0..windows.len())
+        .filter(|&index| index != from && visible[index] >= MIN_VISIBLE_FRACTION)
+        .collect();
+
+    let best = |origin: Rect, screen: Option<usize>| {
+        candidates
+            .iter()
+            .filter(|&&index| {
+                screen
+                    .is_none_or(|screen| screen_index_for(windows[index], screens) == Some(screen))
+            })
+            .filter_map(|&index| {
+                direction_score(origin, windows[index], visible[index], direction)
+                    .map(|score| (score, index))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)))
+            .map(|(_, index)| index)
+    };
+
+    if let Some(index) = best(origin, None) {
+        return Some(index);
+    }
+
+    let current = screen_index_for(origin, screens)?;
+    let target = match strategy.wrap {
+        FocusWrap::None => return None,
+        FocusWrap::Screen => current,
+        FocusWrap::Display => next_screen(screens, current, direction),
+    };
+    let screen = screens[target];
+
+    // Search again as if the window sat just outside the near edge of the target screen
+    let mut wrapped = origin;
+    match direction {
+        Direction::Left => wrapped.x = screen.x + screen.width,
+        Direction::Right => wrapped.x = screen.x - origin.width,
+        Direction::Up => wrapped.y = screen.y + screen.height,
+        Direction::Down => wrapped.y = screen.y - origin.height,
+    }
+    if target != current {
+        match direction {
+            Direction::Left | Direction::Right => {
+                wrapped.y = screen.y + (origin.y - screens[current].y).clamp(0.0, screen.height);
+            }
+            Direction::Up | Direction::Down => {
+                wrapped.x = screen.x + (origin.x - screens[current].x).clamp(0.0, screen.width);
+            }
+        }
+    }
+
+    best(wrapped, Some(target))
+}
+
+/// Score `other` as a target in `direction` from `from`; lower is better.
+fn direction_score(from: Rect, other: Rect, visible: f64, direction: Direction) -> Option<f64> {
+    // The candidate must extend past the current window in the target direction. This allows
+    // partially overlapping windows as long as they extend further in that direction.
+    let extends_past = match direction {
+        Direction::Left => other.x < from.x,
+        Direction::Right => other.x + other.width > from.x + from.width,
+        Direction::Up => other.y < from.y,
+        Direction::Down => other.y + other.height > from.y + from.height,
+    };
+    if !extends_past {
+        return None;
+    }
+
+    let (distance, overlap) = match direction {
+        Direction::Left => (
+            from.x - (other.x + other.width),
+            overlap_1d(from.y, from.height, other.y, other.height),
+        ),
+        Direction::Right => (
+            other.x - (from.x + from.width),
+            overlap_1d(from.y, from.height, other.y, other.height),
+        ),
+        Direction::Up => (
+            from.y - (other.y + other.height),
+            overlap_1d(from.x, from.width, other.x, other.width),
+        ),
+        Direction::Down => (
+            other.y - (from.y + from.height),
+            overlap_1d(from.x, from.width, other.x, other.width),
+        ),
+    };
+
+    // Overlapping windows (negative distance) are visually adjacent
+    Some(distance.max(0.0) - overlap * visible * 100.0)
+}
+
+fn overlap_1d(a: f64, a_len: f64, b: f64, b_len: f64) -> f64 {
+    ((a + a_len).min(b + b_len) - a.max(b)).max(0.0)
+}
+
+fn intersection(a: Rect, b: Rect) -> Option<Rect> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+    (right > x && bottom > y).then(|| Rect::new(x, y, right - x, bottom - y))
+}
+
+/// Fraction of `rect` not covered by any of the `above` rects.
+fn visible_fraction(rect: Rect, above: &[Rect]) -> f64 {
+    let area = rect.width * rect.height;
+    if area <= 0.0 {
+        return 0.0;
+    }
+
+    let covers: Vec<Rect> = above
+        .iter()
+        .filter_map(|other| intersection(rect, *other))
+        .collect();
+    if covers.is_empty() {
+        return 1.0;
+    }
+
+    // Split the rect along every covering edge and sum the cells no cover contains
+    let mut xs = vec![rect.x, rect.x + rect.width];
+    let mut ys = vec![rect.y, rect.y + rect.height];
+    for cover in &covers {
+        xs.extend([cover.x, cover.x + cover.width]);
+        ys.extend([cover.y, cover.y + cover.height]);
+    }
+    for edges in [&mut xs, &mut ys] {
+        edges.sort_by(f64::total_cmp);
+        edges.dedup();
+    }
+
+    let mut visible = 0.0;
+    for x in xs.windows(2) {
+        for y in ys.windows(2) {
+            let (mid_x, mid_y) = ((x[0] + x[1]) / 2.0, (y[0] + y[1]) / 2.0);
+            let covered = covers.iter().any(|cover| {
+                mid_x > cover.x
+                    && mid_x < cover.x + cover.width
+                    && mid_y > cover.y
+                    && mid_y < cover.y + cover.height
+            });
+            if !covered {
+                visible += (x[1] - x[0]) * (y[1] - y[0]);
+            }
+        }
+    }
+
+    visible / area
+}
+
+/// Index of the screen containing the center of `rect`, or else the nearest one.
+fn screen_index_for(rect: Rect, screens: &[Rect]) -> Option<usize> {
+    let center_x = rect.x + rect.width / 2.0;
+    let center_y = rect.y + rect.height / 2.0;
+    let contains = |screen: &Rect| {
+        center_x >= screen.x
+            && center_x < screen.x + screen.width
+            && center_y >= screen.y
+            && center_y < screen.y + screen.height
+    };
+    let distance = |screen: &Rect| {
+        (center_x - (screen.x + screen.width / 2.0)).powi(2)
+            + (center_y - (screen.y + screen.height / 2.0)).powi(2)
+    };
+
+    screens.iter().position(contains).or_else(|| {
+        (0..screens.len()).min_by(|&a, &b| distance(&screens[a]).total_cmp(&distance(&screens[b])))
+    })
+}
+
+/// The nearest screen in `direction`, or the farthest one on the opposite side to cycle around.
+fn next_screen(screens: &[Rect], current: usize, direction: Direction) -> usize {
+    let center = |screen: &Rect| {
+        (
+            screen.x + screen.width / 2.0,
+            screen.y + screen.height / 2.0,
+        )
+    };
+    let (from_x, from_y) = center(&screens[current]);
+    // Signed distance along the direction of travel
+    let ahead = |screen: &Rect| {
+        let (x, y) = center(screen);
+        match direction {
+            Direction::Left => from_x - x,
+            Direction::Right => x - from_x,
+            Direction::Up => from_y - y,
+            Direction::Down => y - from_y,
+        }
+    };
+
+    let others = || (0..screens.len()).filter(move |&index| index != current);
+    others()
+        .filter(|&index| ahead(&screens[index]) > 1.0)
+        .min_by(|&a, &b| ahead(&screens[a]).total_cmp(&ahead(&screens[b])))
+        .or_else(|| {
+            others()
+                .filter(|&index| ahead(&screens[index]) < -1.0)
+                .min_by(|&a, &b| ahead(&screens[a]).total_cmp(&ahead(&screens[b])))
+        })
+        .unwrap_or(current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAPTOP: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 1200.0,
+        height: 800.0,
+    };
+    const EXTERNAL: Rect = Rect {
+        x: 1200.0,
+        y: 0.0,
+        width: 1600.0,
+        height: 1000.0,
+    };
+
+    fn pick(windows: &[Rect], direction: Direction, strategy: FocusStrategy) -> Option<usize> {
+        pick_window(windows, 0, &[LAPTOP, EXTERNAL], direction, strategy)
+    }
+
+    fn wrapping(wrap: FocusWrap) -> FocusStrategy {
+        FocusStrategy {
+            wrap,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn picks_nearest_window_in_each_direction() {
+        // 0 is focused in the middle of a 3x3 arrangement
+        let windows = [
+            Rect::new(400.0, 300.0, 400.0, 200.0),
+            Rect::new(0.0, 300.0, 380.0, 200.0),
+            Rect::new(820.0, 300.0, 380.0, 200.0),
+            Rect::new(400.0, 0.0, 400.0, 280.0),
+            Rect::new(400.0, 520.0, 400.0, 280.0),
+            Rect::new(0.0, 0.0, 380.0, 280.0),
+        ];
+        let strategy = FocusStrategy::default();
+
+        assert_eq!(pick(&windows, Direction::Left, strategy), Some(1));
+        assert_eq!(pick(&windows, Direction::Right, strategy), Some(2));
+        assert_eq!(pick(&windows, Direction::Up, strategy), Some(3));
+        assert_eq!(pick(&windows, Direction::Down, strategy), Some(4));
+    }
+
+    #[test]
+    fn prefers_window_aligned_with_focused_one() {
+        let windows = [
+            Rect::new(600.0, 300.0, 400.0, 300.0),
+            Rect::new(0.0, 0.0, 500.0, 250.0),
+            Rect::new(100.0, 300.0, 400.0, 300.0),
+        ];
+
+        assert_eq!(
+            pick(&windows, Direction::Left, FocusStrategy::default()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn skips_fully_occluded_windows() {
+        // 2 is the nearest window on the left but lies entirely under 1
+        let windows = [
+            Rect::new(600.0, 200.0, 400.0, 400.0),
+            Rect::new(0.0, 100.0, 550.0, 600.0),
+            Rect::new(300.0, 200.0, 280.0, 400.0),
+        ];
+        let strategy = FocusStrategy::default();
+
+        assert_eq!(pick(&windows, Direction::Left, strategy), Some(1));
+        assert_eq!(
+            pick(
+                &windows,
+                Direction::Left,
+                FocusStrategy {
+                    skip_occluded: false,
+                    ..strategy
+                }
+            ),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn penalizes_partially_occluded_windows() {
+        // 2 and 3 are equally good targets on the left, but most of 2 is covered by 1
+        let windows = [
+            Rect::new(800.0, 100.0, 300.0, 600.0),
+            Rect::new(100.0, 400.0, 600.0, 300.0),
+            Rect::new(400.0, 400.0, 350.0, 300.0),
+            Rect::new(400.0, 100.0, 350.0, 300.0),
+        ];
+        let strategy = FocusStrategy::default();
+
+        assert_eq!(pick(&windows, Direction::Left, strategy), Some(3));
+        assert_eq!(
+            pick(
+                &windows,
+                Direction::Left,
+                FocusStrategy {
+                    skip_occluded: false,
+                    ..strategy
+                }
+            ),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn measures_visible_fraction() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(visible_fraction(rect, &[]), 1.0);
+        assert_eq!(
+            visible_fraction(rect, &[Rect::new(50.0, 0.0, 100.0, 100.0)]),
+            0.5
+        );
+        assert_eq!(
+            visible_fraction(
+                rect,
+                &[
+                    Rect::new(0.0, 0.0, 60.0, 60.0),
+                    Rect::new(40.0, 40.0, 60.0, 60.0),
+                ]
+            ),
+            1.0 - 0.36 - 0.36 + 0.04
+        );
+        assert_eq!(
+            visible_fraction(rect, &[Rect::new(-10.0, -10.0, 200.0, 200.0)]),
+            0.0
+        );
+    }
+
+    #[test]
+    fn does_not_wrap_by_default() {
+        let windows = [
+            Rect::new(800.0, 100.0, 400.0, 600.0),
+            Rect::new(0.0, 100.0, 400.0, 600.0),
+        ];
+
+        assert_eq!(
+            pick(&windows, Direction::Right, FocusStrategy::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn wraps_to_opposite_edge_of_screen() {
+        let windows = [
+            Rect::new(800.0, 100.0, 400.0, 600.0),
+            Rect::new(400.0, 100.0, 400.0, 600.0),
+            Rect::new(0.0, 100.0, 400.0, 600.0),
+        ];
+        assert_eq!(
+            pick(&windows, Direction::Right, wrapping(FocusWrap::Screen)),
+            Some(2)
+        );
+
+        // Window 1 pokes past the left edge of the external display but sits on the laptop,
+        // so a screen wrap from the external display skips it
+        let windows = [
+            Rect::new(2400.0, 100.0, 400.0, 600.0),
+            Rect::new(1000.0, 100.0, 300.0, 600.0),
+            Rect::new(1600.0, 100.0, 400.0, 600.0),
+        ];
+        assert_eq!(
+            pick(&windows, Direction::Right, wrapping(FocusWrap::Screen)),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn wraps_to_next_display_cycling_around() {
+        let windows = [
+            Rect::new(2400.0, 100.0, 400.0, 600.0),
+            Rect::new(1200.0, 100.0, 400.0, 600.0),
+            Rect::new(800.0, 100.0, 400.0, 600.0),
+            Rect::new(0.0, 100.0, 400.0, 600.0),
+        ];
+
+        // Rightmost display has nothing further right: cycle to the leftmost display
+        assert_eq!(
+            pick(&windows, Direction::Right, wrapping(FocusWrap::Display)),
+            Some(3)
+        );
+
+        let laptop_windows = [
+            Rect::new(0.0, 100.0, 400.0, 600.0),
+            Rect::new(800.0, 100.0, 400.0, 600.0),
+            Rect::new(2400.0, 100.0, 400.0, 600.0),
+        ];
+        // Nothing left of the leftmost window: continue on the external display from the right
+        assert_eq!(
+            pick(
+                &laptop_windows,
+                Direction::Left,
+                wrapping(FocusWrap::Display)
+            ),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn wrap_with_single_display_stays_on_it() {
+        let windows = [
+            Rect::new(0.0, 500.0, 1200.0, 300.0),
+            Rect::new(0.0, 0.0, 1200.0, 300.0),
+        ];
+
+        assert_eq!(
+            pick_window(
+                &windows,
+                0,
+                &[LAPTOP],
+                Direction::Down,
+                wrapping(FocusWrap::Display)
+            ),
+            Some(1)
+        );
+    }
+}
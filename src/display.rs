@@ -0,0 +1,80 @@
+//! Stable display ordering and lookup by index or name.
+//!
+//! Displays are numbered from 1, left to right and then top to bottom by their origin, so the
+//! numbering only changes when the physical arrangement changes.
+
+use std::cmp::Ordering;
+
+use crate::config::DisplayTarget;
+use crate::geometry::Rect;
+
+/// Compare two display frames in the stable display order.
+pub fn compare_position(a: &Rect, b: &Rect) -> Ordering {
+    a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+}
+
+/// Find the index (into `names`, already in display order) of the display `target` refers to.
+///
+/// Names match case-insensitively, preferring an exact match over a substring match.
+pub fn find_display<S: AsRef<str>>(names: &[S], target: &DisplayTarget) -> Option<usize> {
+    match target {
+        DisplayTarget::Index(number) => number.checked_sub(1).filter(|&index| index < names.len()),
+        DisplayTarget::Name(name) => {
+            let wanted = name.trim().to_lowercase();
+            if wanted.is_empty() {
+                return None;
+            }
+            let names: Vec<String> = names
+                .iter()
+                .map(|name| name.as_ref().to_lowercase())
+                .collect();
+            names
+                .iter()
+                .position(|name| *name == wanted)
+                .or_else(|| names.iter().position(|name| name.contains(&wanted)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_left_to_right_then_top_to_bottom() {
+        let mut frames = [
+            Rect::new(1440.0, -1080.0, 1920.0, 1080.0),
+            Rect::new(0.0, 0.0, 1440.0, 900.0),
+            Rect::new(-1920.0, 0.0, 1920.0, 1080.0),
+            Rect::new(1440.0, 0.0, 1920.0, 1080.0),
+        ];
+        frames.sort_by(compare_position);
+
+        let origins: Vec<(f64, f64)> = frames.iter().map(|frame| (frame.x, frame.y)).collect();
+        assert_eq!(
+            origins,
+            vec![(-1920.0, 0.0), (0.0, 0.0), (1440.0, -1080.0), (1440.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn finds_display_by_number() {
+        let names = ["Built-in Retina Display", "LG UltraFine"];
+        assert_eq!(find_display(&names, &DisplayTarget::Index(1)), Some(0));
+        assert_eq!(find_display(&names, &DisplayTarget::Index(2)), Some(1));
+        assert_eq!(find_display(&names, &DisplayTarget::Index(0)), None);
+        assert_eq!(find_display(&names, &DisplayTarget::Index(3)), None);
+    }
+
+    #[test]
+    fn finds_display_by_name() {
+        let names = ["LG UltraFine 2", "Built-in Retina Display", "LG UltraFine"];
+        let by_name = |name: &str| find_display(&names, &DisplayTarget::Name(name.to_string()));
+
+        assert_eq!(by_name("lg ultrafine"), Some(2));
+        assert_eq!(by_name("built-in"), Some(1));
+        assert_eq!(by_name("UltraFine"), Some(0));
+        assert_eq!(by_name("Dell"), None);
+        assert_eq!(by_name(" "), None);
+    }
+}
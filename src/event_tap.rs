@@ -4,15 +4,26 @@ use core_graphics::event::{
     CGEventFlags, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement,
     CGEventType, EventField,
 };
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
-
-use crate::config::{Action, KeyCode, Keybind, KeybindEntry, Modifiers};
-use crate::ui::{PickerInput, is_window_picker_active, picker_input_from_keycode};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::{Action, KeyCode, Keybind, KeybindEntry, LeaderKey, ModifierKey, Modifiers};
+use crate::hints::HintInput;
+use crate::keymap::{keycode_to_letter, keycode_to_native};
+use crate::modifier_tap::{TapDetector, TapEvent, TapGesture};
+use crate::ui::{
+    PickerInput, hint_input_from_keycode, is_window_hints_active, is_window_picker_active,
+    picker_input_from_keycode,
+};
 
 pub static IS_LISTENING: AtomicBool = AtomicBool::new(false);
 static LEADER_MODIFIERS_ACTIVE: AtomicBool = AtomicBool::new(false);
 static PICKER_REPEAT_COUNTER: AtomicU8 = AtomicU8::new(0);
+/// Whether digits typed since the leader started a count prefix
+static COUNT_PENDING: AtomicBool = AtomicBool::new(false);
+/// Keybinds of the active sticky mode, if any
+static MODE_KEYBINDS: Mutex<Option<Vec<KeybindEntry>>> = Mutex::new(None);
 
 #[derive(Debug, Clone)]
 pub enum EventTapAction {
@@ -21,6 +32,21 @@ pub enum EventTapAction {
     ActionTriggered(Action),
     ArrowPressed(crate::accessibility::Direction),
     PickerInput(PickerInput),
+    /// A key typed while window hints are shown
+    HintInput(HintInput),
+    /// Escape pressed while a sticky mode is active
+    ModeEscape,
+    /// Digit of a count prefix typed after the leader
+    CountDigit(u32),
+    /// `.` typed after the leader
+    RepeatLast,
+    /// A `hold:` leader key was released before any key was typed
+    LeaderReleased,
+}
+
+/// Capture the keys of a sticky mode (`Some`) or stop capturing (`None`)
+pub fn set_mode_keybinds(keybinds: Option<Vec<KeybindEntry>>) {
+    *MODE_KEYBINDS.lock().unwrap() = keybinds;
 }
 
 pub struct EventTap {
@@ -29,13 +55,26 @@ pub struct EventTap {
 
 impl EventTap {
     pub fn new(
-        leader_modifiers: Modifiers,
-        leader_keycode: KeyCode,
+        leader: LeaderKey,
+        tap_interval: Duration,
         keybinds: Vec<KeybindEntry>,
         sender: tokio::sync::mpsc::UnboundedSender<EventTapAction>,
     ) -> Result<Self, String> {
-        let leader_flags = modifiers_to_cg_flags(leader_modifiers);
-        let leader_kc = keycode_to_native(leader_keycode);
+        let (leader_modifiers, leader_code, leader_tap) = match leader {
+            LeaderKey::Chord { modifiers, code } => {
+                (modifiers.unwrap_or(Modifiers::empty()), Some(code), None)
+            }
+            LeaderKey::DoubleTap(key) => (
+                Modifiers::empty(),
+                None,
+                Some(LeaderTap::new(key, TapGesture::DoubleTap, tap_interval)),
+            ),
+            LeaderKey::Hold(key) => (
+                Modifiers::empty(),
+                None,
+                Some(LeaderTap::new(key, TapGesture::Hold, tap_interval)),
+            ),
+        };
 
         let (ready_tx, ready_rx) = std::sync::mpsc::channel::<Result<Arc<CFRunLoop>, String>>();
 
@@ -46,8 +85,9 @@ impl EventTap {
                 let current = Arc::new(current);
 
                 let handler = EventHandler {
-                    leader_modifiers: leader_flags,
-                    leader_keycode: leader_kc,
+                    leader_modifiers,
+                    leader_code,
+                    leader_tap,
                     keybinds,
                     sender,
                 };
@@ -124,14 +164,38 @@ impl Drop for EventTap {
     }
 }
 
+/// A leader entered with a lone modifier key, watched through `FlagsChanged` events.
+struct LeaderTap {
+    key: ModifierKey,
+    keycode: i64,
+    detector: Mutex<TapDetector>,
+}
+
+impl LeaderTap {
+    fn new(key: ModifierKey, gesture: TapGesture, interval: Duration) -> Self {
+        LeaderTap {
+            key,
+            keycode: modifier_key_to_native(key),
+            detector: Mutex::new(TapDetector::new(gesture, interval)),
+        }
+    }
+}
+
 struct EventHandler {
-    leader_modifiers: CGEventFlags,
-    leader_keycode: i64,
+    leader_modifiers: Modifiers,
+    /// Key of a chord leader, matched in the current layout
+    leader_code: Option<KeyCode>,
+    leader_tap: Option<LeaderTap>,
     keybinds: Vec<KeybindEntry>,
     sender: tokio::sync::mpsc::UnboundedSender<EventTapAction>,
 }
 
 impl EventHandler {
+    fn is_leader_key(&self, keycode: i64) -> bool {
+        self.leader_code
+            .is_some_and(|code| keycode_to_native(code) == keycode)
+    }
+
     fn handle_event(
         &self,
         event_type: CGEventType,
@@ -145,13 +209,41 @@ impl EventHandler {
                 if is_window_picker_active() {
                     return;
                 }
-                let modifiers_active = flags.contains(self.leader_modifiers);
+                if keycode == keycode_to_native(KeyCode::CapsLock) {
+                    self.handle_caps_lock_keybind(flags);
+                    return;
+                }
+                if let Some(tap) = &self.leader_tap {
+                    self.handle_leader_tap(tap, keycode, flags);
+                    return;
+                }
+                let modifiers_active = self.leader_modifiers.held_in(held_modifiers(flags));
                 LEADER_MODIFIERS_ACTIVE.store(modifiers_active, Ordering::SeqCst);
             }
             CGEventType::KeyDown => {
+                if let Some(tap) = &self.leader_tap {
+                    let event = tap.detector.lock().unwrap().key_pressed(Instant::now());
+                    if event == Some(TapEvent::Activate) {
+                        self.activate_leader_tap(tap);
+                    }
+                }
                 let is_listening = IS_LISTENING.load(Ordering::SeqCst);
-                let is_leader_combo =
-                    flags.contains(self.leader_modifiers) && keycode == self.leader_keycode;
+                let is_leader_combo = self.leader_modifiers.held_in(held_modifiers(flags))
+                    && self.is_leader_key(keycode);
+                if is_window_hints_active() && !is_listening && !is_leader_combo {
+                    let has_shift = flags.contains(CGEventFlags::CGEventFlagShift);
+                    if let Some(input) = hint_input_from_keycode(keycode, has_shift) {
+                        if !is_autorepeat {
+                            let _ = self.sender.send(EventTapAction::HintInput(input));
+                        }
+                        event.set_type(CGEventType::Null);
+                    } else {
+                        let _ = self
+                            .sender
+                            .send(EventTapAction::HintInput(HintInput::Cancel));
+                    }
+                    return;
+                }
                 if is_window_picker_active() && !is_listening && !is_leader_combo {
                     let has_shift = flags.contains(CGEventFlags::CGEventFlagShift);
                     if let Some(input) = picker_input_from_keycode(keycode, has_shift) {
@@ -187,6 +279,26 @@ impl EventHandler {
                     return;
                 }
 
+                if !is_listening && !is_leader_combo {
+                    let mode_keybinds = MODE_KEYBINDS.lock().unwrap();
+                    if let Some(keybinds) = mode_keybinds.as_ref() {
+                        if keycode == keycode_to_native(KeyCode::Escape) {
+                            let _ = self.sender.send(EventTapAction::ModeEscape);
+                        } else if let Some(entry) = keybinds
+                            .iter()
+                            .find(|entry| direct_keybind_matches(&entry.keybind, keycode, flags))
+                        {
+                            tracing::trace!("mode action triggered: {:?}", entry.action);
+                            let _ = self
+                                .sender
+                                .send(EventTapAction::ActionTriggered(entry.action.clone()));
+                        }
+                        // Other keys are swallowed so they don't reach the focused app
+                        event.set_type(CGEventType::Null);
+                        return;
+                    }
+                }
+
                 if !is_listening && !is_autorepeat {
                     for entry in &self.keybinds {
                         if direct_keybind_matches(&entry.keybind, keycode, flags) {
@@ -201,12 +313,13 @@ impl EventHandler {
                 }
 
                 let mods_active = LEADER_MODIFIERS_ACTIVE.load(Ordering::SeqCst);
-                let is_leader_key = keycode == self.leader_keycode;
+                let is_leader_key = self.is_leader_key(keycode);
 
                 // Check if this is the leader key combo (modifiers + leader key pressed together)
                 if mods_active && is_leader_key && !is_listening {
                     tracing::trace!("leader combo detected (keycode={})", keycode);
                     IS_LISTENING.store(true, Ordering::SeqCst);
+                    COUNT_PENDING.store(false, Ordering::SeqCst);
                     let _ = self.sender.send(EventTapAction::LeaderPressed);
                     event.set_type(CGEventType::Null);
                     return;
@@ -214,6 +327,23 @@ impl EventHandler {
 
                 // Handle keys while in listening mode (after leader combo released)
                 if is_listening {
+                    // Digits start a count, unless bound to a leader action; once a count
+                    // started all digits extend it. A leading 0 is never a count.
+                    if let Some(digit) = keycode_to_digit(keycode) {
+                        let pending = COUNT_PENDING.load(Ordering::SeqCst);
+                        let bound = self.keybinds.iter().any(|entry| {
+                            matches!(&entry.keybind, Keybind::LeaderPrefixed { code }
+                                if keycode_to_native(*code) == keycode)
+                        });
+                        if pending || (!bound && digit != 0) {
+                            tracing::trace!("leader count digit: {}", digit);
+                            COUNT_PENDING.store(true, Ordering::SeqCst);
+                            let _ = self.sender.send(EventTapAction::CountDigit(digit));
+                            event.set_type(CGEventType::Null);
+                            return;
+                        }
+                    }
+
                     // Check for action keybinds
                     for entry in &self.keybinds {
                         if let Keybind::LeaderPrefixed { code } = &entry.keybind
@@ -229,6 +359,14 @@ impl EventHandler {
                         }
                     }
 
+                    if keycode == keycode_to_native(KeyCode::Period) {
+                        tracing::trace!("leader repeat triggered");
+                        let _ = self.sender.send(EventTapAction::RepeatLast);
+                        IS_LISTENING.store(false, Ordering::SeqCst);
+                        event.set_type(CGEventType::Null);
+                        return;
+                    }
+
                     if let Some(direction) = keycode_to_direction(keycode) {
                         tracing::trace!("leader direction triggered: {:?}", direction);
                         let _ = self.sender.send(EventTapAction::ArrowPressed(direction));
@@ -253,133 +391,130 @@ impl EventHandler {
     }
 }
 
-fn modifiers_to_cg_flags(modifiers: Modifiers) -> CGEventFlags {
-    let mut flags = CGEventFlags::empty();
-
-    if modifiers.contains(Modifiers::SUPER) {
-        flags.insert(CGEventFlags::CGEventFlagCommand);
-    }
-    if modifiers.contains(Modifiers::ALT) {
-        flags.insert(CGEventFlags::CGEventFlagAlternate);
-    }
-    if modifiers.contains(Modifiers::SHIFT) {
-        flags.insert(CGEventFlags::CGEventFlagShift);
+impl EventHandler {
+    /// Caps lock never sends a key down, only toggles its flag, so keybinds on it run from
+    /// the flag change. The toggle itself still reaches the system.
+    fn handle_caps_lock_keybind(&self, flags: CGEventFlags) {
+        let code = KeyCode::CapsLock;
+        let held = held_modifiers(flags);
+        let is_listening = IS_LISTENING.load(Ordering::SeqCst);
+        let action = self.keybinds.iter().find_map(|entry| {
+            let bound = match &entry.keybind {
+                Keybind::LeaderPrefixed { code: bound } => is_listening && *bound == code,
+                Keybind::Direct {
+                    modifiers,
+                    code: bound,
+                } => {
+                    !is_listening
+                        && *bound == code
+                        && modifiers.unwrap_or(Modifiers::empty()).matches(held)
+                }
+            };
+            bound.then(|| entry.action.clone())
+        });
+
+        if let Some(action) = action {
+            tracing::trace!("caps lock action triggered: {:?}", action);
+            IS_LISTENING.store(false, Ordering::SeqCst);
+            let _ = self.sender.send(EventTapAction::ActionTriggered(action));
+        }
     }
-    if modifiers.contains(Modifiers::CONTROL) {
-        flags.insert(CGEventFlags::CGEventFlagControl);
+
+    fn activate_leader_tap(&self, tap: &LeaderTap) {
+        if !IS_LISTENING.swap(true, Ordering::SeqCst) {
+            tracing::trace!("leader tap detected ({:?})", tap.key);
+            COUNT_PENDING.store(false, Ordering::SeqCst);
+            let _ = self.sender.send(EventTapAction::LeaderPressed);
+        }
     }
 
-    flags
+    fn handle_leader_tap(&self, tap: &LeaderTap, keycode: i64, flags: CGEventFlags) {
+        let mut detector = tap.detector.lock().unwrap();
+        if keycode != tap.keycode {
+            detector.interrupt();
+            return;
+        }
+
+        let event = detector.modifier_changed(modifier_key_held(tap.key, flags), Instant::now());
+
+        match event {
+            Some(TapEvent::Activate) => self.activate_leader_tap(tap),
+            Some(TapEvent::Release) => {
+                if IS_LISTENING.swap(false, Ordering::SeqCst) {
+                    let _ = self.sender.send(EventTapAction::LeaderReleased);
+                }
+            }
+            None => {}
+        }
+    }
 }
 
-fn keycode_to_native(code: KeyCode) -> i64 {
-    match code {
-        KeyCode::KeyA => 0,
-        KeyCode::KeyS => 1,
-        KeyCode::KeyD => 2,
-        KeyCode::KeyF => 3,
-        KeyCode::KeyH => 4,
-        KeyCode::KeyG => 5,
-        KeyCode::KeyZ => 6,
-        KeyCode::KeyX => 7,
-        KeyCode::KeyC => 8,
-        KeyCode::KeyV => 9,
-        KeyCode::KeyB => 11,
-        KeyCode::KeyQ => 12,
-        KeyCode::KeyW => 13,
-        KeyCode::KeyE => 14,
-        KeyCode::KeyR => 15,
-        KeyCode::KeyY => 16,
-        KeyCode::KeyT => 17,
-        KeyCode::Digit1 => 18,
-        KeyCode::Digit2 => 19,
-        KeyCode::Digit3 => 20,
-        KeyCode::Digit4 => 21,
-        KeyCode::Digit6 => 22,
-        KeyCode::Digit5 => 23,
-        KeyCode::Equal => 24,
-        KeyCode::Digit9 => 25,
-        KeyCode::Digit7 => 26,
-        KeyCode::Minus => 27,
-        KeyCode::Digit8 => 28,
-        KeyCode::Digit0 => 29,
-        KeyCode::BracketRight => 30,
-        KeyCode::KeyO => 31,
-        KeyCode::KeyU => 32,
-        KeyCode::BracketLeft => 33,
-        KeyCode::KeyI => 34,
-        KeyCode::KeyP => 35,
-        KeyCode::KeyL => 37,
-        KeyCode::KeyJ => 38,
-        KeyCode::KeyK => 40,
-        KeyCode::Quote => 39,
-        KeyCode::Semicolon => 41,
-        KeyCode::Backslash => 42,
-        KeyCode::Comma => 43,
-        KeyCode::Slash => 44,
-        KeyCode::KeyN => 45,
-        KeyCode::KeyM => 46,
-        KeyCode::Period => 47,
-        KeyCode::Space => 49,
-        KeyCode::Escape => 53,
-        KeyCode::F1 => 122,
-        KeyCode::F2 => 120,
-        KeyCode::F3 => 99,
-        KeyCode::F4 => 118,
-        KeyCode::F5 => 96,
-        KeyCode::F6 => 97,
-        KeyCode::F7 => 98,
-        KeyCode::F8 => 100,
-        KeyCode::F9 => 101,
-        KeyCode::F10 => 109,
-        KeyCode::F11 => 103,
-        KeyCode::F12 => 111,
-        KeyCode::Enter => 36,
-        KeyCode::Tab => 48,
-        KeyCode::Backspace => 51,
-        KeyCode::Delete => 117,
-        KeyCode::Insert => 114,
-        KeyCode::Home => 115,
-        KeyCode::End => 119,
-        KeyCode::PageUp => 116,
-        KeyCode::PageDown => 121,
-        KeyCode::ArrowLeft => 123,
-        KeyCode::ArrowRight => 124,
-        KeyCode::ArrowDown => 125,
-        KeyCode::ArrowUp => 126,
+fn modifier_key_to_native(key: ModifierKey) -> i64 {
+    match key {
+        ModifierKey::RightCmd => 54,
+        ModifierKey::LeftCmd => 55,
+        ModifierKey::LeftShift => 56,
+        ModifierKey::LeftAlt => 58,
+        ModifierKey::LeftCtrl => 59,
+        ModifierKey::RightShift => 60,
+        ModifierKey::RightAlt => 61,
+        ModifierKey::RightCtrl => 62,
+        ModifierKey::Fn => 63,
     }
 }
 
-fn keycode_to_letter(keycode: i64) -> Option<char> {
-    match keycode {
-        0 => Some('a'),
-        1 => Some('s'),
-        2 => Some('d'),
-        3 => Some('f'),
-        4 => Some('h'),
-        5 => Some('g'),
-        6 => Some('z'),
-        7 => Some('x'),
-        8 => Some('c'),
-        9 => Some('v'),
-        11 => Some('b'),
-        12 => Some('q'),
-        13 => Some('w'),
-        14 => Some('e'),
-        15 => Some('r'),
-        16 => Some('y'),
-        17 => Some('t'),
-        31 => Some('o'),
-        32 => Some('u'),
-        34 => Some('i'),
-        35 => Some('p'),
-        38 => Some('j'),
-        40 => Some('k'),
-        37 => Some('l'),
-        46 => Some('m'),
-        45 => Some('n'),
-        _ => None,
+/// Whether `key` is down, telling left and right apart
+fn modifier_key_held(key: ModifierKey, flags: CGEventFlags) -> bool {
+    let side = match key {
+        ModifierKey::LeftCmd => Modifiers::LEFT_SUPER,
+        ModifierKey::RightCmd => Modifiers::RIGHT_SUPER,
+        ModifierKey::LeftAlt => Modifiers::LEFT_ALT,
+        ModifierKey::RightAlt => Modifiers::RIGHT_ALT,
+        ModifierKey::LeftShift => Modifiers::LEFT_SHIFT,
+        ModifierKey::RightShift => Modifiers::RIGHT_SHIFT,
+        ModifierKey::LeftCtrl => Modifiers::LEFT_CONTROL,
+        ModifierKey::RightCtrl => Modifiers::RIGHT_CONTROL,
+        ModifierKey::Fn => return flags.contains(CGEventFlags::CGEventFlagSecondaryFn),
+    };
+    held_modifiers(flags).contains(side)
+}
+
+/// Device-dependent bits in the low word of the event flags (`NX_DEVICE*KEYMASK`), which
+/// say which physical modifier keys are down.
+const DEVICE_MODIFIER_BITS: [(u64, Modifiers); 8] = [
+    (0x0000_0001, Modifiers::LEFT_CONTROL),
+    (0x0000_0002, Modifiers::LEFT_SHIFT),
+    (0x0000_0004, Modifiers::RIGHT_SHIFT),
+    (0x0000_0008, Modifiers::LEFT_SUPER),
+    (0x0000_0010, Modifiers::RIGHT_SUPER),
+    (0x0000_0020, Modifiers::LEFT_ALT),
+    (0x0000_0040, Modifiers::RIGHT_ALT),
+    (0x0000_2000, Modifiers::RIGHT_CONTROL),
+];
+
+/// The modifiers down in `flags`: the side-agnostic flags plus the keys the device reported
+fn held_modifiers(flags: CGEventFlags) -> Modifiers {
+    let mut held = Modifiers::empty();
+
+    if flags.contains(CGEventFlags::CGEventFlagCommand) {
+        held.insert(Modifiers::SUPER);
+    }
+    if flags.contains(CGEventFlags::CGEventFlagAlternate) {
+        held.insert(Modifiers::ALT);
     }
+    if flags.contains(CGEventFlags::CGEventFlagShift) {
+        held.insert(Modifiers::SHIFT);
+    }
+    if flags.contains(CGEventFlags::CGEventFlagControl) {
+        held.insert(Modifiers::CONTROL);
+    }
+    for (bit, side) in DEVICE_MODIFIER_BITS {
+        if flags.bits() & bit != 0 {
+            held.insert(side);
+        }
+    }
+
+    held
 }
 
 fn keycode_to_direction(keycode: i64) -> Option<crate::accessibility::Direction> {
@@ -392,6 +527,24 @@ fn keycode_to_direction(keycode: i64) -> Option<crate::accessibility::Direction>
     }
 }
 
+fn keycode_to_digit(keycode: i64) -> Option<u32> {
+    [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ]
+    .iter()
+    .position(|&code| keycode_to_native(code) == keycode)
+    .map(|digit| digit as u32)
+}
+
 fn direct_keybind_matches(keybind: &Keybind, keycode: i64, flags: CGEventFlags) -> bool {
     let Keybind::Direct { modifiers, code } = keybind else {
         return false;
@@ -400,16 +553,7 @@ fn direct_keybind_matches(keybind: &Keybind, keycode: i64, flags: CGEventFlags)
         return false;
     }
 
-    direct_modifier_flags(flags)
-        == modifiers
-            .map(modifiers_to_cg_flags)
-            .unwrap_or_else(CGEventFlags::empty)
-}
-
-fn direct_modifier_flags(flags: CGEventFlags) -> CGEventFlags {
-    flags
-        & (CGEventFlags::CGEventFlagCommand
-            | CGEventFlags::CGEventFlagAlternate
-            | CGEventFlags::CGEventFlagShift
-            | CGEventFlags::CGEventFlagControl)
+    modifiers
+        .unwrap_or(Modifiers::empty())
+        .matches(held_modifiers(flags))
 }
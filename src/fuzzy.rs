@@ -0,0 +1,278 @@
+//! fzf-style fuzzy matching.
+//!
+//! Every query character has to appear in the text in order. Among all ways to line them up
+//! the best scoring one is picked: matches at word starts, camelCase humps and in consecutive
+//! runs score higher, gaps between matched characters cost a little. The constants follow
+//! fzf's v2 algorithm, so rankings feel familiar.
+
+const SCORE_MATCH: i32 = 16;
+const SCORE_GAP_START: i32 = -3;
+const SCORE_GAP_EXTENSION: i32 = -1;
+const BONUS_BOUNDARY: i32 = SCORE_MATCH / 2;
+const BONUS_BOUNDARY_WHITE: i32 = BONUS_BOUNDARY + 2;
+const BONUS_BOUNDARY_DELIMITER: i32 = BONUS_BOUNDARY + 1;
+const BONUS_NON_WORD: i32 = SCORE_MATCH / 2;
+const BONUS_CAMEL: i32 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i32 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// Char (not byte) indices of the matched characters, ascending
+    pub positions: Vec<usize>,
+}
+
+/// A query matched against several fields, like an app name and a window title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldsMatch {
+    pub score: i32,
+    /// Matched char indices per field, in the order the fields were given
+    pub positions: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    White,
+    NonWord,
+    Delimiter,
+    Lower,
+    Upper,
+    Number,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::White
+    } else if matches!(c, '/' | ',' | ':' | ';' | '|') {
+        CharClass::Delimiter
+    } else if c.is_numeric() {
+        CharClass::Number
+    } else if c.is_uppercase() {
+        CharClass::Upper
+    } else if c.is_alphabetic() {
+        CharClass::Lower
+    } else {
+        CharClass::NonWord
+    }
+}
+
+/// Bonus for matching a character of class `class` right after one of class `prev`
+fn bonus_for(prev: CharClass, class: CharClass) -> i32 {
+    let is_word = matches!(
+        class,
+        CharClass::Lower | CharClass::Upper | CharClass::Number
+    );
+    if is_word {
+        match prev {
+            CharClass::White => return BONUS_BOUNDARY_WHITE,
+            CharClass::Delimiter => return BONUS_BOUNDARY_DELIMITER,
+            CharClass::NonWord => return BONUS_BOUNDARY,
+            _ => {}
+        }
+    }
+    if (prev == CharClass::Lower && class == CharClass::Upper)
+        || (prev != CharClass::Number && class == CharClass::Number)
+    {
+        return BONUS_CAMEL;
+    }
+    match class {
+        CharClass::NonWord | CharClass::Delimiter => BONUS_NON_WORD,
+        CharClass::White => BONUS_BOUNDARY_WHITE,
+        _ => 0,
+    }
+}
+
+/// Score `query` against `text`, or `None` if the text doesn't contain it as a subsequence.
+///
+/// Matching ignores case unless the query has an uppercase character (smart case). An empty
+/// query matches everything with a score of 0.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let normalize = |c: char| {
+        if case_sensitive {
+            c
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
+    let query: Vec<char> = query.chars().map(normalize).collect();
+    let text: Vec<char> = text.chars().collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    // Cheap rejection before filling the tables
+    let mut remaining = query.iter().peekable();
+    for &c in &text {
+        if remaining.peek() == Some(&&normalize(c)) {
+            remaining.next();
+        }
+    }
+    if remaining.peek().is_some() {
+        return None;
+    }
+
+    let mut prev_class = CharClass::White;
+    let bonuses: Vec<i32> = text
+        .iter()
+        .map(|&c| {
+            let class = char_class(c);
+            let bonus = bonus_for(prev_class, class);
+            prev_class = class;
+            bonus
+        })
+        .collect();
+
+    // Best score with query[..=i] matched and query[i] at text[j], the bonus carried by the
+    // consecutive run it ends, and the column query[i - 1] was matched at.
+    let (n, m) = (query.len(), text.len());
+    let mut scores: Vec<Option<i32>> = vec![None; n * m];
+    let mut run_bonus = vec![0; n * m];
+    let mut from = vec![0; n * m];
+
+    for i in 0..n {
+        // Best previous-row cell at least two columns back, with the gap penalty up to `j`
+        let mut gapped: Option<(i32, usize)> = None;
+        for j in 0..m {
+            if i > 0 && j >= 2 {
+                gapped = gapped.map(|(score, k)| (score + SCORE_GAP_EXTENSION, k));
+                if let Some(prev) = scores[(i - 1) * m + j - 2] {
+                    let candidate = prev + SCORE_GAP_START;
+                    if gapped.is_none_or(|(score, _)| candidate > score) {
+                        gapped = Some((candidate, j - 2));
+                    }
+                }
+            }
+            if normalize(text[j]) != query[i] {
+                continue;
+            }
+
+            let bonus = bonuses[j];
+            let cell = i * m + j;
+            if i == 0 {
+                scores[cell] = Some(SCORE_MATCH + bonus * BONUS_FIRST_CHAR_MULTIPLIER);
+                run_bonus[cell] = bonus;
+                continue;
+            }
+
+            let consecutive = (j > 0).then(|| scores[cell - m - 1]).flatten().map(|prev| {
+                let bonus = bonus.max(run_bonus[cell - m - 1]).max(BONUS_CONSECUTIVE);
+                (prev + SCORE_MATCH + bonus, bonus, j - 1)
+            });
+            let after_gap = gapped.map(|(score, k)| (score + SCORE_MATCH + bonus, bonus, k));
+            let best = match (consecutive, after_gap) {
+                (Some(a), Some(b)) => Some(if b.0 > a.0 { b } else { a }),
+                (a, b) => a.or(b),
+            };
+            if let Some((score, bonus, k)) = best {
+                scores[cell] = Some(score);
+                run_bonus[cell] = bonus;
+                from[cell] = k;
+            }
+        }
+    }
+
+    let last_row = (n - 1) * m;
+    let (mut j, score) = (0..m)
+        .filter_map(|j| scores[last_row + j].map(|score| (j, score)))
+        .fold(None, |best: Option<(usize, i32)>, (j, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((j, score)),
+        })?;
+
+    let mut positions = vec![0; n];
+    for i in (0..n).rev() {
+        positions[i] = j;
+        j = from[i * m + j];
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+/// Match a query against several fields at once, as if they were joined by spaces.
+///
+/// Whitespace separates the query into terms that must all match, in any order; their
+/// scores add up.
+pub fn match_fields(query: &str, fields: &[&str]) -> Option<FieldsMatch> {
+    let joined = fields.join(" ");
+    let mut score = 0;
+    let mut joined_positions = Vec::new();
+    for term in query.split_whitespace() {
+        let matched = fuzzy_match(term, &joined)?;
+        score += matched.score;
+        joined_positions.extend(matched.positions);
+    }
+    joined_positions.sort_unstable();
+    joined_positions.dedup();
+
+    let mut positions = Vec::with_capacity(fields.len());
+    let mut start = 0;
+    for field in fields {
+        let end = start + field.chars().count();
+        positions.push(
+            joined_positions
+                .iter()
+                .filter(|&&position| position >= start && position < end)
+                .map(|position| position - start)
+                .collect(),
+        );
+        // Skip the joining space
+        start = end + 1;
+    }
+
+    Some(FieldsMatch { score, positions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, text: &str) -> i32 {
+        fuzzy_match(query, text).unwrap().score
+    }
+
+    #[test]
+    fn requires_the_query_in_order() {
+        assert!(fuzzy_match("xyz", "abc").is_none());
+        assert!(fuzzy_match("ba", "ab").is_none());
+        assert_eq!(
+            fuzzy_match("abc", "a_b_c").unwrap().positions,
+            vec![0, 2, 4]
+        );
+        assert_eq!(score("", "anything"), 0);
+    }
+
+    #[test]
+    fn uses_smart_case() {
+        assert!(fuzzy_match("term", "Terminal").is_some());
+        assert!(fuzzy_match("Term", "terminal").is_none());
+        assert!(fuzzy_match("Term", "Terminal").is_some());
+    }
+
+    #[test]
+    fn prefers_word_starts_and_runs() {
+        // The whole word beats the same letters inside another word
+        assert_eq!(
+            fuzzy_match("bar", "foobar bar").unwrap().positions,
+            vec![7, 8, 9]
+        );
+        assert!(score("term", "Terminal") > score("term", "tXeXrXm"));
+        assert!(score("fb", "foo bar") > score("fb", "afxbx"));
+        assert!(score("gh", "GitHub") > score("gh", "Spaghetti"));
+    }
+
+    #[test]
+    fn matches_terms_across_fields() {
+        let matched = match_fields("git saf", &["Safari", "GitHub - repo"]).unwrap();
+        assert_eq!(matched.positions, vec![vec![0, 1, 2], vec![0, 1, 2]]);
+        assert!(match_fields("saf zzz", &["Safari", "GitHub"]).is_none());
+
+        let empty = match_fields("", &["Safari", "GitHub"]).unwrap();
+        assert_eq!(empty.score, 0);
+        assert_eq!(empty.positions, vec![Vec::<usize>::new(), Vec::new()]);
+    }
+}
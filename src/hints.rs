@@ -0,0 +1,203 @@
+//! Vimium-style window hints: short labels to type for jumping to a window.
+//!
+//! Labels come from a small alphabet of easy to reach keys. No label is a prefix of another,
+//! so a label is chosen as soon as it is fully typed. Earlier windows get the shorter labels.
+
+use crate::geometry::Rect;
+
+/// Home row keys, in the order labels use them
+pub const HINT_ALPHABET: [char; 9] = ['a', 's', 'd', 'f', 'j', 'k', 'l', 'g', 'h'];
+
+/// Space kept between labels that would otherwise overlap
+const HINT_GAP: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintInput {
+    Char(char),
+    Backspace,
+    Cancel,
+}
+
+/// `count` labels over `alphabet`, none a prefix of another, shortest first.
+pub fn generate_labels(count: usize, alphabet: &[char]) -> Vec<String> {
+    if count == 0 || alphabet.is_empty() {
+        return Vec::new();
+    }
+    if alphabet.len() == 1 {
+        // Only one prefix-free label exists
+        return vec![alphabet[0].to_string()];
+    }
+
+    // Expand labels breadth first until enough unexpanded ones are left. An expanded label
+    // is dropped, so the remaining ones never prefix each other.
+    let mut labels = vec![String::new()];
+    let mut expanded = 0;
+    while labels.len() - expanded < count || labels.len() == 1 {
+        let prefix = labels[expanded].clone();
+        expanded += 1;
+        labels.extend(alphabet.iter().map(|ch| format!("{}{}", prefix, ch)));
+    }
+    labels.drain(..expanded);
+    labels.truncate(count);
+    labels
+}
+
+/// A label and the top-left corner it is drawn at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub label: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Label each frame and center its label on it, moving labels down where they would cover
+/// an earlier one. `label_size` is the (width, height) of a one-character label; longer
+/// labels grow by that width per character.
+pub fn place_hints(frames: &[Rect], alphabet: &[char], label_size: (f64, f64)) -> Vec<Hint> {
+    let (char_width, height) = label_size;
+    let mut placed: Vec<Rect> = Vec::new();
+
+    generate_labels(frames.len(), alphabet)
+        .into_iter()
+        .zip(frames)
+        .map(|(label, frame)| {
+            let width = char_width * label.chars().count() as f64;
+            let mut rect = Rect::new(
+                frame.x + (frame.width - width) / 2.0,
+                frame.y + (frame.height - height) / 2.0,
+                width,
+                height,
+            );
+            while let Some(covered) = placed.iter().find(|other| overlaps(&rect, other)) {
+                rect.y = covered.y + covered.height + HINT_GAP;
+            }
+            placed.push(rect);
+            Hint {
+                label,
+                x: rect.x,
+                y: rect.y,
+            }
+        })
+        .collect()
+}
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+/// What typing a key did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintOutcome {
+    /// The typed keys start one or more labels
+    Pending,
+    /// The typed keys are this label
+    Matched(usize),
+    /// No label starts with the key; it was not kept
+    NoMatch,
+}
+
+/// Tracks the keys typed so far against a set of labels.
+#[derive(Debug, Clone, Default)]
+pub struct HintMatcher {
+    pub labels: Vec<String>,
+    pub typed: String,
+}
+
+impl HintMatcher {
+    pub fn new(labels: Vec<String>) -> Self {
+        Self {
+            labels,
+            typed: String::new(),
+        }
+    }
+
+    pub fn input(&mut self, ch: char) -> HintOutcome {
+        self.typed.push(ch.to_ascii_lowercase());
+        if let Some(index) = self.labels.iter().position(|label| *label == self.typed) {
+            return HintOutcome::Matched(index);
+        }
+        if self
+            .labels
+            .iter()
+            .any(|label| label.starts_with(&self.typed))
+        {
+            return HintOutcome::Pending;
+        }
+        self.typed.pop();
+        HintOutcome::NoMatch
+    }
+
+    pub fn backspace(&mut self) {
+        self.typed.pop();
+    }
+
+    /// Whether the label at `index` can still be typed
+    pub fn is_candidate(&self, index: usize) -> bool {
+        self.labels
+            .get(index)
+            .is_some_and(|label| label.starts_with(&self.typed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_prefix_free(labels: &[String]) -> bool {
+        labels.iter().enumerate().all(|(i, a)| {
+            labels
+                .iter()
+                .enumerate()
+                .all(|(j, b)| i == j || !b.starts_with(a.as_str()))
+        })
+    }
+
+    #[test]
+    fn generates_prefix_free_labels() {
+        assert_eq!(generate_labels(3, &['a', 's', 'd']), vec!["a", "s", "d"]);
+        assert_eq!(
+            generate_labels(4, &['a', 's', 'd']),
+            vec!["s", "d", "aa", "as"]
+        );
+        assert_eq!(generate_labels(1, &['a', 's']), vec!["a"]);
+        assert!(generate_labels(0, &HINT_ALPHABET).is_empty());
+
+        for count in [1, 9, 10, 50, 81, 82, 300] {
+            let labels = generate_labels(count, &HINT_ALPHABET);
+            assert_eq!(labels.len(), count);
+            assert!(is_prefix_free(&labels), "{} labels", count);
+            // Shortest first
+            assert!(labels.windows(2).all(|w| w[0].len() <= w[1].len()));
+        }
+    }
+
+    #[test]
+    fn moves_overlapping_labels_down() {
+        let frame = Rect::new(0.0, 0.0, 200.0, 100.0);
+        let hints = place_hints(&[frame, frame, frame], &['a', 's', 'd'], (20.0, 20.0));
+        let positions: Vec<_> = hints.iter().map(|hint| (hint.x, hint.y)).collect();
+        assert_eq!(positions, vec![(90.0, 40.0), (90.0, 64.0), (90.0, 88.0)]);
+
+        let apart = place_hints(
+            &[frame, Rect::new(300.0, 0.0, 200.0, 100.0)],
+            &['a', 's'],
+            (20.0, 20.0),
+        );
+        assert_eq!((apart[1].x, apart[1].y), (390.0, 40.0));
+    }
+
+    #[test]
+    fn matches_typed_labels() {
+        let mut matcher = HintMatcher::new(generate_labels(4, &['a', 's', 'd']));
+        assert_eq!(matcher.input('x'), HintOutcome::NoMatch);
+        assert_eq!(matcher.input('A'), HintOutcome::Pending);
+        assert!(matcher.is_candidate(3));
+        assert!(!matcher.is_candidate(0));
+        matcher.backspace();
+        assert_eq!(matcher.input('s'), HintOutcome::Matched(0));
+
+        let mut matcher = HintMatcher::new(generate_labels(4, &['a', 's', 'd']));
+        matcher.input('a');
+        assert_eq!(matcher.input('s'), HintOutcome::Matched(3));
+    }
+}
@@ -0,0 +1,306 @@
+//! Undo/redo history for window frames changed by Pixie.
+//!
+//! Every frame change goes through [`record`]. Changes made inside [`batch`] are grouped so
+//! that a multi-window operation such as tiling is undone or redone as a single step.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use crate::geometry::Rect;
+
+/// A window identified by (pid, CGWindowID).
+pub type WindowKey = (i32, u32);
+
+/// Maximum number of undo steps kept.
+const HISTORY_CAPACITY: usize = 50;
+
+/// Maximum number of windows whose original frame is kept.
+const ORIGINAL_CAPACITY: usize = 200;
+
+static HISTORY: OnceLock<Mutex<GeometryHistory>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameChange {
+    pub window: WindowKey,
+    pub before: Rect,
+    pub after: Rect,
+}
+
+#[derive(Debug)]
+pub struct GeometryHistory {
+    undo: VecDeque<Vec<FrameChange>>,
+    redo: Vec<Vec<FrameChange>>,
+    pending: Vec<FrameChange>,
+    batch_depth: usize,
+    /// Frame of each window before Pixie first moved it, least recently moved first. Kept
+    /// apart from the steps so it outlives them.
+    original: VecDeque<(WindowKey, Rect)>,
+    capacity: usize,
+    original_capacity: usize,
+}
+
+impl GeometryHistory {
+    pub fn new(capacity: usize, original_capacity: usize) -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            pending: Vec::new(),
+            batch_depth: 0,
+            original: VecDeque::new(),
+            capacity,
+            original_capacity,
+        }
+    }
+
+    /// Record a frame change. Changes that don't move the window are ignored.
+    pub fn record(&mut self, change: FrameChange) {
+        if change.before == change.after {
+            return;
+        }
+
+        self.remember_original(change.window, change.before);
+
+        if self.batch_depth > 0 {
+            self.pending.push(change);
+        } else {
+            self.push_step(vec![change]);
+        }
+    }
+
+    pub fn begin_batch(&mut self) {
+        self.batch_depth += 1;
+    }
+
+    pub fn end_batch(&mut self) {
+        self.batch_depth = self.batch_depth.saturating_sub(1);
+        if self.batch_depth == 0 {
+            let step = std::mem::take(&mut self.pending);
+            self.push_step(step);
+        }
+    }
+
+    /// Take the most recent step and return the frames that revert it, in the order to apply them.
+    pub fn undo(&mut self) -> Option<Vec<(WindowKey, Rect)>> {
+        let step = self.undo.pop_back()?;
+        let frames = step
+            .iter()
+            .rev()
+            .map(|change| (change.window, change.before))
+            .collect();
+        self.redo.push(step);
+        Some(frames)
+    }
+
+    /// Take the most recently undone step and return the frames that reapply it.
+    pub fn redo(&mut self) -> Option<Vec<(WindowKey, Rect)>> {
+        let step = self.redo.pop()?;
+        let frames = step
+            .iter()
+            .map(|change| (change.window, change.after))
+            .collect();
+        self.undo.push_back(step);
+        Some(frames)
+    }
+
+    /// The frame a window had before Pixie first changed it.
+    pub fn original(&self, window: WindowKey) -> Option<Rect> {
+        self.original
+            .iter()
+            .find(|(key, _)| *key == window)
+            .map(|(_, frame)| *frame)
+    }
+
+    /// Keep `frame` as the window's original unless it already has one, and mark the window
+    /// as most recently moved, forgetting the least recently moved window when full.
+    fn remember_original(&mut self, window: WindowKey, frame: Rect) {
+        let frame = match self.original.iter().position(|(key, _)| *key == window) {
+            Some(index) => self.original.remove(index).map_or(frame, |(_, kept)| kept),
+            None => frame,
+        };
+        self.original.push_back((window, frame));
+        while self.original.len() > self.original_capacity {
+            self.original.pop_front();
+        }
+    }
+
+    fn push_step(&mut self, step: Vec<FrameChange>) {
+        if step.is_empty() {
+            return;
+        }
+
+        self.redo.clear();
+        self.undo.push_back(step);
+        while self.undo.len() > self.capacity {
+            self.undo.pop_front();
+        }
+    }
+}
+
+impl Default for GeometryHistory {
+    fn default() -> Self {
+        Self::new(HISTORY_CAPACITY, ORIGINAL_CAPACITY)
+    }
+}
+
+fn with_history<T>(f: impl FnOnce(&mut GeometryHistory) -> T) -> T {
+    let history = HISTORY.get_or_init(|| Mutex::new(GeometryHistory::default()));
+    let mut history = history.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut history)
+}
+
+/// Record a frame change in the global history.
+pub fn record(window: WindowKey, before: Rect, after: Rect) {
+    with_history(|history| {
+        history.record(FrameChange {
+            window,
+            before,
+            after,
+        })
+    });
+}
+
+/// Run `f`, grouping every frame change it records into one undo step.
+pub fn batch<T>(f: impl FnOnce() -> T) -> T {
+    with_history(GeometryHistory::begin_batch);
+    let result = f();
+    with_history(GeometryHistory::end_batch);
+    result
+}
+
+pub fn take_undo() -> Option<Vec<(WindowKey, Rect)>> {
+    with_history(GeometryHistory::undo)
+}
+
+pub fn take_redo() -> Option<Vec<(WindowKey, Rect)>> {
+    with_history(GeometryHistory::redo)
+}
+
+pub fn original_frame(window: WindowKey) -> Option<Rect> {
+    with_history(|history| history.original(window))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(window: WindowKey, before: f64, after: f64) -> FrameChange {
+        FrameChange {
+            window,
+            before: Rect::new(before, 0.0, 100.0, 100.0),
+            after: Rect::new(after, 0.0, 100.0, 100.0),
+        }
+    }
+
+    fn x_positions(frames: Vec<(WindowKey, Rect)>) -> Vec<(WindowKey, f64)> {
+        frames
+            .into_iter()
+            .map(|(window, rect)| (window, rect.x))
+            .collect()
+    }
+
+    #[test]
+    fn undo_and_redo_single_changes() {
+        let mut history = GeometryHistory::new(10, 10);
+        history.record(change((1, 1), 0.0, 10.0));
+        history.record(change((1, 1), 10.0, 20.0));
+
+        assert_eq!(x_positions(history.undo().unwrap()), vec![((1, 1), 10.0)]);
+        assert_eq!(x_positions(history.undo().unwrap()), vec![((1, 1), 0.0)]);
+        assert!(history.undo().is_none());
+
+        assert_eq!(x_positions(history.redo().unwrap()), vec![((1, 1), 10.0)]);
+        assert_eq!(x_positions(history.redo().unwrap()), vec![((1, 1), 20.0)]);
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn batch_is_undone_in_one_step() {
+        let mut history = GeometryHistory::new(10, 10);
+        history.begin_batch();
+        for pid in 1..=6 {
+            history.record(change((pid, 1), pid as f64, 100.0 * pid as f64));
+        }
+        history.end_batch();
+
+        let frames = x_positions(history.undo().unwrap());
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[0], ((6, 1), 6.0));
+        assert_eq!(frames[5], ((1, 1), 1.0));
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn nested_batches_form_one_step() {
+        let mut history = GeometryHistory::new(10, 10);
+        history.begin_batch();
+        history.record(change((1, 1), 0.0, 10.0));
+        history.begin_batch();
+        history.record(change((2, 1), 0.0, 10.0));
+        history.end_batch();
+        history.end_batch();
+
+        assert_eq!(history.undo().unwrap().len(), 2);
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn new_change_clears_redo() {
+        let mut history = GeometryHistory::new(10, 10);
+        history.record(change((1, 1), 0.0, 10.0));
+        history.undo();
+        history.record(change((1, 1), 0.0, 30.0));
+
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = GeometryHistory::new(2, 10);
+        for i in 0..5 {
+            history.record(change((1, 1), i as f64, i as f64 + 1.0));
+        }
+
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+    }
+
+    #[test]
+    fn remembers_original_frame_and_ignores_no_ops() {
+        let mut history = GeometryHistory::new(10, 10);
+        history.record(change((1, 1), 5.0, 5.0));
+        assert!(history.undo().is_none());
+        assert_eq!(history.original((1, 1)), None);
+
+        history.record(change((1, 1), 5.0, 10.0));
+        history.record(change((1, 1), 10.0, 20.0));
+        assert_eq!(history.original((1, 1)).map(|rect| rect.x), Some(5.0));
+    }
+
+    #[test]
+    fn original_frame_outlives_the_undo_history() {
+        let mut history = GeometryHistory::new(2, 10);
+        history.record(change((1, 1), 0.0, 10.0));
+        for i in 0..5 {
+            history.record(change((2, 1), i as f64, i as f64 + 1.0));
+        }
+        history.undo();
+        history.record(change((3, 1), 0.0, 10.0));
+
+        assert_eq!(history.original((1, 1)).map(|rect| rect.x), Some(0.0));
+        assert_eq!(history.original((2, 1)).map(|rect| rect.x), Some(0.0));
+    }
+
+    #[test]
+    fn forgets_the_least_recently_moved_original() {
+        let mut history = GeometryHistory::new(10, 2);
+        history.record(change((1, 1), 0.0, 10.0));
+        history.record(change((2, 1), 0.0, 10.0));
+        history.record(change((1, 1), 10.0, 20.0));
+        history.record(change((3, 1), 0.0, 10.0));
+
+        assert_eq!(history.original((1, 1)).map(|rect| rect.x), Some(0.0));
+        assert_eq!(history.original((2, 1)), None);
+        assert_eq!(history.original((3, 1)).map(|rect| rect.x), Some(0.0));
+    }
+}
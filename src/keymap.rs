@@ -0,0 +1,608 @@
+//! Translation between macOS virtual keycodes, [`KeyCode`]s and the characters they type.
+//!
+//! Virtual keycodes name physical key positions (US ANSI). With [`KeyMatching::Character`]
+//! keybinds and slots follow the characters of the current keyboard layout instead, so `h` is
+//! the key that types `h` on Dvorak, AZERTY or QWERTZ. The layout is a [`LayoutTable`] read
+//! from the system, or any table given to [`set_layout`].
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::KeyCode;
+
+/// How keys in keybinds and slot letters are matched against key presses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyMatching {
+    /// By the character the key types in the current keyboard layout
+    #[default]
+    Character,
+    /// By the key's position on a US ANSI keyboard, whatever the layout
+    Position,
+}
+
+/// Characters typed by each virtual keycode on a US ANSI keyboard, unshifted and shifted.
+const US_ANSI: [(i64, char, char); 49] = [
+    (0, 'a', 'A'),
+    (1, 's', 'S'),
+    (2, 'd', 'D'),
+    (3, 'f', 'F'),
+    (4, 'h', 'H'),
+    (5, 'g', 'G'),
+    (6, 'z', 'Z'),
+    (7, 'x', 'X'),
+    (8, 'c', 'C'),
+    (9, 'v', 'V'),
+    (10, '§', '±'),
+    (11, 'b', 'B'),
+    (12, 'q', 'Q'),
+    (13, 'w', 'W'),
+    (14, 'e', 'E'),
+    (15, 'r', 'R'),
+    (16, 'y', 'Y'),
+    (17, 't', 'T'),
+    (18, '1', '!'),
+    (19, '2', '@'),
+    (20, '3', '#'),
+    (21, '4', '$'),
+    (22, '6', '^'),
+    (23, '5', '%'),
+    (24, '=', '+'),
+    (25, '9', '('),
+    (26, '7', '&'),
+    (27, '-', '_'),
+    (28, '8', '*'),
+    (29, '0', ')'),
+    (30, ']', '}'),
+    (31, 'o', 'O'),
+    (32, 'u', 'U'),
+    (33, '[', '{'),
+    (34, 'i', 'I'),
+    (35, 'p', 'P'),
+    (37, 'l', 'L'),
+    (38, 'j', 'J'),
+    (39, '\'', '"'),
+    (40, 'k', 'K'),
+    (41, ';', ':'),
+    (42, '\\', '|'),
+    (43, ',', '<'),
+    (44, '/', '?'),
+    (45, 'n', 'N'),
+    (46, 'm', 'M'),
+    (47, '.', '>'),
+    (49, ' ', ' '),
+    (50, '`', '~'),
+];
+
+/// The characters a keyboard layout types.
+#[derive(Debug, Clone, Default)]
+pub struct LayoutTable {
+    chars: HashMap<(i64, bool), char>,
+    /// The keycode typing each character without shift
+    keycodes: HashMap<char, i64>,
+}
+
+impl LayoutTable {
+    /// Build a table from (keycode, shift, character) entries. When several keys type the
+    /// same character, the first one is used for reverse lookups. Keypad keys are never used
+    /// for them, so a digit the layout only types with shift keeps its top-row position.
+    pub fn from_entries(entries: impl IntoIterator<Item = (i64, bool, char)>) -> Self {
+        let mut table = LayoutTable::default();
+        for (keycode, shift, ch) in entries {
+            table.chars.insert((keycode, shift), ch);
+            if !shift && !is_keypad(keycode) {
+                table.keycodes.entry(ch).or_insert(keycode);
+            }
+        }
+        table
+    }
+
+    pub fn us_ansi() -> Self {
+        Self::from_entries(
+            US_ANSI.iter().flat_map(|&(keycode, lower, upper)| {
+                [(keycode, false, lower), (keycode, true, upper)]
+            }),
+        )
+    }
+
+    pub fn char_for(&self, keycode: i64, shift: bool) -> Option<char> {
+        self.chars.get(&(keycode, shift)).copied()
+    }
+
+    /// The keycode typing `ch` without shift
+    pub fn keycode_for(&self, ch: char) -> Option<i64> {
+        self.keycodes.get(&ch).copied()
+    }
+}
+
+/// Whether `keycode` is on the numeric keypad, whose keys type the same in every layout
+fn is_keypad(keycode: i64) -> bool {
+    matches!(
+        keycode,
+        65 | 67 | 69 | 71 | 75 | 76 | 78 | 81..=89 | 91 | 92
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    layout: LayoutTable,
+    us_ansi: LayoutTable,
+    matching: KeyMatching,
+}
+
+impl Keymap {
+    pub fn new(layout: LayoutTable, matching: KeyMatching) -> Self {
+        Keymap {
+            layout,
+            us_ansi: LayoutTable::us_ansi(),
+            matching,
+        }
+    }
+
+    /// The layout keybinds and slots are matched with
+    fn matching_layout(&self) -> &LayoutTable {
+        match self.matching {
+            KeyMatching::Character => &self.layout,
+            KeyMatching::Position => &self.us_ansi,
+        }
+    }
+
+    /// The character typed by `keycode`, always in the current layout
+    pub fn char_for(&self, keycode: i64, shift: bool) -> Option<char> {
+        self.layout.char_for(keycode, shift)
+    }
+
+    /// The slot letter of `keycode`, if it is a letter key
+    pub fn letter(&self, keycode: i64) -> Option<char> {
+        self.matching_layout()
+            .char_for(keycode, false)
+            .map(|ch| ch.to_ascii_lowercase())
+            .filter(char::is_ascii_lowercase)
+    }
+
+    /// The virtual keycode a keybind's key is matched against.
+    ///
+    /// Keys that type no character, and characters the layout only types with shift or
+    /// not at all, keep their US ANSI position.
+    pub fn native(&self, code: KeyCode) -> i64 {
+        key_code_char(code)
+            .and_then(|ch| self.matching_layout().keycode_for(ch))
+            .unwrap_or_else(|| ansi_keycode(code))
+    }
+}
+
+static KEYMAP: OnceLock<Mutex<Keymap>> = OnceLock::new();
+
+fn with_keymap<T>(f: impl FnOnce(&mut Keymap) -> T) -> T {
+    let keymap = KEYMAP
+        .get_or_init(|| Mutex::new(Keymap::new(LayoutTable::us_ansi(), KeyMatching::default())));
+    let mut keymap = keymap.lock().unwrap();
+    f(&mut keymap)
+}
+
+pub fn set_layout(layout: LayoutTable) {
+    with_keymap(|keymap| keymap.layout = layout);
+}
+
+pub fn set_matching(matching: KeyMatching) {
+    with_keymap(|keymap| keymap.matching = matching);
+}
+
+/// Re-read the current keyboard layout from the system, falling back to US ANSI.
+pub fn refresh_layout() {
+    set_layout(system::current_layout().unwrap_or_else(LayoutTable::us_ansi));
+}
+
+/// Refresh the layout whenever the user switches input source. The notifications arrive on
+/// the main run loop, so call this from the main thread.
+pub fn watch_layout_changes() {
+    system::watch_layout_changes();
+}
+
+pub fn keycode_to_native(code: KeyCode) -> i64 {
+    with_keymap(|keymap| keymap.native(code))
+}
+
+pub fn keycode_to_letter(keycode: i64) -> Option<char> {
+    with_keymap(|keymap| keymap.letter(keycode))
+}
+
+pub fn printable_char(keycode: i64, shift: bool) -> Option<char> {
+    with_keymap(|keymap| keymap.char_for(keycode, shift))
+}
+
+/// The character a [`KeyCode`] stands for, for keys that type one
+fn key_code_char(code: KeyCode) -> Option<char> {
+    let ch = match code {
+        KeyCode::KeyA => 'a',
+        KeyCode::KeyB => 'b',
+        KeyCode::KeyC => 'c',
+        KeyCode::KeyD => 'd',
+        KeyCode::KeyE => 'e',
+        KeyCode::KeyF => 'f',
+        KeyCode::KeyG => 'g',
+        KeyCode::KeyH => 'h',
+        KeyCode::KeyI => 'i',
+        KeyCode::KeyJ => 'j',
+        KeyCode::KeyK => 'k',
+        KeyCode::KeyL => 'l',
+        KeyCode::KeyM => 'm',
+        KeyCode::KeyN => 'n',
+        KeyCode::KeyO => 'o',
+        KeyCode::KeyP => 'p',
+        KeyCode::KeyQ => 'q',
+        KeyCode::KeyR => 'r',
+        KeyCode::KeyS => 's',
+        KeyCode::KeyT => 't',
+        KeyCode::KeyU => 'u',
+        KeyCode::KeyV => 'v',
+        KeyCode::KeyW => 'w',
+        KeyCode::KeyX => 'x',
+        KeyCode::KeyY => 'y',
+        KeyCode::KeyZ => 'z',
+        KeyCode::Digit0 => '0',
+        KeyCode::Digit1 => '1',
+        KeyCode::Digit2 => '2',
+        KeyCode::Digit3 => '3',
+        KeyCode::Digit4 => '4',
+        KeyCode::Digit5 => '5',
+        KeyCode::Digit6 => '6',
+        KeyCode::Digit7 => '7',
+        KeyCode::Digit8 => '8',
+        KeyCode::Digit9 => '9',
+        KeyCode::Equal => '=',
+        KeyCode::Minus => '-',
+        KeyCode::BracketLeft => '[',
+        KeyCode::BracketRight => ']',
+        KeyCode::Backslash => '\\',
+        KeyCode::Semicolon => ';',
+        KeyCode::Quote => '\'',
+        KeyCode::Comma => ',',
+        KeyCode::Period => '.',
+        KeyCode::Slash => '/',
+        KeyCode::Grave => '`',
+        KeyCode::Section => '§',
+        _ => return None,
+    };
+    Some(ch)
+}
+
+/// The virtual keycode of a key's position on a US ANSI keyboard
+fn ansi_keycode(code: KeyCode) -> i64 {
+    match code {
+        KeyCode::KeyA => 0,
+        KeyCode::KeyS => 1,
+        KeyCode::KeyD => 2,
+        KeyCode::KeyF => 3,
+        KeyCode::KeyH => 4,
+        KeyCode::KeyG => 5,
+        KeyCode::KeyZ => 6,
+        KeyCode::KeyX => 7,
+        KeyCode::KeyC => 8,
+        KeyCode::KeyV => 9,
+        KeyCode::KeyB => 11,
+        KeyCode::KeyQ => 12,
+        KeyCode::KeyW => 13,
+        KeyCode::KeyE => 14,
+        KeyCode::KeyR => 15,
+        KeyCode::KeyY => 16,
+        KeyCode::KeyT => 17,
+        KeyCode::Digit1 => 18,
+        KeyCode::Digit2 => 19,
+        KeyCode::Digit3 => 20,
+        KeyCode::Digit4 => 21,
+        KeyCode::Digit6 => 22,
+        KeyCode::Digit5 => 23,
+        KeyCode::Equal => 24,
+        KeyCode::Digit9 => 25,
+        KeyCode::Digit7 => 26,
+        KeyCode::Minus => 27,
+        KeyCode::Digit8 => 28,
+        KeyCode::Digit0 => 29,
+        KeyCode::BracketRight => 30,
+        KeyCode::KeyO => 31,
+        KeyCode::KeyU => 32,
+        KeyCode::BracketLeft => 33,
+        KeyCode::KeyI => 34,
+        KeyCode::KeyP => 35,
+        KeyCode::KeyL => 37,
+        KeyCode::KeyJ => 38,
+        KeyCode::KeyK => 40,
+        KeyCode::Quote => 39,
+        KeyCode::Semicolon => 41,
+        KeyCode::Backslash => 42,
+        KeyCode::Comma => 43,
+        KeyCode::Slash => 44,
+        KeyCode::KeyN => 45,
+        KeyCode::KeyM => 46,
+        KeyCode::Period => 47,
+        KeyCode::Space => 49,
+        KeyCode::Escape => 53,
+        KeyCode::F1 => 122,
+        KeyCode::F2 => 120,
+        KeyCode::F3 => 99,
+        KeyCode::F4 => 118,
+        KeyCode::F5 => 96,
+        KeyCode::F6 => 97,
+        KeyCode::F7 => 98,
+        KeyCode::F8 => 100,
+        KeyCode::F9 => 101,
+        KeyCode::F10 => 109,
+        KeyCode::F11 => 103,
+        KeyCode::F12 => 111,
+        KeyCode::F13 => 105,
+        KeyCode::F14 => 107,
+        KeyCode::F15 => 113,
+        KeyCode::F16 => 106,
+        KeyCode::F17 => 64,
+        KeyCode::F18 => 79,
+        KeyCode::F19 => 80,
+        KeyCode::F20 => 90,
+        KeyCode::Enter => 36,
+        KeyCode::Tab => 48,
+        KeyCode::Backspace => 51,
+        KeyCode::Delete => 117,
+        KeyCode::Insert => 114,
+        KeyCode::Home => 115,
+        KeyCode::End => 119,
+        KeyCode::PageUp => 116,
+        KeyCode::PageDown => 121,
+        KeyCode::ArrowLeft => 123,
+        KeyCode::ArrowRight => 124,
+        KeyCode::ArrowDown => 125,
+        KeyCode::ArrowUp => 126,
+        KeyCode::Section => 10,
+        KeyCode::Grave => 50,
+        KeyCode::CapsLock => 57,
+        KeyCode::KeypadDecimal => 65,
+        KeyCode::KeypadMultiply => 67,
+        KeyCode::KeypadPlus => 69,
+        KeyCode::KeypadClear => 71,
+        KeyCode::KeypadDivide => 75,
+        KeyCode::KeypadEnter => 76,
+        KeyCode::KeypadMinus => 78,
+        KeyCode::KeypadEquals => 81,
+        KeyCode::Keypad0 => 82,
+        KeyCode::Keypad1 => 83,
+        KeyCode::Keypad2 => 84,
+        KeyCode::Keypad3 => 85,
+        KeyCode::Keypad4 => 86,
+        KeyCode::Keypad5 => 87,
+        KeyCode::Keypad6 => 88,
+        KeyCode::Keypad7 => 89,
+        KeyCode::Keypad8 => 91,
+        KeyCode::Keypad9 => 92,
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod system {
+    use std::ffi::c_void;
+
+    use core_foundation::base::{CFRelease, TCFType};
+    use core_foundation::data::{CFData, CFDataRef};
+    use core_foundation::string::CFStringRef;
+    use core_foundation_sys::dictionary::CFDictionaryRef;
+    use core_foundation_sys::notification_center::{
+        CFNotificationCenterAddObserver, CFNotificationCenterGetDistributedCenter,
+        CFNotificationCenterRef, CFNotificationSuspensionBehaviorDeliverImmediately,
+    };
+
+    use super::LayoutTable;
+
+    #[link(name = "Carbon", kind = "framework")]
+    unsafe extern "C" {
+        static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+        static kTISNotifySelectedKeyboardInputSourceChanged: CFStringRef;
+        fn TISCopyCurrentASCIICapableKeyboardLayoutInputSource() -> *mut c_void;
+        fn TISGetInputSourceProperty(source: *mut c_void, key: CFStringRef) -> *const c_void;
+        fn LMGetKbdType() -> u8;
+        fn UCKeyTranslate(
+            layout: *const c_void,
+            virtual_key_code: u16,
+            key_action: u16,
+            modifier_key_state: u32,
+            keyboard_type: u32,
+            key_translate_options: u32,
+            dead_key_state: *mut u32,
+            max_string_length: usize,
+            actual_string_length: *mut usize,
+            unicode_string: *mut u16,
+        ) -> i32;
+    }
+
+    /// kUCKeyActionDown
+    const KEY_ACTION_DOWN: u16 = 0;
+    /// 1 << kUCKeyTranslateNoDeadKeysBit
+    const NO_DEAD_KEYS: u32 = 1;
+    /// (shiftKey >> 8) & 0xFF
+    const SHIFT_KEY_STATE: u32 = 0x02;
+
+    /// Identifies Pixie's observer in the notification center
+    static OBSERVER: u8 = 0;
+
+    extern "C" fn input_source_changed(
+        _center: CFNotificationCenterRef,
+        _observer: *mut c_void,
+        _name: CFStringRef,
+        _object: *const c_void,
+        _user_info: CFDictionaryRef,
+    ) {
+        super::refresh_layout();
+    }
+
+    pub fn watch_layout_changes() {
+        unsafe {
+            CFNotificationCenterAddObserver(
+                CFNotificationCenterGetDistributedCenter(),
+                &OBSERVER as *const u8 as *const c_void,
+                input_source_changed,
+                kTISNotifySelectedKeyboardInputSourceChanged,
+                std::ptr::null(),
+                CFNotificationSuspensionBehaviorDeliverImmediately,
+            );
+        }
+    }
+
+    /// The characters of the current ASCII-capable keyboard layout (so input methods like
+    /// Japanese still map to their underlying Latin layout).
+    pub fn current_layout() -> Option<LayoutTable> {
+        unsafe {
+            let source = TISCopyCurrentASCIICapableKeyboardLayoutInputSource();
+            if source.is_null() {
+                return None;
+            }
+
+            let data =
+                TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData) as CFDataRef;
+            let table = (!data.is_null()).then(|| {
+                let data = CFData::wrap_under_get_rule(data);
+                let layout = data.bytes().as_ptr() as *const c_void;
+                let keyboard_type = u32::from(LMGetKbdType());
+
+                let mut entries = Vec::new();
+                for keycode in 0..128u16 {
+                    for shift in [false, true] {
+                        let mut dead_key_state = 0;
+                        let mut length = 0;
+                        let mut chars = [0u16; 4];
+                        let status = UCKeyTranslate(
+                            layout,
+                            keycode,
+                            KEY_ACTION_DOWN,
+                            if shift { SHIFT_KEY_STATE } else { 0 },
+                            keyboard_type,
+                            NO_DEAD_KEYS,
+                            &mut dead_key_state,
+                            chars.len(),
+                            &mut length,
+                            chars.as_mut_ptr(),
+                        );
+                        if status == 0
+                            && length == 1
+                            && let Some(ch) = char::from_u32(u32::from(chars[0]))
+                            && !ch.is_control()
+                        {
+                            entries.push((i64::from(keycode), shift, ch));
+                        }
+                    }
+                }
+                LayoutTable::from_entries(entries)
+            });
+
+            CFRelease(source as _);
+            table
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+mod system {
+    use super::LayoutTable;
+
+    pub fn current_layout() -> Option<LayoutTable> {
+        None
+    }
+
+    pub fn watch_layout_changes() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The letter rows of Dvorak on US ANSI keycodes
+    fn dvorak() -> LayoutTable {
+        let rows = [
+            ([12, 13, 14, 15, 17, 16, 32, 34, 31, 35], "',.pyfgcrl"),
+            ([0, 1, 2, 3, 5, 4, 38, 40, 37, 41], "aoeuidhtns"),
+            ([6, 7, 8, 9, 11, 45, 46, 43, 47, 44], ";qjkxbmwvz"),
+        ];
+        LayoutTable::from_entries(rows.iter().flat_map(|(keycodes, chars)| {
+            keycodes
+                .iter()
+                .zip(chars.chars())
+                .flat_map(|(&keycode, ch)| {
+                    [
+                        (keycode, false, ch),
+                        (keycode, true, ch.to_ascii_uppercase()),
+                    ]
+                })
+        }))
+    }
+
+    #[test]
+    fn us_ansi_round_trips() {
+        let keymap = Keymap::new(LayoutTable::us_ansi(), KeyMatching::Character);
+
+        assert_eq!(keymap.native(KeyCode::KeyH), 4);
+        assert_eq!(keymap.native(KeyCode::Period), 47);
+        assert_eq!(keymap.native(KeyCode::ArrowLeft), 123);
+        assert_eq!(keymap.native(KeyCode::Grave), 50);
+        assert_eq!(keymap.native(KeyCode::F18), 79);
+        assert_eq!(keymap.native(KeyCode::Keypad1), 83);
+        assert_eq!(keymap.letter(4), Some('h'));
+        assert_eq!(keymap.letter(18), None);
+        assert_eq!(keymap.char_for(18, true), Some('!'));
+        assert_eq!(keymap.char_for(123, false), None);
+    }
+
+    #[test]
+    fn character_matching_follows_the_layout() {
+        let keymap = Keymap::new(dvorak(), KeyMatching::Character);
+
+        // Dvorak's h is on the US j key
+        assert_eq!(keymap.native(KeyCode::KeyH), 38);
+        assert_eq!(keymap.letter(38), Some('h'));
+        assert_eq!(keymap.char_for(1, true), Some('O'));
+        // Keys the layout table doesn't type keep their position
+        assert_eq!(keymap.native(KeyCode::Digit1), 18);
+        assert_eq!(keymap.native(KeyCode::Escape), 53);
+        assert_eq!(keymap.native(KeyCode::Keypad1), 83);
+    }
+
+    #[test]
+    fn position_matching_ignores_the_layout_except_for_typing() {
+        let keymap = Keymap::new(dvorak(), KeyMatching::Position);
+
+        assert_eq!(keymap.native(KeyCode::KeyH), 4);
+        assert_eq!(keymap.letter(38), Some('j'));
+        // Search text is still typed in the layout
+        assert_eq!(keymap.char_for(38, false), Some('h'));
+    }
+
+    #[test]
+    fn first_key_typing_a_character_wins() {
+        let layout = LayoutTable::from_entries([(47, false, '.'), (43, false, '.')]);
+        assert_eq!(layout.keycode_for('.'), Some(47));
+    }
+
+    #[test]
+    fn shifted_digits_keep_their_position_over_the_keypad() {
+        // AZERTY types digits with shift on the top row, and unshifted on the keypad
+        let top_row = [18, 19, 20, 21, 23, 22, 26, 28, 25, 29]
+            .into_iter()
+            .zip("&é\"'(§è!çà".chars().zip("1234567890".chars()))
+            .flat_map(|(keycode, (lower, upper))| {
+                [(keycode, false, lower), (keycode, true, upper)]
+            });
+        let keypad = [(65, false, '.'), (83, false, '1'), (84, false, '2')];
+        let layout = LayoutTable::from_entries(
+            keypad
+                .into_iter()
+                .chain(top_row)
+                .chain([(43, false, ';'), (43, true, '.')]),
+        );
+        assert_eq!(layout.keycode_for('1'), None);
+        assert_eq!(layout.char_for(83, false), Some('1'));
+
+        let keymap = Keymap::new(layout, KeyMatching::Character);
+        assert_eq!(keymap.native(KeyCode::Digit1), 18);
+        assert_eq!(keymap.native(KeyCode::Digit2), 19);
+        assert_eq!(keymap.native(KeyCode::Period), 47);
+        assert_eq!(keymap.native(KeyCode::Keypad1), 83);
+        assert_eq!(keymap.char_for(18, true), Some('1'));
+    }
+}
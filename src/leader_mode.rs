@@ -1,12 +1,14 @@
 use crossbeam::channel::{Receiver, Sender, unbounded};
-use std::sync::Arc;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::accessibility::Direction;
-use crate::config::Action;
+use crate::config::{Action, KeybindEntry, Mode};
 use crate::error::Result;
+use crate::event_tap;
 
 #[derive(Debug, Clone)]
 pub enum LeaderModeEvent {
@@ -14,7 +16,22 @@ pub enum LeaderModeEvent {
     FocusSlot(char),
     Cancelled,
     KeybindAction(Action),
-    FocusDirection(Direction),
+    /// Focus the window this many steps away in a direction
+    FocusDirection(Direction, u32),
+    ModeEntered(String),
+    ModeExited(String),
+}
+
+/// Largest count prefix; further digits are ignored.
+const MAX_COUNT: u32 = 99;
+
+/// The sticky mode currently capturing keys.
+struct ActiveMode {
+    name: String,
+    timeout: Option<Duration>,
+    last_key: Instant,
+    /// Distinguishes re-entries so a stale timeout thread leaves a newer mode alone
+    generation: u64,
 }
 
 pub struct LeaderModeController {
@@ -22,6 +39,13 @@ pub struct LeaderModeController {
     event_sender: Sender<LeaderModeEvent>,
     is_listening: Arc<AtomicBool>,
     timeout_millis: Arc<AtomicU64>,
+    modes: Mutex<HashMap<String, Mode>>,
+    active_mode: Arc<Mutex<Option<ActiveMode>>>,
+    mode_generation: AtomicU64,
+    /// Count typed after the leader, like the `3` in `leader 3 →`
+    count: Mutex<Option<u32>>,
+    /// Last executed action and its count, for `leader .`
+    last_action: Mutex<Option<(Action, u32)>>,
 }
 
 impl LeaderModeController {
@@ -40,11 +64,17 @@ impl LeaderModeController {
             event_sender,
             is_listening,
             timeout_millis,
+            modes: Mutex::new(HashMap::new()),
+            active_mode: Arc::new(Mutex::new(None)),
+            mode_generation: AtomicU64::new(0),
+            count: Mutex::new(None),
+            last_action: Mutex::new(None),
         })
     }
 
     pub fn enter_listening_mode(&self) {
         self.is_listening.store(true, Ordering::SeqCst);
+        *self.count.lock().unwrap() = None;
 
         let is_listening = Arc::clone(&self.is_listening);
         let sender = self.event_sender.clone();
@@ -62,6 +92,82 @@ impl LeaderModeController {
             .store(duration_to_millis(timeout), Ordering::SeqCst);
     }
 
+    /// Replace the modes. An active mode is re-entered with its new keybinds, or left if it
+    /// no longer exists.
+    pub fn set_modes(&self, modes: HashMap<String, Mode>) {
+        *self.modes.lock().unwrap() = modes;
+        let Some(active) = self.active_mode() else {
+            return;
+        };
+        if self.modes.lock().unwrap().contains_key(&active) {
+            self.enter_mode(&active);
+        } else {
+            self.exit_mode();
+        }
+    }
+
+    /// Start capturing the keys of the mode `name` until Escape or its timeout.
+    pub fn enter_mode(&self, name: &str) {
+        let Some(mode) = self.modes.lock().unwrap().get(name).cloned() else {
+            eprintln!("✗ Mode '{}' not found", name);
+            return;
+        };
+        let keybinds: Vec<KeybindEntry> = mode.parsed_keybinds();
+        if keybinds.len() != mode.keybinds.len() {
+            eprintln!(
+                "Warning: Some keybinds of mode '{}' are invalid and were ignored.",
+                name
+            );
+        }
+
+        let generation = self.mode_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let timeout = (mode.timeout > 0).then(|| Duration::from_secs(mode.timeout));
+        *self.active_mode.lock().unwrap() = Some(ActiveMode {
+            name: name.to_string(),
+            timeout,
+            last_key: Instant::now(),
+            generation,
+        });
+        event_tap::set_mode_keybinds(Some(keybinds));
+        let _ = self
+            .event_sender
+            .send(LeaderModeEvent::ModeEntered(name.to_string()));
+
+        if let Some(timeout) = timeout {
+            let active_mode = Arc::clone(&self.active_mode);
+            let sender = self.event_sender.clone();
+            thread::spawn(move || {
+                let mut wait = timeout;
+                loop {
+                    thread::sleep(wait);
+                    match exit_if_idle(&active_mode, &sender, generation, Instant::now()) {
+                        Some(remaining) => wait = remaining,
+                        None => return,
+                    }
+                }
+            });
+        }
+    }
+
+    /// Leave the active mode, if any.
+    pub fn exit_mode(&self) {
+        let exited = self.active_mode.lock().unwrap().take();
+        if let Some(mode) = exited {
+            event_tap::set_mode_keybinds(None);
+            let _ = self
+                .event_sender
+                .send(LeaderModeEvent::ModeExited(mode.name));
+        }
+    }
+
+    pub fn active_mode(&self) -> Option<String> {
+        self.active_mode
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|mode| mode.name.clone())
+    }
+
     pub fn handle_key(&self, key: char, shift: bool) {
         if !self.is_listening.swap(false, Ordering::SeqCst) {
             return;
@@ -75,25 +181,99 @@ impl LeaderModeController {
         let _ = self.event_sender.send(event);
     }
 
-    #[allow(dead_code)]
     pub fn cancel(&self) {
         if self.is_listening.swap(false, Ordering::SeqCst) {
             let _ = self.event_sender.send(LeaderModeEvent::Cancelled);
         }
     }
 
+    /// Add a digit to the count prefix; the leader keeps listening.
+    pub fn handle_digit(&self, digit: u32) {
+        if !self.is_listening.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let mut count = self.count.lock().unwrap();
+        *count = Some(
+            count
+                .unwrap_or(0)
+                .saturating_mul(10)
+                .saturating_add(digit)
+                .min(MAX_COUNT),
+        );
+    }
+
     pub fn handle_action(&self, action: Action) {
-        self.is_listening.store(false, Ordering::SeqCst);
-        let _ = self
-            .event_sender
-            .send(LeaderModeEvent::KeybindAction(action));
+        let count = self.take_count();
+        let mode = match &action {
+            Action::EnterMode(name) => Some(name.as_str()),
+            Action::ResizeMode => Some("resize"),
+            _ => None,
+        };
+        if let Some(name) = mode {
+            self.enter_mode(name);
+            return;
+        }
+        // Keys of a sticky mode keep it alive
+        if let Some(mode) = self.active_mode.lock().unwrap().as_mut() {
+            mode.last_key = Instant::now();
+        }
+        self.run(action, count);
     }
 
     pub fn handle_direction(&self, direction: Direction) {
-        self.is_listening.store(false, Ordering::SeqCst);
-        let _ = self
-            .event_sender
-            .send(LeaderModeEvent::FocusDirection(direction));
+        let count = self.take_count();
+        self.run(focus_action(direction), count);
+    }
+
+    /// Run the last action again, with a new count if one was typed.
+    pub fn repeat_last(&self) {
+        let was_listening = self.is_listening.swap(false, Ordering::SeqCst);
+        let typed = self.count.lock().unwrap().take().filter(|_| was_listening);
+        let last = self.last_action.lock().unwrap().clone();
+        match last {
+            Some((action, count)) => self.run(action, typed.unwrap_or(count).max(1)),
+            None => {
+                let _ = self.event_sender.send(LeaderModeEvent::Cancelled);
+            }
+        }
+    }
+
+    /// Stop listening and return the typed count, 1 if there is none.
+    fn take_count(&self) -> u32 {
+        let was_listening = self.is_listening.swap(false, Ordering::SeqCst);
+        let count = self.count.lock().unwrap().take();
+        if was_listening {
+            count.unwrap_or(1).max(1)
+        } else {
+            1
+        }
+    }
+
+    fn run(&self, action: Action, count: u32) {
+        *self.last_action.lock().unwrap() = Some((action.clone(), count));
+
+        if let Some(direction) = focus_direction(&action) {
+            let _ = self
+                .event_sender
+                .send(LeaderModeEvent::FocusDirection(direction, count));
+            return;
+        }
+
+        // Opening the picker twice makes no sense, everything else runs `count` times
+        let times = if matches!(
+            action,
+            Action::Tile | Action::Select | Action::Hints | Action::Palette | Action::Launcher
+        ) {
+            1
+        } else {
+            count
+        };
+        for _ in 0..times {
+            let _ = self
+                .event_sender
+                .send(LeaderModeEvent::KeybindAction(action.clone()));
+        }
     }
 
     #[allow(dead_code)]
@@ -113,6 +293,256 @@ impl LeaderModeController {
     }
 }
 
+/// Leave the mode entered as `generation` if no key was pressed for its timeout at `now`.
+/// Returns how much longer to wait, or `None` once the mode is gone or was re-entered.
+fn exit_if_idle(
+    active_mode: &Mutex<Option<ActiveMode>>,
+    sender: &Sender<LeaderModeEvent>,
+    generation: u64,
+    now: Instant,
+) -> Option<Duration> {
+    let mut active = active_mode.lock().unwrap();
+    let mode = active
+        .as_ref()
+        .filter(|mode| mode.generation == generation)?;
+    let timeout = mode.timeout?;
+    let idle = now.saturating_duration_since(mode.last_key);
+    if idle < timeout {
+        return Some(timeout - idle);
+    }
+
+    let name = mode.name.clone();
+    *active = None;
+    event_tap::set_mode_keybinds(None);
+    let _ = sender.send(LeaderModeEvent::ModeExited(name));
+    None
+}
+
+fn focus_action(direction: Direction) -> Action {
+    match direction {
+        Direction::Left => Action::FocusLeft,
+        Direction::Right => Action::FocusRight,
+        Direction::Up => Action::FocusUp,
+        Direction::Down => Action::FocusDown,
+    }
+}
+
+fn focus_direction(action: &Action) -> Option<Direction> {
+    match action {
+        Action::FocusLeft => Some(Direction::Left),
+        Action::FocusRight => Some(Direction::Right),
+        Action::FocusUp => Some(Direction::Up),
+        Action::FocusDown => Some(Direction::Down),
+        _ => None,
+    }
+}
+
 fn duration_to_millis(timeout: Duration) -> u64 {
     timeout.as_millis().clamp(1, u128::from(u64::MAX)) as u64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn controller() -> LeaderModeController {
+        LeaderModeController::with_timeout(Duration::from_secs(60)).unwrap()
+    }
+
+    fn events(controller: &LeaderModeController) -> Vec<LeaderModeEvent> {
+        controller.events().try_iter().collect()
+    }
+
+    fn action_count(events: &[LeaderModeEvent], action: &Action) -> usize {
+        events
+            .iter()
+            .filter(|event| matches!(event, LeaderModeEvent::KeybindAction(a) if a == action))
+            .count()
+    }
+
+    fn mode(timeout: u64) -> Mode {
+        Mode {
+            keybinds: HashMap::from([("h".to_string(), Action::MoveLeft)]),
+            timeout,
+        }
+    }
+
+    fn generation(controller: &LeaderModeController) -> u64 {
+        controller
+            .active_mode
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|mode| mode.generation)
+            .unwrap()
+    }
+
+    fn exit_if_idle_at(
+        controller: &LeaderModeController,
+        generation: u64,
+        now: Instant,
+    ) -> Option<Duration> {
+        exit_if_idle(
+            &controller.active_mode,
+            &controller.event_sender,
+            generation,
+            now,
+        )
+    }
+
+    #[test]
+    fn count_prefix_repeats_the_action() {
+        let controller = controller();
+        controller.enter_listening_mode();
+        controller.handle_digit(3);
+        controller.handle_action(Action::MoveLeft);
+        assert_eq!(action_count(&events(&controller), &Action::MoveLeft), 3);
+
+        controller.enter_listening_mode();
+        controller.handle_digit(1);
+        controller.handle_digit(2);
+        controller.handle_direction(Direction::Right);
+        assert!(matches!(
+            events(&controller)[..],
+            [LeaderModeEvent::FocusDirection(Direction::Right, 12)]
+        ));
+    }
+
+    #[test]
+    fn count_is_clamped_and_needs_the_leader() {
+        let controller = controller();
+        controller.enter_listening_mode();
+        for digit in [1, 2, 3] {
+            controller.handle_digit(digit);
+        }
+        controller.handle_action(Action::MoveUp);
+        assert_eq!(
+            action_count(&events(&controller), &Action::MoveUp),
+            MAX_COUNT as usize
+        );
+
+        // Digits outside leader mode and a leftover count are ignored
+        controller.handle_digit(5);
+        controller.handle_action(Action::MoveUp);
+        assert_eq!(action_count(&events(&controller), &Action::MoveUp), 1);
+    }
+
+    #[test]
+    fn pickers_run_once() {
+        let controller = controller();
+        controller.enter_listening_mode();
+        controller.handle_digit(4);
+        controller.handle_action(Action::Select);
+        assert_eq!(action_count(&events(&controller), &Action::Select), 1);
+    }
+
+    #[test]
+    fn repeat_last_reuses_or_overrides_the_count() {
+        let controller = controller();
+        controller.repeat_last();
+        assert!(matches!(
+            events(&controller)[..],
+            [LeaderModeEvent::Cancelled]
+        ));
+
+        controller.enter_listening_mode();
+        controller.handle_digit(2);
+        controller.handle_action(Action::MoveDown);
+        assert_eq!(action_count(&events(&controller), &Action::MoveDown), 2);
+
+        controller.repeat_last();
+        assert_eq!(action_count(&events(&controller), &Action::MoveDown), 2);
+
+        controller.enter_listening_mode();
+        controller.handle_digit(5);
+        controller.repeat_last();
+        assert_eq!(action_count(&events(&controller), &Action::MoveDown), 5);
+
+        // The typed count is remembered for the next repeat
+        controller.repeat_last();
+        assert_eq!(action_count(&events(&controller), &Action::MoveDown), 5);
+    }
+
+    #[test]
+    fn reentering_a_mode_ignores_the_stale_timeout() {
+        let controller = controller();
+        controller.set_modes(HashMap::from([("move".to_string(), mode(10))]));
+        controller.enter_mode("move");
+        let first = generation(&controller);
+        controller.enter_mode("move");
+        let second = generation(&controller);
+        assert_ne!(first, second);
+        events(&controller);
+
+        let later = Instant::now() + Duration::from_secs(3600);
+        assert_eq!(exit_if_idle_at(&controller, first, later), None);
+        assert_eq!(controller.active_mode().as_deref(), Some("move"));
+        assert!(events(&controller).is_empty());
+
+        assert_eq!(exit_if_idle_at(&controller, second, later), None);
+        assert_eq!(controller.active_mode(), None);
+        assert!(matches!(
+            &events(&controller)[..],
+            [LeaderModeEvent::ModeExited(name)] if name == "move"
+        ));
+    }
+
+    #[test]
+    fn keys_keep_a_mode_alive() {
+        let controller = controller();
+        controller.set_modes(HashMap::from([("move".to_string(), mode(10))]));
+        controller.enter_mode("move");
+        let generation = generation(&controller);
+        let entered = Instant::now();
+        if let Some(mode) = controller.active_mode.lock().unwrap().as_mut() {
+            mode.last_key = entered - Duration::from_secs(9);
+        }
+
+        controller.handle_action(Action::MoveLeft);
+        let wait = exit_if_idle_at(&controller, generation, entered + Duration::from_secs(5));
+        assert!(wait.is_some_and(|wait| wait > Duration::from_secs(4)));
+        assert_eq!(controller.active_mode().as_deref(), Some("move"));
+    }
+
+    #[test]
+    fn exiting_a_mode_is_reported_once() {
+        let controller = controller();
+        controller.set_modes(HashMap::from([("move".to_string(), mode(0))]));
+        controller.enter_mode("move");
+        controller.exit_mode();
+        controller.exit_mode();
+
+        let events = events(&controller);
+        assert!(matches!(
+            &events[..],
+            [LeaderModeEvent::ModeEntered(_), LeaderModeEvent::ModeExited(name)] if name == "move"
+        ));
+    }
+
+    #[test]
+    fn reloading_modes_refreshes_the_active_one() {
+        let controller = controller();
+        controller.set_modes(HashMap::from([("move".to_string(), mode(0))]));
+        controller.enter_mode("move");
+        let first = generation(&controller);
+
+        controller.set_modes(HashMap::from([("move".to_string(), mode(5))]));
+        assert_ne!(generation(&controller), first);
+        assert_eq!(
+            controller
+                .active_mode
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|mode| mode.timeout),
+            Some(Duration::from_secs(5))
+        );
+
+        controller.set_modes(HashMap::new());
+        assert_eq!(controller.active_mode(), None);
+        assert!(matches!(
+            events(&controller).last(),
+            Some(LeaderModeEvent::ModeExited(name)) if name == "move"
+        ));
+    }
+}
@@ -1,12 +1,27 @@
 //! Pixie - macOS Window Focusing Tool
 
 mod accessibility;
+mod adjust;
+mod apps;
 mod config;
+mod direction;
+mod display;
 mod error;
 mod event_tap;
+mod fuzzy;
+mod geometry;
+mod hints;
+mod history;
+mod keymap;
 mod leader_mode;
 mod menu_bar;
+mod modifier_tap;
 mod notification;
+mod palette;
+mod picker;
+mod placement;
+mod recent;
+mod tiling;
 mod ui;
 mod window;
 
@@ -14,6 +29,7 @@ use clap::{Parser, Subcommand};
 use cocoa::appkit::{NSApplication, NSApplicationActivationPolicy};
 use cocoa::base::nil;
 use gpui::AssetSource;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
@@ -28,15 +44,21 @@ impl AssetSource for EmptyAssets {
     }
 }
 
+use adjust::MoveStep;
 use config::Action;
 use error::{PixieError, Result};
 use event_tap::EventTapAction;
 use leader_mode::{LeaderModeController, LeaderModeEvent};
 use window::WindowManager;
 
-struct WindowManagerState;
+/// The window manager, for UI code on the GPUI thread
+struct WindowManagerState(Arc<WindowManager>);
 impl gpui::Global for WindowManagerState {}
 
+/// Lets the command palette run actions as if their keybind was pressed
+struct LeaderModeState(Arc<LeaderModeController>);
+impl gpui::Global for LeaderModeState {}
+
 /// Pixie - macOS Window Focusing Tool
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -193,7 +215,60 @@ fn handle_command(cmd: Commands, window_manager: &WindowManager) -> Result<()> {
     Ok(())
 }
 
-fn handle_keybind_action(action: &Action, _window_manager: &WindowManager) {
+/// Focus the window `count` steps away in `direction`, stopping early at the last one found
+fn focus_in_direction(direction: accessibility::Direction, count: u32) {
+    let strategy = config::load()
+        .map(|config| config.focus_strategy())
+        .unwrap_or_default();
+
+    match accessibility::get_focused_window() {
+        Ok(focused_element) => match accessibility::get_window_rect(&focused_element) {
+            Ok(mut from_rect) => {
+                let mut target = None;
+                for _ in 0..count.max(1) {
+                    match accessibility::find_window_in_direction(&from_rect, direction, strategy) {
+                        Ok(window) => {
+                            let rect = accessibility::get_window_rect(&window);
+                            target = Some(window);
+                            match rect {
+                                Ok(rect) => from_rect = rect,
+                                Err(_) => break,
+                            }
+                        }
+                        Err(e) => {
+                            if target.is_none() {
+                                eprintln!("✗ No window found {:?}: {}", direction, e);
+                            }
+                            break;
+                        }
+                    }
+                }
+                if let Some(target_window) = target
+                    && let Err(e) = accessibility::focus_window(&target_window)
+                {
+                    eprintln!("✗ Failed to focus window: {}", e);
+                }
+            }
+            Err(e) => eprintln!("✗ Failed to get window rect: {}", e),
+        },
+        Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+    }
+}
+
+fn handle_keybind_action(action: &Action, window_manager: &WindowManager) {
+    handle_action(action, None, window_manager);
+}
+
+/// The window with this identity, or the focused window without one.
+fn target_window(target: Option<(i32, u32)>) -> Result<::accessibility::AXUIElement> {
+    match target {
+        Some((pid, window_id)) => accessibility::find_window_by_id(pid, window_id),
+        None => accessibility::get_focused_window(),
+    }
+}
+
+/// Run `action` on the `target` window (pid, window id), or on the focused window.
+fn handle_action(action: &Action, target: Option<(i32, u32)>, window_manager: &WindowManager) {
     match action {
         Action::FocusLeft | Action::FocusRight | Action::FocusUp | Action::FocusDown => {
             let direction = match action {
@@ -204,46 +279,130 @@ fn handle_keybind_action(action: &Action, _window_manager: &WindowManager) {
                 _ => unreachable!(),
             };
 
-            match accessibility::get_focused_window() {
-                Ok(focused_element) => match accessibility::get_window_rect(&focused_element) {
-                    Ok(from_rect) => {
-                        match accessibility::find_window_in_direction(&from_rect, direction) {
-                            Ok(target_window) => {
-                                if let Err(e) = accessibility::focus_window(&target_window) {
-                                    eprintln!("✗ Failed to focus window: {}", e);
-                                }
-                            }
-                            Err(e) => eprintln!("✗ No window found {:?}: {}", direction, e),
-                        }
+            focus_in_direction(direction, 1);
+        }
+        Action::SwapLeft | Action::SwapRight | Action::SwapUp | Action::SwapDown => {
+            let direction = match action {
+                Action::SwapLeft => accessibility::Direction::Left,
+                Action::SwapRight => accessibility::Direction::Right,
+                Action::SwapUp => accessibility::Direction::Up,
+                Action::SwapDown => accessibility::Direction::Down,
+                _ => unreachable!(),
+            };
+            let strategy = config::load()
+                .map(|config| config.focus_strategy())
+                .unwrap_or_default();
+
+            match target_window(target) {
+                Ok(element) => {
+                    if let Err(e) =
+                        accessibility::swap_window_in_direction(&element, direction, strategy)
+                    {
+                        eprintln!("✗ Failed to swap window {:?}: {}", direction, e);
                     }
-                    Err(e) => eprintln!("✗ Failed to get window rect: {}", e),
-                },
-                Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+                }
+                Err(e) => eprintln!("✗ Failed to get window: {}", e),
+            }
+        }
+        Action::MoveLeft | Action::MoveRight | Action::MoveUp | Action::MoveDown => {
+            let direction = match action {
+                Action::MoveLeft => accessibility::Direction::Left,
+                Action::MoveRight => accessibility::Direction::Right,
+                Action::MoveUp => accessibility::Direction::Up,
+                Action::MoveDown => accessibility::Direction::Down,
+                _ => unreachable!(),
+            };
+            let step = match config::load().and_then(|config| MoveStep::parse(&config.move_step)) {
+                Ok(step) => step,
+                Err(e) => {
+                    eprintln!("✗ {}", e);
+                    return;
+                }
+            };
+
+            match target_window(target) {
+                Ok(element) => {
+                    if let Err(e) =
+                        accessibility::move_window_in_direction(&element, direction, &step)
+                    {
+                        eprintln!("✗ Failed to move window {:?}: {}", direction, e);
+                    }
+                }
+                Err(e) => eprintln!("✗ Failed to get window: {}", e),
+            }
+        }
+        Action::Grow(resize) | Action::Shrink(resize) => {
+            let grow = matches!(action, Action::Grow(_));
+            let amount = match adjust::parse_length("resize amount", &resize.by) {
+                Ok(amount) => amount,
+                Err(e) => {
+                    eprintln!("✗ {}", e);
+                    return;
+                }
+            };
+
+            match target_window(target) {
+                Ok(element) => {
+                    if let Err(e) =
+                        accessibility::resize_window(&element, resize.axis, &amount, grow)
+                    {
+                        eprintln!("✗ Failed to resize window: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("✗ Failed to get window: {}", e),
+            }
+        }
+        Action::Nudge(nudge) => {
+            let step = match adjust::parse_length("nudge amount", &nudge.by) {
+                Ok(amount) => MoveStep::Length(amount),
+                Err(e) => {
+                    eprintln!("✗ {}", e);
+                    return;
+                }
+            };
+
+            match target_window(target) {
+                Ok(element) => {
+                    if let Err(e) =
+                        accessibility::move_window_in_direction(&element, nudge.direction, &step)
+                    {
+                        eprintln!("✗ Failed to nudge window: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("✗ Failed to get window: {}", e),
             }
         }
-        Action::Minimize => match accessibility::get_focused_window() {
+        Action::Minimize => match target_window(target) {
             Ok(element) => {
                 if let Err(e) = accessibility::minimize_window(&element) {
                     eprintln!("✗ Failed to minimize window: {}", e);
                 }
             }
-            Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
+        },
+        Action::Close => match target_window(target) {
+            Ok(element) => {
+                if let Err(e) = accessibility::close_window(&element) {
+                    eprintln!("✗ Failed to close window: {}", e);
+                }
+            }
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
         },
-        Action::Maximize => match accessibility::get_focused_window() {
+        Action::Maximize => match target_window(target) {
             Ok(element) => {
                 if let Err(e) = accessibility::maximize_window(&element) {
                     eprintln!("✗ Failed to maximize window: {}", e);
                 }
             }
-            Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
         },
-        Action::Fullscreen => match accessibility::get_focused_window() {
+        Action::Fullscreen => match target_window(target) {
             Ok(element) => {
                 if let Err(e) = accessibility::toggle_fullscreen(&element) {
                     eprintln!("✗ Failed to toggle fullscreen: {}", e);
                 }
             }
-            Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
         },
         Action::MoveMonitorLeft
         | Action::MoveMonitorRight
@@ -257,27 +416,29 @@ fn handle_keybind_action(action: &Action, _window_manager: &WindowManager) {
                 _ => unreachable!(),
             };
 
-            match accessibility::get_focused_window() {
+            match target_window(target) {
                 Ok(element) => {
                     if let Err(e) = accessibility::move_window_to_monitor(&element, direction) {
                         eprintln!("✗ Failed to move window to monitor: {}", e);
                     }
                 }
-                Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+                Err(e) => eprintln!("✗ Failed to get window: {}", e),
             }
         }
-        Action::Center => match accessibility::get_focused_window() {
+        Action::Center => match target_window(target) {
             Ok(element) => {
                 let placements = config::builtin_placements();
-                if let Some(placement) = placements.get("center")
+                if let Some(placement) = placements
+                    .get("center")
+                    .and_then(|spec| spec.variants().first())
                     && let Err(e) = accessibility::apply_placement(&element, placement)
                 {
                     eprintln!("✗ Failed to center window: {}", e);
                 }
             }
-            Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
         },
-        Action::Place(name) => match accessibility::get_focused_window() {
+        Action::Place(name) => match target_window(target) {
             Ok(element) => {
                 let config = config::load().unwrap_or_else(|e| {
                     eprintln!("Error loading config: {}", e);
@@ -285,17 +446,64 @@ fn handle_keybind_action(action: &Action, _window_manager: &WindowManager) {
                     std::process::exit(1);
                 });
                 let placements = config.get_placements();
-                if let Some(placement) = placements.get(name) {
-                    if let Err(e) = accessibility::apply_placement(&element, placement) {
-                        eprintln!("✗ Failed to apply placement '{}': {}", name, e);
+                if let Some(spec) = placements.get(name) {
+                    let variants = spec.variants();
+                    let index = match accessibility::get_window_rect(&element) {
+                        Ok(accessibility::WindowRect {
+                            pid,
+                            window_id: Some(window_id),
+                            ..
+                        }) => window_manager.next_placement_variant(
+                            (pid, window_id),
+                            name,
+                            variants.len(),
+                            Duration::from_millis(config.placement_cycle_timeout_ms),
+                        ),
+                        _ => 0,
+                    };
+                    match variants.get(index) {
+                        Some(placement) => {
+                            if let Err(e) = accessibility::apply_placement(&element, placement) {
+                                eprintln!("✗ Failed to apply placement '{}': {}", name, e);
+                            }
+                        }
+                        None => eprintln!("✗ Placement '{}' has no variants", name),
                     }
                 } else {
                     eprintln!("✗ Placement '{}' not found", name);
                 }
             }
-            Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
         },
-        Action::Tile | Action::Select => {}
+        Action::MoveToDisplay(display) => match target_window(target) {
+            Ok(element) => {
+                if let Err(e) = accessibility::move_window_to_display(&element, display) {
+                    eprintln!("✗ Failed to move window to display: {}", e);
+                }
+            }
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
+        },
+        Action::Undo => {
+            if let Err(e) = accessibility::undo_geometry() {
+                eprintln!("✗ Failed to undo: {}", e);
+            }
+        }
+        Action::Redo => {
+            if let Err(e) = accessibility::redo_geometry() {
+                eprintln!("✗ Failed to redo: {}", e);
+            }
+        }
+        Action::Restore => match target_window(target) {
+            Ok(element) => {
+                if let Err(e) = accessibility::restore_window(&element) {
+                    eprintln!("✗ Failed to restore window: {}", e);
+                }
+            }
+            Err(e) => eprintln!("✗ Failed to get window: {}", e),
+        },
+        // Entered by the leader mode controller
+        Action::ResizeMode | Action::EnterMode(_) => {}
+        Action::Tile | Action::Select | Action::Hints | Action::Palette | Action::Launcher => {}
     }
 }
 
@@ -315,30 +523,45 @@ fn apply_autostart_setting(enabled: bool) {
 fn runtime_bindings(
     cfg: &config::Config,
 ) -> (
-    config::Modifiers,
-    config::KeyCode,
+    config::LeaderKey,
+    Duration,
     Vec<config::KeybindEntry>,
     Duration,
 ) {
-    let (leader_modifiers, leader_keycode) = config::parse_leader_key(&cfg.leader_key)
-        .unwrap_or_else(|e| {
+    let leader = match config::parse_leader(&cfg.expand_modifier_groups(&cfg.leader_key)) {
+        Ok(config::LeaderKey::Chord { modifiers, code }) => config::LeaderKey::Chord {
+            modifiers: Some(
+                modifiers.unwrap_or(config::Modifiers::SUPER | config::Modifiers::SHIFT),
+            ),
+            code,
+        },
+        Ok(leader) => leader,
+        Err(e) => {
             eprintln!(
                 "Warning: Invalid leader key '{}': {}. Falling back to cmd+shift+a.",
                 cfg.leader_key, e
             );
-            (
-                Some(config::Modifiers::SUPER | config::Modifiers::SHIFT),
-                config::KeyCode::KeyA,
-            )
-        });
+            config::LeaderKey::Chord {
+                modifiers: Some(config::Modifiers::SUPER | config::Modifiers::SHIFT),
+                code: config::KeyCode::KeyA,
+            }
+        }
+    };
+    keymap::set_matching(cfg.key_matching);
     let keybinds = cfg.parsed_keybinds();
     if keybinds.len() != cfg.keybinds.len() {
         eprintln!("Warning: Some keybinds are invalid and were ignored.");
     }
+    for conflict in cfg.keybind_conflicts() {
+        eprintln!("Warning: {}", conflict);
+    }
+    for ignored in cfg.ignored_picker_keybinds() {
+        eprintln!("Warning: {}", ignored);
+    }
 
     (
-        leader_modifiers.unwrap_or(config::Modifiers::SUPER | config::Modifiers::SHIFT),
-        leader_keycode,
+        leader,
+        Duration::from_millis(cfg.leader_tap_interval_ms),
         keybinds,
         Duration::from_secs(cfg.timeout),
     )
@@ -351,7 +574,9 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
         std::process::exit(1);
     });
     apply_autostart_setting(config.autostart);
-    let (leader_modifiers, leader_keycode, keybinds, leader_timeout) = runtime_bindings(&config);
+    keymap::refresh_layout();
+    keymap::watch_layout_changes();
+    let (leader, tap_interval, keybinds, leader_timeout) = runtime_bindings(&config);
     let leader_keybinds: Vec<_> = keybinds
         .iter()
         .filter(|k| matches!(k.keybind, config::Keybind::LeaderPrefixed { .. }))
@@ -390,10 +615,11 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
         println!("Running in headless mode (Ctrl+C to quit)...");
         run_headless_only(
             window_manager,
-            leader_modifiers,
-            leader_keycode,
+            leader,
+            tap_interval,
             keybinds,
             leader_timeout,
+            config.get_modes(),
         )?;
         return Ok(());
     }
@@ -401,9 +627,14 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
     enum UiAction {
         ShowWindowPicker,
         ShowSelectWindowPicker,
+        ShowWindowHints,
+        ShowCommandPalette,
+        ShowAppLauncher,
         PickerInput(ui::PickerInput),
+        HintInput(hints::HintInput),
         MenuBarRefresh,
         MenuBarSetActive(bool),
+        MenuBarSetMode(Option<String>),
         Quit,
     }
 
@@ -412,6 +643,7 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
         tokio::sync::mpsc::unbounded_channel::<EventTapAction>();
 
     let wm_for_events = Arc::clone(&window_manager);
+    let modes = config.get_modes();
     let menubar_enabled = config.menubar_icon;
     let menubar_active_color = config.menubar_active_color.clone();
 
@@ -427,13 +659,12 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
             }
 
             tracing::trace!(
-                "creating event tap with leader_modifiers={:?}, leader_keycode={:?}",
-                leader_modifiers,
-                leader_keycode
+                "creating event tap with leader={:?}",
+                leader
             );
             let event_tap = event_tap::EventTap::new(
-                leader_modifiers,
-                leader_keycode,
+                leader,
+                tap_interval,
                 keybinds.clone(),
                 event_sender.clone(),
             );
@@ -451,10 +682,12 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                 LeaderModeController::with_timeout(leader_timeout)
                     .expect("Failed to create leader mode controller"),
             );
+            leader_mode_controller.set_modes(modes.clone());
 
             ui::init(cx);
 
-            cx.set_global(WindowManagerState);
+            cx.set_global(WindowManagerState(Arc::clone(&wm_for_events)));
+            cx.set_global(LeaderModeState(Arc::clone(&leader_mode_controller)));
 
             let menu_bar_controller = if menubar_enabled {
                 match menu_bar::MenuBarController::new(
@@ -500,7 +733,7 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                                     let _ = ui_sender.send(UiAction::MenuBarSetActive(true));
                                 }
                                 EventTapAction::KeyPressed(keycode, has_shift) => {
-                                    if let Some(letter) = keycode_to_letter(keycode) {
+                                    if let Some(letter) = keymap::keycode_to_letter(keycode) {
                                         controller.handle_key(letter, has_shift);
                                     }
                                 }
@@ -513,6 +746,21 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                                 EventTapAction::PickerInput(input) => {
                                     let _ = ui_sender.send(UiAction::PickerInput(input));
                                 }
+                                EventTapAction::HintInput(input) => {
+                                    let _ = ui_sender.send(UiAction::HintInput(input));
+                                }
+                                EventTapAction::ModeEscape => {
+                                    controller.exit_mode();
+                                }
+                                EventTapAction::CountDigit(digit) => {
+                                    controller.handle_digit(digit);
+                                }
+                                EventTapAction::RepeatLast => {
+                                    controller.repeat_last();
+                                }
+                                EventTapAction::LeaderReleased => {
+                                    controller.cancel();
+                                }
                             }
                         }
                         Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {}
@@ -562,44 +810,29 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                                 Action::Select => {
                                     let _ = ui_sender.send(UiAction::ShowSelectWindowPicker);
                                 }
+                                Action::Hints => {
+                                    let _ = ui_sender.send(UiAction::ShowWindowHints);
+                                }
+                                Action::Palette => {
+                                    let _ = ui_sender.send(UiAction::ShowCommandPalette);
+                                }
+                                Action::Launcher => {
+                                    let _ = ui_sender.send(UiAction::ShowAppLauncher);
+                                }
                                 _ => handle_keybind_action(&action, &wm),
                             },
-                            LeaderModeEvent::FocusDirection(direction) => {
-                                match accessibility::get_focused_window() {
-                                    Ok(focused_element) => {
-                                        match accessibility::get_window_rect(&focused_element) {
-                                            Ok(from_rect) => {
-                                                match accessibility::find_window_in_direction(
-                                                    &from_rect, direction,
-                                                ) {
-                                                    Ok(target_window) => {
-                                                        if let Err(e) = accessibility::focus_window(
-                                                            &target_window,
-                                                        ) {
-                                                            eprintln!(
-                                                                "✗ Failed to focus window: {}",
-                                                                e
-                                                            );
-                                                        }
-                                                    }
-                                                    Err(e) => {
-                                                        eprintln!(
-                                                            "✗ No window found {:?}: {}",
-                                                            direction, e
-                                                        )
-                                                    }
-                                                }
-                                            }
-                                            Err(e) => {
-                                                eprintln!("✗ Failed to get window rect: {}", e)
-                                            }
-                                        }
-                                    }
-                                    Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
-                                }
+                            LeaderModeEvent::FocusDirection(direction, count) => {
+                                focus_in_direction(direction, count);
+                            }
+                            LeaderModeEvent::ModeEntered(_) | LeaderModeEvent::ModeExited(_) => {
+                                let _ = ui_sender
+                                    .send(UiAction::MenuBarSetMode(controller.active_mode()));
                             }
                         }
-                        let _ = ui_sender.send(UiAction::MenuBarSetActive(false));
+                        // The icon stays active while a sticky mode captures keys
+                        let _ = ui_sender.send(UiAction::MenuBarSetActive(
+                            controller.active_mode().is_some(),
+                        ));
                         if refresh_menu {
                             let _ = ui_sender.send(UiAction::MenuBarRefresh);
                         }
@@ -611,20 +844,21 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                             match reload {
                                 Ok(new_config) => {
                                     let (
-                                        new_leader_modifiers,
-                                        new_leader_keycode,
+                                        new_leader,
+                                        new_tap_interval,
                                         new_keybinds,
                                         new_timeout,
                                     ) = runtime_bindings(&new_config);
                                     match event_tap::EventTap::new(
-                                        new_leader_modifiers,
-                                        new_leader_keycode,
+                                        new_leader,
+                                        new_tap_interval,
                                         new_keybinds,
                                         event_sender.clone(),
                                     ) {
                                         Ok(new_event_tap) => {
                                             event_tap = new_event_tap;
                                             controller.set_timeout(new_timeout);
+                                            controller.set_modes(new_config.get_modes());
                                             apply_autostart_setting(new_config.autostart);
                                             if new_config.menubar_icon != watched_menubar_icon
                                                 || new_config.menubar_active_color
@@ -689,12 +923,41 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                             })
                             .ok();
                         }
+                        UiAction::ShowWindowHints => {
+                            cx.update(ui::show_window_hints).ok();
+                        }
+                        UiAction::ShowCommandPalette => {
+                            cx.update(|cx| {
+                                unsafe {
+                                    let ns_app = NSApplication::sharedApplication(nil);
+                                    ns_app.activateIgnoringOtherApps_(true);
+                                }
+                                ui::show_command_palette(cx);
+                            })
+                            .ok();
+                        }
+                        UiAction::ShowAppLauncher => {
+                            cx.update(|cx| {
+                                unsafe {
+                                    let ns_app = NSApplication::sharedApplication(nil);
+                                    ns_app.activateIgnoringOtherApps_(true);
+                                }
+                                ui::show_app_launcher(cx);
+                            })
+                            .ok();
+                        }
                         UiAction::PickerInput(input) => {
                             cx.update(|cx| {
                                 ui::handle_picker_input(input, cx);
                             })
                             .ok();
                         }
+                        UiAction::HintInput(input) => {
+                            cx.update(|cx| {
+                                ui::handle_hint_input(input, cx);
+                            })
+                            .ok();
+                        }
                         UiAction::MenuBarRefresh => {
                             cx.update(|_| {
                                 if let Some(controller) = menu_bar_controller.as_ref() {
@@ -711,6 +974,14 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
                             })
                             .ok();
                         }
+                        UiAction::MenuBarSetMode(mode) => {
+                            cx.update(|_| {
+                                if let Some(controller) = menu_bar_controller.as_ref() {
+                                    controller.set_active_mode(mode.as_deref());
+                                }
+                            })
+                            .ok();
+                        }
                         UiAction::Quit => {
                             cx.update(|cx: &mut gpui::App| cx.quit()).ok();
                             break;
@@ -725,60 +996,22 @@ fn run_daemon(window_manager: Arc<WindowManager>, headless: bool) -> Result<()>
     Ok(())
 }
 
-fn keycode_to_letter(keycode: i64) -> Option<char> {
-    match keycode {
-        0 => Some('a'),
-        1 => Some('s'),
-        2 => Some('d'),
-        3 => Some('f'),
-        4 => Some('h'),
-        5 => Some('g'),
-        6 => Some('z'),
-        7 => Some('x'),
-        8 => Some('c'),
-        9 => Some('v'),
-        11 => Some('b'),
-        12 => Some('q'),
-        13 => Some('w'),
-        14 => Some('e'),
-        15 => Some('r'),
-        16 => Some('y'),
-        17 => Some('t'),
-        31 => Some('o'),
-        32 => Some('u'),
-        34 => Some('i'),
-        35 => Some('p'),
-        38 => Some('j'),
-        40 => Some('k'),
-        37 => Some('l'),
-        46 => Some('m'),
-        45 => Some('n'),
-        _ => None,
-    }
-}
-
 fn run_headless_only(
     window_manager: Arc<WindowManager>,
-    leader_modifiers: config::Modifiers,
-    leader_keycode: config::KeyCode,
+    leader: config::LeaderKey,
+    tap_interval: Duration,
     keybinds: Vec<config::KeybindEntry>,
     leader_timeout: Duration,
+    modes: HashMap<String, config::Mode>,
 ) -> Result<()> {
     let leader_mode_controller = Arc::new(LeaderModeController::with_timeout(leader_timeout)?);
+    leader_mode_controller.set_modes(modes);
 
     let (event_sender, mut event_receiver) =
         tokio::sync::mpsc::unbounded_channel::<EventTapAction>();
-    tracing::trace!(
-        "creating headless event tap with leader_modifiers={:?}, leader_keycode={:?}",
-        leader_modifiers,
-        leader_keycode
-    );
-    let event_tap = event_tap::EventTap::new(
-        leader_modifiers,
-        leader_keycode,
-        keybinds.clone(),
-        event_sender.clone(),
-    );
+    tracing::trace!("creating headless event tap with leader={:?}", leader);
+    let event_tap =
+        event_tap::EventTap::new(leader, tap_interval, keybinds.clone(), event_sender.clone());
 
     if let Err(e) = &event_tap {
         eprintln!("\n❌ Failed to create event tap:\n{}\n", e);
@@ -809,7 +1042,7 @@ fn run_headless_only(
                         println!("Listening...");
                     }
                     EventTapAction::KeyPressed(keycode, has_shift) => {
-                        if let Some(letter) = keycode_to_letter(keycode) {
+                        if let Some(letter) = keymap::keycode_to_letter(keycode) {
                             controller_for_event.handle_key(letter, has_shift);
                         }
                     }
@@ -819,7 +1052,19 @@ fn run_headless_only(
                     EventTapAction::ArrowPressed(direction) => {
                         controller_for_event.handle_direction(direction);
                     }
-                    EventTapAction::PickerInput(_) => {}
+                    EventTapAction::PickerInput(_) | EventTapAction::HintInput(_) => {}
+                    EventTapAction::ModeEscape => {
+                        controller_for_event.exit_mode();
+                    }
+                    EventTapAction::CountDigit(digit) => {
+                        controller_for_event.handle_digit(digit);
+                    }
+                    EventTapAction::RepeatLast => {
+                        controller_for_event.repeat_last();
+                    }
+                    EventTapAction::LeaderReleased => {
+                        controller_for_event.cancel();
+                    }
                 },
                 Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {}
                 Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
@@ -859,29 +1104,14 @@ fn run_headless_only(
                     LeaderModeEvent::KeybindAction(action) => {
                         handle_keybind_action(&action, &wm_for_events);
                     }
-                    LeaderModeEvent::FocusDirection(direction) => {
-                        match accessibility::get_focused_window() {
-                            Ok(focused_element) => {
-                                match accessibility::get_window_rect(&focused_element) {
-                                    Ok(from_rect) => match accessibility::find_window_in_direction(
-                                        &from_rect, direction,
-                                    ) {
-                                        Ok(target_window) => {
-                                            if let Err(e) =
-                                                accessibility::focus_window(&target_window)
-                                            {
-                                                eprintln!("✗ Failed to focus window: {}", e);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            eprintln!("✗ No window found {:?}: {}", direction, e)
-                                        }
-                                    },
-                                    Err(e) => eprintln!("✗ Failed to get window rect: {}", e),
-                                }
-                            }
-                            Err(e) => eprintln!("✗ Failed to get focused window: {}", e),
-                        }
+                    LeaderModeEvent::FocusDirection(direction, count) => {
+                        focus_in_direction(direction, count);
+                    }
+                    LeaderModeEvent::ModeEntered(name) => {
+                        println!("{} mode (esc to exit)", name);
+                    }
+                    LeaderModeEvent::ModeExited(name) => {
+                        println!("{} mode off", name);
                     }
                 }
             }
@@ -891,21 +1121,18 @@ fn run_headless_only(
                 if let Some(reload) = config_watcher.poll_changed() {
                     match reload {
                         Ok(new_config) => {
-                            let (
-                                new_leader_modifiers,
-                                new_leader_keycode,
-                                new_keybinds,
-                                new_timeout,
-                            ) = runtime_bindings(&new_config);
+                            let (new_leader, new_tap_interval, new_keybinds, new_timeout) =
+                                runtime_bindings(&new_config);
                             match event_tap::EventTap::new(
-                                new_leader_modifiers,
-                                new_leader_keycode,
+                                new_leader,
+                                new_tap_interval,
                                 new_keybinds,
                                 event_sender.clone(),
                             ) {
                                 Ok(new_event_tap) => {
                                     event_tap = new_event_tap;
                                     controller_for_event.set_timeout(new_timeout);
+                                    controller_for_event.set_modes(new_config.get_modes());
                                     apply_autostart_setting(new_config.autostart);
                                     println!("↻ Reloaded config");
                                 }
@@ -930,8 +1157,13 @@ fn run_headless_only(
         }
     });
 
+    // Run the main run loop so keyboard layout changes are delivered
     while RUNNING.load(Ordering::SeqCst) {
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        core_foundation::runloop::CFRunLoop::run_in_mode(
+            unsafe { core_foundation::runloop::kCFRunLoopDefaultMode },
+            Duration::from_millis(100),
+            false,
+        );
     }
 
     Ok(())
@@ -90,6 +90,31 @@ impl MenuBarController {
         }
     }
 
+    /// Show the name of the sticky mode capturing keys next to the icon, or clear it.
+    pub fn set_active_mode(&self, mode: Option<&str>) {
+        unsafe {
+            let button = self.status_item.button();
+            if button == nil {
+                return;
+            }
+
+            let icon_title = if self.icon_image != nil { "" } else { "🧚" };
+            let (title, tooltip) = match mode {
+                Some(mode) => (
+                    format!("{} {}", icon_title, mode),
+                    format!("Pixie: {} mode (esc to exit)", mode),
+                ),
+                None => (icon_title.to_string(), "Pixie".to_string()),
+            };
+            let title = NSString::alloc(nil).init_str(title.trim_start());
+            let tooltip = NSString::alloc(nil).init_str(&tooltip);
+            // NSImageLeft
+            let _: () = msg_send![button, setImagePosition: 2u64];
+            let _: () = msg_send![button, setTitle: title];
+            let _: () = msg_send![button, setToolTip: tooltip];
+        }
+    }
+
     pub fn refresh_menu(&self) {
         unsafe {
             let menu = NSMenu::new(nil);
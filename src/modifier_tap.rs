@@ -0,0 +1,210 @@
+//! Leader activation from a lone modifier key: tapping it twice, or holding it down.
+//!
+//! The detector only sees presses and releases of the watched key, the other keys typed and
+//! "something else was used" interruptions, so it is independent of how the events are
+//! delivered.
+
+use std::time::{Duration, Instant};
+
+/// How the watched modifier key enters leader mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapGesture {
+    DoubleTap,
+    Hold,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapEvent {
+    /// Enter leader mode
+    Activate,
+    /// A held leader key was let go
+    Release,
+}
+
+#[derive(Debug)]
+pub struct TapDetector {
+    gesture: TapGesture,
+    interval: Duration,
+    /// When the watched key went down, while it is down
+    pressed_at: Option<Instant>,
+    /// When the last clean tap ended, if it may still start a double tap
+    last_tap: Option<Instant>,
+    /// Another key was used since the watched key went down
+    interrupted: bool,
+    /// A held key activated the leader and has not been released yet
+    holding: bool,
+}
+
+impl TapDetector {
+    /// `interval` bounds both how long a tap may be held and the pause between two taps.
+    pub fn new(gesture: TapGesture, interval: Duration) -> Self {
+        TapDetector {
+            gesture,
+            interval,
+            pressed_at: None,
+            last_tap: None,
+            interrupted: false,
+            holding: false,
+        }
+    }
+
+    /// The watched key went down (`pressed`) or up at `now`.
+    pub fn modifier_changed(&mut self, pressed: bool, now: Instant) -> Option<TapEvent> {
+        if pressed {
+            if self.pressed_at.is_some() && self.gesture == TapGesture::DoubleTap {
+                // Repeated press without a release in between (e.g. a lost event)
+                return None;
+            }
+            self.pressed_at = Some(now);
+            self.interrupted = false;
+            match self.gesture {
+                TapGesture::Hold => None,
+                TapGesture::DoubleTap => {
+                    let double = self
+                        .last_tap
+                        .take()
+                        .is_some_and(|tap| now.duration_since(tap) <= self.interval);
+                    if double {
+                        // The second press activates; its release must not start a new tap
+                        self.interrupted = true;
+                        Some(TapEvent::Activate)
+                    } else {
+                        None
+                    }
+                }
+            }
+        } else {
+            let pressed_at = self.pressed_at.take();
+            match self.gesture {
+                TapGesture::Hold => std::mem::take(&mut self.holding).then_some(TapEvent::Release),
+                TapGesture::DoubleTap => {
+                    self.last_tap = pressed_at
+                        .filter(|&at| !self.interrupted && now.duration_since(at) <= self.interval)
+                        .map(|_| now);
+                    None
+                }
+            }
+        }
+    }
+
+    /// A key other than a modifier went down at `now`.
+    ///
+    /// A held key activates the leader here, if it has been down for the interval with
+    /// nothing else used in between, and the key is then read by the leader. Any earlier key
+    /// is a shortcut using the modifier and passes through.
+    pub fn key_pressed(&mut self, now: Instant) -> Option<TapEvent> {
+        let ready = self.gesture == TapGesture::Hold
+            && !self.interrupted
+            && self
+                .pressed_at
+                .is_some_and(|at| now.duration_since(at) >= self.interval);
+        self.interrupt();
+        if ready {
+            self.holding = true;
+            Some(TapEvent::Activate)
+        } else {
+            None
+        }
+    }
+
+    /// Another key or modifier was used, so the current press is not a tap.
+    pub fn interrupt(&mut self) {
+        self.interrupted = true;
+        self.last_tap = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(300);
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn double_tap_within_interval_activates() {
+        let start = Instant::now();
+        let mut detector = TapDetector::new(TapGesture::DoubleTap, INTERVAL);
+
+        assert_eq!(detector.modifier_changed(true, start), None);
+        assert_eq!(detector.modifier_changed(false, ms(start, 80)), None);
+        assert_eq!(
+            detector.modifier_changed(true, ms(start, 250)),
+            Some(TapEvent::Activate)
+        );
+        assert_eq!(detector.modifier_changed(false, ms(start, 300)), None);
+
+        // A third tap right after does not activate again
+        assert_eq!(detector.modifier_changed(true, ms(start, 400)), None);
+    }
+
+    #[test]
+    fn slow_or_long_taps_do_not_activate() {
+        let start = Instant::now();
+        let mut detector = TapDetector::new(TapGesture::DoubleTap, INTERVAL);
+
+        // Too long a pause between taps
+        detector.modifier_changed(true, start);
+        detector.modifier_changed(false, ms(start, 50));
+        assert_eq!(detector.modifier_changed(true, ms(start, 500)), None);
+
+        // Held too long to count as a tap
+        detector.modifier_changed(false, ms(start, 900));
+        assert_eq!(detector.modifier_changed(true, ms(start, 950)), None);
+    }
+
+    #[test]
+    fn using_the_modifier_in_a_shortcut_is_not_a_tap() {
+        let start = Instant::now();
+        let mut detector = TapDetector::new(TapGesture::DoubleTap, INTERVAL);
+
+        // cmd+c, then a quick tap of cmd
+        detector.modifier_changed(true, start);
+        detector.interrupt();
+        detector.modifier_changed(false, ms(start, 100));
+        assert_eq!(detector.modifier_changed(true, ms(start, 150)), None);
+
+        // A key between two taps breaks the sequence too
+        detector.modifier_changed(false, ms(start, 200));
+        detector.interrupt();
+        assert_eq!(detector.modifier_changed(true, ms(start, 250)), None);
+    }
+
+    #[test]
+    fn hold_activates_on_the_first_key_after_the_interval() {
+        let start = Instant::now();
+        let mut detector = TapDetector::new(TapGesture::Hold, INTERVAL);
+
+        assert_eq!(detector.modifier_changed(true, start), None);
+        assert_eq!(
+            detector.key_pressed(ms(start, 400)),
+            Some(TapEvent::Activate)
+        );
+        assert_eq!(detector.key_pressed(ms(start, 500)), None);
+        assert_eq!(
+            detector.modifier_changed(false, ms(start, 2000)),
+            Some(TapEvent::Release)
+        );
+        assert_eq!(detector.modifier_changed(false, ms(start, 2100)), None);
+    }
+
+    #[test]
+    fn quick_shortcuts_with_a_hold_key_pass_through() {
+        let start = Instant::now();
+        let mut detector = TapDetector::new(TapGesture::Hold, INTERVAL);
+
+        // rcmd+c right after pressing rcmd, then another key while still holding it
+        detector.modifier_changed(true, start);
+        assert_eq!(detector.key_pressed(ms(start, 100)), None);
+        assert_eq!(detector.key_pressed(ms(start, 600)), None);
+        assert_eq!(detector.modifier_changed(false, ms(start, 700)), None);
+
+        // Another modifier used during the hold
+        detector.modifier_changed(true, ms(start, 800));
+        detector.interrupt();
+        assert_eq!(detector.key_pressed(ms(start, 1500)), None);
+    }
+}
@@ -0,0 +1,224 @@
+//! Command palette entries: every action, placement, mode, display and slot by name.
+//!
+//! The window picker switches to these entries in palette mode and searches them like
+//! windows, so actions without a keybind can be found without looking them up.
+
+use crate::config::{Action, DisplayTarget, Nudge, Resize};
+use crate::direction::Direction;
+use crate::fuzzy::{FieldsMatch, match_fields};
+
+/// What choosing a palette entry does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaletteCommand {
+    /// Run the action as if its keybind was pressed
+    Action(Action),
+    /// Focus the window saved in the slot
+    FocusSlot(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaletteEntry {
+    pub title: String,
+    /// What kind of entry it is, like "Placement"
+    pub category: &'static str,
+    pub command: PaletteCommand,
+}
+
+impl PaletteEntry {
+    fn new(title: impl Into<String>, category: &'static str, command: PaletteCommand) -> Self {
+        Self {
+            title: title.into(),
+            category,
+            command,
+        }
+    }
+
+    fn action(title: impl Into<String>, action: Action) -> Self {
+        Self::new(title, "Action", PaletteCommand::Action(action))
+    }
+
+    /// Fuzzy match `query` against the title and category, positions in that order.
+    pub fn matches(&self, query: &str) -> Option<FieldsMatch> {
+        match_fields(query, &[&self.title, self.category])
+    }
+}
+
+/// Actions that take no configuration, with their palette titles
+fn fixed_actions() -> Vec<PaletteEntry> {
+    let directions = [
+        (
+            "left",
+            Direction::Left,
+            [
+                Action::FocusLeft,
+                Action::SwapLeft,
+                Action::MoveLeft,
+                Action::MoveMonitorLeft,
+            ],
+        ),
+        (
+            "right",
+            Direction::Right,
+            [
+                Action::FocusRight,
+                Action::SwapRight,
+                Action::MoveRight,
+                Action::MoveMonitorRight,
+            ],
+        ),
+        (
+            "up",
+            Direction::Up,
+            [
+                Action::FocusUp,
+                Action::SwapUp,
+                Action::MoveUp,
+                Action::MoveMonitorUp,
+            ],
+        ),
+        (
+            "down",
+            Direction::Down,
+            [
+                Action::FocusDown,
+                Action::SwapDown,
+                Action::MoveDown,
+                Action::MoveMonitorDown,
+            ],
+        ),
+    ];
+
+    let mut entries = Vec::new();
+    for (name, direction, [focus, swap, step, monitor]) in directions {
+        entries.push(PaletteEntry::action(format!("Focus {}", name), focus));
+        entries.push(PaletteEntry::action(format!("Swap {}", name), swap));
+        entries.push(PaletteEntry::action(format!("Move {}", name), step));
+        entries.push(PaletteEntry::action(
+            format!("Nudge {}", name),
+            Action::Nudge(Nudge::towards(direction)),
+        ));
+        entries.push(PaletteEntry::action(
+            format!("Move to monitor {}", name),
+            monitor,
+        ));
+    }
+    entries.extend([
+        PaletteEntry::action("Grow", Action::Grow(Resize::default())),
+        PaletteEntry::action("Shrink", Action::Shrink(Resize::default())),
+        PaletteEntry::action("Minimize", Action::Minimize),
+        PaletteEntry::action("Close", Action::Close),
+        PaletteEntry::action("Maximize", Action::Maximize),
+        PaletteEntry::action("Toggle fullscreen", Action::Fullscreen),
+        PaletteEntry::action("Center", Action::Center),
+        PaletteEntry::action("Tile windows", Action::Tile),
+        PaletteEntry::action("Select window", Action::Select),
+        PaletteEntry::action("Window hints", Action::Hints),
+        PaletteEntry::action("Launch app", Action::Launcher),
+        PaletteEntry::action("Undo", Action::Undo),
+        PaletteEntry::action("Redo", Action::Redo),
+        PaletteEntry::action("Restore", Action::Restore),
+    ]);
+    entries
+}
+
+/// All palette entries: the fixed actions, then one per placement, mode, display (by name,
+/// in display order) and saved slot (with a description of its window).
+pub fn palette_entries(
+    placements: &[String],
+    modes: &[String],
+    displays: &[String],
+    slots: &[(char, String)],
+) -> Vec<PaletteEntry> {
+    let mut entries = fixed_actions();
+
+    let mut placements = placements.to_vec();
+    placements.sort();
+    entries.extend(placements.into_iter().map(|name| {
+        PaletteEntry::new(
+            format!("Place {}", name),
+            "Placement",
+            PaletteCommand::Action(Action::Place(name)),
+        )
+    }));
+
+    let mut modes = modes.to_vec();
+    modes.sort();
+    entries.extend(modes.into_iter().map(|name| {
+        PaletteEntry::new(
+            format!("Enter {} mode", name),
+            "Mode",
+            PaletteCommand::Action(Action::EnterMode(name)),
+        )
+    }));
+
+    entries.extend(displays.iter().enumerate().map(|(index, name)| {
+        PaletteEntry::new(
+            format!("Move to display {} ({})", index + 1, name),
+            "Display",
+            PaletteCommand::Action(Action::MoveToDisplay(DisplayTarget::Index(index + 1))),
+        )
+    }));
+
+    let mut slots = slots.to_vec();
+    slots.sort();
+    entries.extend(slots.into_iter().map(|(slot, window)| {
+        PaletteEntry::new(
+            format!("Focus [{}] {}", slot, window),
+            "Slot",
+            PaletteCommand::FocusSlot(slot),
+        )
+    }));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_every_source_in_order() {
+        let entries = palette_entries(
+            &["right".into(), "left".into()],
+            &["resize".into()],
+            &["Built-in".into(), "DELL".into()],
+            &[('b', "Slack".into()), ('a', "Safari".into())],
+        );
+        let tail: Vec<_> = entries[entries.len() - 7..]
+            .iter()
+            .map(|entry| (entry.title.as_str(), entry.category))
+            .collect();
+        assert_eq!(
+            tail,
+            vec![
+                ("Place left", "Placement"),
+                ("Place right", "Placement"),
+                ("Enter resize mode", "Mode"),
+                ("Move to display 1 (Built-in)", "Display"),
+                ("Move to display 2 (DELL)", "Display"),
+                ("Focus [a] Safari", "Slot"),
+                ("Focus [b] Slack", "Slot"),
+            ]
+        );
+        assert_eq!(
+            entries.last().map(|entry| &entry.command),
+            Some(&PaletteCommand::FocusSlot('b'))
+        );
+        assert!(entries.iter().any(|entry| entry.command
+            == PaletteCommand::Action(Action::Nudge(Nudge::towards(Direction::Up)))));
+    }
+
+    #[test]
+    fn matches_title_and_category() {
+        let entry = PaletteEntry::new(
+            "Place left",
+            "Placement",
+            PaletteCommand::Action(Action::Place("left".into())),
+        );
+        assert_eq!(
+            entry.matches("left").map(|m| m.positions),
+            Some(vec![vec![6, 7, 8, 9], vec![]])
+        );
+        assert!(entry.matches("placement left").is_some());
+        assert!(entry.matches("mode").is_none());
+    }
+}
@@ -0,0 +1,1330 @@
+//! Window picker behavior, independent of the UI.
+//!
+//! [`PickerModel`] holds everything the picker shows and turns each [`PickerInput`] into a new
+//! state plus the [`PickerEffect`]s to carry out (raising a preview, tiling, focusing,
+//! closing, running an action on a window). The GPUI picker renders the model and executes
+//! the effects.
+//!
+//! The search query is whitespace-separated terms that all have to match. Plain terms are
+//! fuzzy matched against the app name and title; `app:`, `title:`, `screen:` and `slot:` narrow
+//! a term to one property, `minimized` matches windows minimized to the Dock, and a leading `!`
+//! inverts any term.
+//!
+//! In palette mode the rows are [`PaletteEntry`]s instead of windows, matched with plain fuzzy
+//! search, and confirming runs the focused entry. In launcher mode installed apps that have no
+//! windows are listed after the windows, and confirming switches to the window or launches the
+//! app.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::config::{Action, DisplayTarget, PickerAction};
+use crate::fuzzy::{FieldsMatch, fuzzy_match, match_fields};
+use crate::geometry::Rect;
+use crate::palette::{PaletteCommand, PaletteEntry};
+use crate::tiling::TileLayout;
+
+/// (pid, window id)
+pub type WindowIdentity = (i32, u32);
+
+/// Characters with a fixed meaning in the picker, which `[picker_keybinds]` can't use
+pub const RESERVED_KEYS: [char; 12] = ['j', 'k', 'J', 'K', 'n', 'N', 'q', 's', '/', ':', ' ', '\''];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerInput {
+    SelectDown,
+    SelectUp,
+    ToggleSelect,
+    Confirm,
+    Cancel,
+    SearchBackspace,
+    SearchChar(char),
+    /// The mouse moved over the window at this index
+    Hover(usize),
+    /// The window at this index was clicked
+    Click(usize),
+    /// Switch to the next tiling layout
+    NextLayout,
+    /// Tile onto the next display
+    NextScreen,
+}
+
+/// A window listed in the picker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickerWindow {
+    pub pid: i32,
+    pub window_id: u32,
+    pub app_name: String,
+    pub title: String,
+    pub app_icon_path: Option<String>,
+    /// 1-based number of the display showing the window
+    pub screen: Option<usize>,
+    /// Minimized to the Dock
+    pub minimized: bool,
+    /// Slots the window is saved in
+    pub slots: Vec<char>,
+}
+
+impl PickerWindow {
+    pub fn identity(&self) -> WindowIdentity {
+        (self.pid, self.window_id)
+    }
+}
+
+/// An installed app listed by the launcher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickerApp {
+    pub name: String,
+    /// Passed to `WindowManager::launch_app`
+    pub launch: String,
+    pub icon_path: Option<String>,
+}
+
+impl PickerApp {
+    /// The app as a row without a title, display or slots, for matching the query
+    fn as_window(&self) -> PickerWindow {
+        PickerWindow {
+            pid: 0,
+            window_id: 0,
+            app_name: self.name.clone(),
+            title: String::new(),
+            app_icon_path: None,
+            screen: None,
+            minimized: false,
+            slots: Vec::new(),
+        }
+    }
+}
+
+/// What the picker lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PickerMode {
+    #[default]
+    Windows,
+    /// The command palette; rows are indices into [`PickerModel::palette`]
+    Palette,
+    /// Windows, then apps without windows; row indices past the windows are
+    /// [`PickerModel::apps`]
+    Launcher,
+}
+
+/// Which windows the picker lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PickerScope {
+    #[default]
+    AllMonitors,
+    CurrentMonitor,
+    /// Windows of the app that was focused when the picker opened
+    CurrentApp,
+}
+
+impl PickerScope {
+    pub fn next(self) -> Self {
+        match self {
+            PickerScope::AllMonitors => PickerScope::CurrentMonitor,
+            PickerScope::CurrentMonitor => PickerScope::CurrentApp,
+            PickerScope::CurrentApp => PickerScope::AllMonitors,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PickerScope::AllMonitors => "all monitors",
+            PickerScope::CurrentMonitor => "current monitor",
+            PickerScope::CurrentApp => "current app",
+        }
+    }
+}
+
+/// What one query term tests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum QueryTerm {
+    /// Fuzzy match against the app name and title
+    Text(String),
+    App(String),
+    Title(String),
+    Screen(usize),
+    /// A specific slot, or any slot
+    Slot(Option<char>),
+    Minimized,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QueryFilter {
+    term: QueryTerm,
+    negated: bool,
+}
+
+impl QueryFilter {
+    /// Whether the filter ranks windows rather than only keeping or dropping them
+    fn scores(&self) -> bool {
+        !self.negated
+            && matches!(
+                self.term,
+                QueryTerm::Text(_) | QueryTerm::App(_) | QueryTerm::Title(_)
+            )
+    }
+}
+
+/// Split a query into filters. Prefixes without a value yet (like a half typed `app:`) are
+/// skipped, and values that don't parse are searched as text.
+fn parse_query(query: &str) -> Vec<QueryFilter> {
+    query
+        .split_whitespace()
+        .filter_map(|part| {
+            let (negated, part) = match part.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, part),
+            };
+            let term = match part.split_once(':') {
+                Some((_, "")) => return None,
+                Some(("app", value)) => QueryTerm::App(value.to_string()),
+                Some(("title", value)) => QueryTerm::Title(value.to_string()),
+                Some(("screen", value)) => match value.parse() {
+                    Ok(number) => QueryTerm::Screen(number),
+                    Err(_) => QueryTerm::Text(part.to_string()),
+                },
+                Some(("slot", "*")) => QueryTerm::Slot(None),
+                Some(("slot", value)) if value.chars().count() == 1 => {
+                    QueryTerm::Slot(value.chars().next())
+                }
+                _ if part.is_empty() => return None,
+                _ if part.eq_ignore_ascii_case("minimized") => QueryTerm::Minimized,
+                _ => QueryTerm::Text(part.to_string()),
+            };
+            Some(QueryFilter { term, negated })
+        })
+        .collect()
+}
+
+/// Match a window against all filters, combining the fuzzy scores and matched characters.
+fn match_window(filters: &[QueryFilter], window: &PickerWindow) -> Option<FieldsMatch> {
+    let mut score = 0;
+    let mut positions = vec![Vec::new(), Vec::new()];
+    for filter in filters {
+        let matched = match &filter.term {
+            QueryTerm::Text(text) => match_fields(text, &[&window.app_name, &window.title]),
+            QueryTerm::App(text) => fuzzy_match(text, &window.app_name).map(|m| FieldsMatch {
+                score: m.score,
+                positions: vec![m.positions, Vec::new()],
+            }),
+            QueryTerm::Title(text) => fuzzy_match(text, &window.title).map(|m| FieldsMatch {
+                score: m.score,
+                positions: vec![Vec::new(), m.positions],
+            }),
+            QueryTerm::Screen(number) => (window.screen == Some(*number)).then(no_match_details),
+            QueryTerm::Slot(slot) => match slot {
+                Some(slot) => window.slots.contains(&slot.to_ascii_lowercase()),
+                None => !window.slots.is_empty(),
+            }
+            .then(no_match_details),
+            QueryTerm::Minimized => window.minimized.then(no_match_details),
+        };
+
+        if filter.negated {
+            if matched.is_some() {
+                return None;
+            }
+            continue;
+        }
+        let matched = matched?;
+        score += matched.score;
+        for (field, matched) in positions.iter_mut().zip(matched.positions) {
+            field.extend(matched);
+        }
+    }
+
+    for field in &mut positions {
+        field.sort_unstable();
+        field.dedup();
+    }
+    Some(FieldsMatch { score, positions })
+}
+
+/// A match of a filter that doesn't look at the text
+fn no_match_details() -> FieldsMatch {
+    FieldsMatch {
+        score: 0,
+        positions: vec![Vec::new(), Vec::new()],
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickerEffect {
+    /// Raise the window to preview it, keeping the picker focused
+    Preview(WindowIdentity),
+    /// Tile the windows with `layout` on the display numbered `screen` (1-based), or on the
+    /// picker's display
+    Tile {
+        windows: Vec<WindowIdentity>,
+        layout: TileLayout,
+        screen: Option<usize>,
+    },
+    Focus(WindowIdentity),
+    /// Run a keybind action on the window
+    Run(Action, WindowIdentity),
+    /// Save the window in a slot
+    AssignSlot(char, WindowIdentity),
+    /// Carry out a palette entry
+    Command(PaletteCommand),
+    /// Launch an installed app
+    Launch(String),
+    /// Close the picker window
+    Close,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PickerModel {
+    /// Windows of the current monitor first, then other monitors and minimized ones
+    pub windows: Vec<PickerWindow>,
+    pub current_monitor_count: usize,
+    pub focused_index: usize,
+    pub selected_indices: Vec<usize>,
+    pub search_mode: bool,
+    pub search_query: String,
+    pub scope: PickerScope,
+    /// Window indices in display order: the windows in scope, or the matches best first
+    /// while the query ranks them
+    pub visible: Vec<usize>,
+    /// The visible windows are ordered by match score instead of grouped by monitor
+    pub ranked: bool,
+    /// What the query matched in each visible window, by window index
+    pub matches: HashMap<usize, FieldsMatch>,
+    pub previously_focused_window: Option<WindowIdentity>,
+    pub last_previewed_window: Option<WindowIdentity>,
+    /// Keys that run an action on the focused window, sorted by key
+    pub keybinds: Vec<(char, PickerAction)>,
+    /// 1-based number of the display the picker is shown on
+    pub current_screen: Option<usize>,
+    /// `'` was pressed and the next letter saves the focused window in that slot
+    pub assigning_slot: bool,
+    pub layout: TileLayout,
+    pub screen_count: usize,
+    /// 1-based number of the display to tile on
+    pub target_screen: Option<usize>,
+    pub mode: PickerMode,
+    pub palette: Vec<PaletteEntry>,
+    /// Installed apps without windows, in launcher mode
+    pub apps: Vec<PickerApp>,
+}
+
+impl PickerModel {
+    /// `preselect_focused_window` starts with the previously focused window selected (for
+    /// tiling) rather than only focused.
+    pub fn new(
+        windows: Vec<PickerWindow>,
+        current_monitor_count: usize,
+        previously_focused_window: Option<WindowIdentity>,
+        preselect_focused_window: bool,
+    ) -> Self {
+        let focused_window_index = previously_focused_window
+            .and_then(|(_, id)| windows.iter().position(|w| w.window_id == id));
+        let selected_indices = if preselect_focused_window {
+            focused_window_index.into_iter().collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut model = PickerModel {
+            windows,
+            current_monitor_count,
+            focused_index: focused_window_index.unwrap_or_default(),
+            selected_indices,
+            previously_focused_window,
+            ..Default::default()
+        };
+        model.rebuild_search_matches();
+        model
+    }
+
+    /// Apply `input` and return what has to happen as a result, in order.
+    pub fn handle_input(&mut self, input: PickerInput) -> Vec<PickerEffect> {
+        if self.mode != PickerMode::Windows {
+            return self.handle_typing_input(input);
+        }
+        match input {
+            PickerInput::Hover(index) => {
+                if index < self.windows.len() {
+                    self.focused_index = index;
+                }
+                return self.preview().into_iter().collect();
+            }
+            PickerInput::Click(index) => {
+                if index < self.windows.len() {
+                    self.focused_index = index;
+                    self.toggle_selected(index);
+                }
+                return self.preview().into_iter().collect();
+            }
+            PickerInput::NextLayout => {
+                self.layout = self.layout.next();
+                return Vec::new();
+            }
+            PickerInput::NextScreen => {
+                if self.screen_count > 0 {
+                    self.target_screen = Some(
+                        self.target_screen
+                            .map_or(1, |screen| screen % self.screen_count + 1),
+                    );
+                }
+                return Vec::new();
+            }
+            _ => {}
+        }
+
+        if self.assigning_slot {
+            self.assigning_slot = false;
+            return match input {
+                PickerInput::SearchChar(ch) if ch.is_ascii_alphabetic() => {
+                    self.assign_slot(ch.to_ascii_lowercase())
+                }
+                _ => Vec::new(),
+            };
+        }
+
+        if self.search_mode {
+            match input {
+                PickerInput::Confirm | PickerInput::Cancel => self.search_mode = false,
+                PickerInput::SearchBackspace => {
+                    self.search_query.pop();
+                    self.rebuild_search_matches();
+                }
+                PickerInput::SearchChar(ch) => {
+                    self.search_query.push(ch);
+                    self.rebuild_search_matches();
+                }
+                _ => {}
+            }
+            return self.preview().into_iter().collect();
+        }
+
+        match input {
+            PickerInput::SelectDown | PickerInput::SearchChar('j') => self.move_focus(true),
+            PickerInput::SelectUp | PickerInput::SearchChar('k') => self.move_focus(false),
+            PickerInput::ToggleSelect | PickerInput::SearchChar(' ')
+                if self.focused_position().is_some() =>
+            {
+                self.toggle_selected(self.focused_index)
+            }
+            PickerInput::Confirm => return self.confirm(),
+            PickerInput::Cancel | PickerInput::SearchChar('q') => return self.cancel(),
+            PickerInput::SearchChar('/') => {
+                self.search_mode = true;
+                self.search_query.clear();
+                self.rebuild_search_matches();
+            }
+            PickerInput::SearchChar('J') => self.move_in_selection(true),
+            PickerInput::SearchChar('K') => self.move_in_selection(false),
+            PickerInput::SearchChar('n') if !self.search_query.is_empty() => self.move_focus(true),
+            PickerInput::SearchChar('N') if !self.search_query.is_empty() => self.move_focus(false),
+            PickerInput::SearchChar('s') => {
+                self.scope = self.scope.next();
+                self.rebuild_search_matches();
+            }
+            PickerInput::SearchChar(':') => {
+                self.open_palette();
+                return Vec::new();
+            }
+            PickerInput::SearchChar('\'') if self.focused_position().is_some() => {
+                self.assigning_slot = true;
+            }
+            PickerInput::SearchChar(ch) => {
+                if let Some((_, action)) = self.keybinds.iter().find(|(key, _)| *key == ch) {
+                    return self.run_action(action.clone());
+                }
+            }
+            _ => {}
+        }
+        self.preview().into_iter().collect()
+    }
+
+    /// Show the command palette instead of the windows, with an empty query.
+    pub fn open_palette(&mut self) {
+        self.mode = PickerMode::Palette;
+        self.search_mode = false;
+        self.search_query.clear();
+        self.focused_index = 0;
+        self.rebuild_search_matches();
+    }
+
+    /// List the windows together with the installed `apps` that have none, so confirming
+    /// either switches to a window or launches an app.
+    pub fn open_launcher(&mut self, apps: Vec<PickerApp>) {
+        let running: HashSet<String> = self
+            .windows
+            .iter()
+            .map(|window| window.app_name.to_lowercase())
+            .collect();
+        self.apps = apps
+            .into_iter()
+            .filter(|app| !running.contains(&app.name.to_lowercase()))
+            .collect();
+        self.mode = PickerMode::Launcher;
+        self.search_mode = false;
+        self.search_query.clear();
+        self.selected_indices.clear();
+        self.rebuild_search_matches();
+    }
+
+    /// The app shown in the row for `index`, in launcher mode
+    pub fn app_at(&self, index: usize) -> Option<&PickerApp> {
+        index
+            .checked_sub(self.windows.len())
+            .and_then(|index| self.apps.get(index))
+    }
+
+    /// Number of row indices in the current mode
+    fn entry_count(&self) -> usize {
+        match self.mode {
+            PickerMode::Windows => self.windows.len(),
+            PickerMode::Palette => self.palette.len(),
+            PickerMode::Launcher => self.windows.len() + self.apps.len(),
+        }
+    }
+
+    /// In the palette and launcher typing filters the rows; there are no single-key commands.
+    fn handle_typing_input(&mut self, input: PickerInput) -> Vec<PickerEffect> {
+        let confirm = |model: &mut Self| match model.mode {
+            PickerMode::Launcher => model.switch_or_launch(),
+            _ => model.run_command(),
+        };
+        match input {
+            PickerInput::SelectDown => self.move_focus(true),
+            PickerInput::SelectUp => self.move_focus(false),
+            PickerInput::Hover(index) if index < self.entry_count() => self.focused_index = index,
+            PickerInput::Click(index) if index < self.entry_count() => {
+                self.focused_index = index;
+                return confirm(self);
+            }
+            PickerInput::Confirm => return confirm(self),
+            PickerInput::Cancel => return self.cancel(),
+            PickerInput::SearchBackspace => {
+                self.search_query.pop();
+                self.rebuild_search_matches();
+            }
+            PickerInput::SearchChar(ch) => {
+                self.search_query.push(ch);
+                self.rebuild_search_matches();
+            }
+            PickerInput::ToggleSelect => {
+                self.search_query.push(' ');
+                self.rebuild_search_matches();
+            }
+            _ => {}
+        }
+        self.preview().into_iter().collect()
+    }
+
+    /// Close the picker and focus the focused window, or launch the focused app.
+    fn switch_or_launch(&mut self) -> Vec<PickerEffect> {
+        if self.focused_position().is_none() {
+            return Vec::new();
+        }
+        let mut effects = vec![PickerEffect::Close];
+        match self.app_at(self.focused_index) {
+            Some(app) => effects.push(PickerEffect::Launch(app.launch.clone())),
+            None => effects.extend(
+                self.windows
+                    .get(self.focused_index)
+                    .map(|window| PickerEffect::Focus(window.identity())),
+            ),
+        }
+        effects
+    }
+
+    /// Close the picker and carry out the focused palette entry. Actions run on the window
+    /// that was focused before the picker opened, so it gets the focus back first.
+    fn run_command(&mut self) -> Vec<PickerEffect> {
+        let Some(entry) = self
+            .focused_position()
+            .and_then(|_| self.palette.get(self.focused_index))
+        else {
+            return Vec::new();
+        };
+        let command = entry.command.clone();
+        let mut effects = vec![PickerEffect::Close];
+        if matches!(command, PaletteCommand::Action(_)) {
+            effects.extend(self.previously_focused_window.map(PickerEffect::Focus));
+        }
+        effects.push(PickerEffect::Command(command));
+        effects
+    }
+
+    /// The key hints for the footer, including the configured window actions.
+    pub fn hint(&self) -> String {
+        match self.mode {
+            PickerMode::Palette => {
+                return "type to filter • ↑/↓ navigate • enter run • esc cancel".to_string();
+            }
+            PickerMode::Launcher => {
+                return "type to filter • ↑/↓ navigate • enter switch/launch • esc cancel"
+                    .to_string();
+            }
+            PickerMode::Windows => {}
+        }
+        if self.assigning_slot {
+            return "a-z assign slot • esc cancel".to_string();
+        }
+        if self.search_mode {
+            return "type to filter • enter/esc exit".to_string();
+        }
+        let mut hints: Vec<String> = [
+            "j/k navigate",
+            "space select",
+            "J/K reorder",
+            "/ search",
+            "n/N next/prev",
+            "s scope",
+            ": commands",
+            "tab layout",
+            "⇧tab display",
+            "' slot",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        hints.extend(
+            self.keybinds
+                .iter()
+                .map(|(key, action)| format!("{} {}", key, action.label())),
+        );
+        hints.push("enter tile".to_string());
+        hints.push("esc cancel".to_string());
+        hints.join(" • ")
+    }
+
+    /// 1-based position of the window in the selection, which is the order it is tiled in
+    pub fn selection_ordinal(&self, index: usize) -> Option<usize> {
+        self.selected_indices
+            .iter()
+            .position(|&i| i == index)
+            .map(|position| position + 1)
+    }
+
+    /// Indices of the windows confirming would tile: the selection, or else the focused
+    /// window if it is shown.
+    pub fn tile_targets(&self) -> Vec<usize> {
+        if self.selected_indices.is_empty() {
+            self.focused_position()
+                .map(|_| self.focused_index)
+                .into_iter()
+                .collect()
+        } else {
+            self.selected_indices.clone()
+        }
+    }
+
+    /// Where each window to tile would land in `area` with the current layout, by window
+    /// index.
+    pub fn tile_preview(&self, area: Rect) -> Vec<(usize, Rect)> {
+        let targets = self.tile_targets();
+        let frames = self.layout.frames(targets.len(), area);
+        targets.into_iter().zip(frames).collect()
+    }
+
+    /// Position in `visible` where the other monitors and minimized windows start, when the
+    /// list shows both groups.
+    pub fn secondary_group_start(&self) -> Option<usize> {
+        if self.ranked || self.mode != PickerMode::Windows {
+            return None;
+        }
+        let start = self
+            .visible
+            .iter()
+            .position(|&index| index >= self.current_monitor_count)?;
+        (start > 0).then_some(start)
+    }
+
+    /// Rows in the list, including the group separator
+    pub fn row_count(&self) -> usize {
+        self.visible.len() + usize::from(self.secondary_group_start().is_some())
+    }
+
+    /// The window index shown in a row, `None` for the separator
+    pub fn window_at_row(&self, row: usize) -> Option<usize> {
+        let position = match self.secondary_group_start() {
+            Some(start) if row == start => return None,
+            Some(start) if row > start => row - 1,
+            _ => row,
+        };
+        self.visible.get(position).copied()
+    }
+
+    /// The row of the focused window, if it is shown
+    pub fn focused_row(&self) -> Option<usize> {
+        self.focused_position()
+            .map(|position| match self.secondary_group_start() {
+                Some(start) if position >= start => position + 1,
+                _ => position,
+            })
+    }
+
+    /// Position of the focused window in the visible list, if it is shown
+    pub fn focused_position(&self) -> Option<usize> {
+        self.visible
+            .iter()
+            .position(|&index| index == self.focused_index)
+    }
+
+    fn toggle_selected(&mut self, index: usize) {
+        if self.selected_indices.contains(&index) {
+            self.selected_indices.retain(|i| *i != index);
+        } else {
+            self.selected_indices.push(index);
+        }
+    }
+
+    /// Move the focused window one place later or earlier in the selection order.
+    fn move_in_selection(&mut self, later: bool) {
+        let Some(position) = self
+            .selected_indices
+            .iter()
+            .position(|&i| i == self.focused_index)
+        else {
+            return;
+        };
+        let other = if later {
+            position + 1
+        } else {
+            match position.checked_sub(1) {
+                Some(other) => other,
+                None => return,
+            }
+        };
+        if other < self.selected_indices.len() {
+            self.selected_indices.swap(position, other);
+        }
+    }
+
+    /// Move the focus through the visible windows, wrapping around.
+    fn move_focus(&mut self, forward: bool) {
+        let len = self.visible.len();
+        if len == 0 {
+            return;
+        }
+        let position = match self.focused_position() {
+            Some(position) if forward => (position + 1) % len,
+            Some(position) => (position + len - 1) % len,
+            None => 0,
+        };
+        self.focused_index = self.visible[position];
+    }
+
+    fn in_scope(&self, index: usize) -> bool {
+        match self.scope {
+            PickerScope::AllMonitors => true,
+            PickerScope::CurrentMonitor => index < self.current_monitor_count,
+            PickerScope::CurrentApp => self
+                .previously_focused_window
+                .is_some_and(|(pid, _)| self.windows[index].pid == pid),
+        }
+    }
+
+    /// Filter the windows by scope and query, ranking them by match score when the query has
+    /// text to match. Ranking focuses the best match. In palette mode the palette entries are
+    /// filtered instead.
+    fn rebuild_search_matches(&mut self) {
+        let mut matched: Vec<(usize, FieldsMatch)> = match self.mode {
+            PickerMode::Palette => {
+                self.ranked = !self.search_query.trim().is_empty();
+                self.palette
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, entry)| {
+                        entry
+                            .matches(&self.search_query)
+                            .map(|matched| (index, matched))
+                    })
+                    .collect()
+            }
+            PickerMode::Windows | PickerMode::Launcher => {
+                let filters = parse_query(&self.search_query);
+                self.ranked = filters.iter().any(QueryFilter::scores);
+                let windows = (0..self.windows.len())
+                    .filter(|&index| self.in_scope(index))
+                    .filter_map(|index| {
+                        match_window(&filters, &self.windows[index]).map(|matched| (index, matched))
+                    });
+                let apps = self.apps.iter().enumerate().filter_map(|(index, app)| {
+                    match_window(&filters, &app.as_window())
+                        .map(|matched| (self.windows.len() + index, matched))
+                });
+                if self.mode == PickerMode::Launcher {
+                    windows.chain(apps).collect()
+                } else {
+                    windows.collect()
+                }
+            }
+        };
+
+        if self.ranked {
+            // Stable, so equal scores keep windows of the current monitor first
+            matched.sort_by_key(|(_, matched)| std::cmp::Reverse(matched.score));
+        }
+
+        self.visible = matched.iter().map(|(index, _)| *index).collect();
+        self.matches = matched.into_iter().collect();
+        if (self.ranked || self.focused_position().is_none())
+            && let Some(&first) = self.visible.first()
+        {
+            self.focused_index = first;
+        }
+    }
+
+    /// Filter again after windows changed, keeping `focused` (a window index) focused if it
+    /// is still shown, or else the window now at `position`.
+    fn refilter(&mut self, position: usize, focused: Option<usize>) {
+        self.rebuild_search_matches();
+        match focused {
+            Some(index) if self.visible.contains(&index) => self.focused_index = index,
+            _ => {
+                if let Some(&index) = self.visible.get(position).or(self.visible.last()) {
+                    self.focused_index = index;
+                }
+            }
+        }
+    }
+
+    /// Run `action` on the focused window, updating the list to what it will look like.
+    fn run_action(&mut self, action: PickerAction) -> Vec<PickerEffect> {
+        let Some(position) = self.focused_position() else {
+            return Vec::new();
+        };
+        let index = self.focused_index;
+        let target = self.windows[index].identity();
+        let action = match action {
+            PickerAction::Close => Action::Close,
+            PickerAction::Minimize => Action::Minimize,
+            PickerAction::MoveHere => match self.current_screen {
+                Some(screen) => Action::MoveToDisplay(DisplayTarget::Index(screen)),
+                None => return Vec::new(),
+            },
+            PickerAction::Place(name) => Action::Place(name),
+        };
+
+        let focused = match action {
+            Action::Close => {
+                self.remove_window(index);
+                None
+            }
+            Action::Minimize => {
+                self.windows[index].minimized = true;
+                Some(index)
+            }
+            Action::MoveToDisplay(_) => {
+                self.windows[index].screen = self.current_screen;
+                self.windows[index].minimized = false;
+                Some(index)
+            }
+            _ => Some(index),
+        };
+        self.refilter(position, focused);
+
+        let mut effects = vec![PickerEffect::Run(action, target)];
+        effects.extend(self.preview());
+        effects
+    }
+
+    fn remove_window(&mut self, index: usize) {
+        let removed = self.windows.remove(index).identity();
+        if index < self.current_monitor_count {
+            self.current_monitor_count -= 1;
+        }
+        self.selected_indices.retain(|&i| i != index);
+        for i in &mut self.selected_indices {
+            if *i > index {
+                *i -= 1;
+            }
+        }
+        if self.previously_focused_window == Some(removed) {
+            self.previously_focused_window = None;
+        }
+    }
+
+    /// Save the focused window in `slot`, taking the slot from any other window.
+    fn assign_slot(&mut self, slot: char) -> Vec<PickerEffect> {
+        let Some(position) = self.focused_position() else {
+            return Vec::new();
+        };
+        for window in &mut self.windows {
+            window.slots.retain(|s| *s != slot);
+        }
+        let index = self.focused_index;
+        let window = &mut self.windows[index];
+        window.slots.push(slot);
+        window.slots.sort_unstable();
+        let target = window.identity();
+        self.refilter(position, Some(index));
+        vec![PickerEffect::AssignSlot(slot, target)]
+    }
+
+    /// Raise the focused window while nothing is selected, once per window.
+    fn preview(&mut self) -> Option<PickerEffect> {
+        if self.mode == PickerMode::Palette {
+            return None;
+        }
+        if !self.selected_indices.is_empty() {
+            self.last_previewed_window = None;
+            return None;
+        }
+        let focused = self
+            .focused_position()
+            .and_then(|_| self.windows.get(self.focused_index))
+            .map(PickerWindow::identity)?;
+        if Some(focused) == self.last_previewed_window {
+            return None;
+        }
+        self.last_previewed_window = Some(focused);
+        Some(PickerEffect::Preview(focused))
+    }
+
+    fn confirm(&mut self) -> Vec<PickerEffect> {
+        // Nothing to tile when the search hides every window
+        let windows_to_tile: Vec<WindowIdentity> = self
+            .tile_targets()
+            .into_iter()
+            .filter_map(|i| self.windows.get(i))
+            .map(PickerWindow::identity)
+            .collect();
+
+        let mut effects = vec![PickerEffect::Close];
+        if windows_to_tile.is_empty() {
+            effects.extend(self.previously_focused_window.map(PickerEffect::Focus));
+            return effects;
+        }
+
+        effects.push(PickerEffect::Tile {
+            windows: windows_to_tile.clone(),
+            layout: self.layout,
+            screen: self.target_screen,
+        });
+        effects.extend(windows_to_tile.iter().copied().map(PickerEffect::Focus));
+        let target = self
+            .previously_focused_window
+            .filter(|focused| windows_to_tile.contains(focused))
+            .or_else(|| windows_to_tile.first().copied());
+        effects.extend(target.map(PickerEffect::Focus));
+        effects
+    }
+
+    fn cancel(&mut self) -> Vec<PickerEffect> {
+        let mut effects = vec![PickerEffect::Close];
+        effects.extend(self.previously_focused_window.map(PickerEffect::Focus));
+        effects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(window_id: u32, app_name: &str, title: &str) -> PickerWindow {
+        PickerWindow {
+            pid: window_id as i32 * 10,
+            window_id,
+            app_name: app_name.to_string(),
+            title: title.to_string(),
+            app_icon_path: None,
+            screen: Some(1),
+            minimized: false,
+            slots: Vec::new(),
+        }
+    }
+
+    /// Two windows on the current monitor and one elsewhere; window 2 was focused
+    fn model(preselect: bool) -> PickerModel {
+        PickerModel::new(
+            vec![
+                window(1, "Safari", "GitHub"),
+                window(2, "Terminal", "zsh"),
+                window(3, "Slack", "general"),
+            ],
+            2,
+            Some((20, 2)),
+            preselect,
+        )
+    }
+
+    /// Start a new search and type `query`
+    fn type_query(model: &mut PickerModel, query: &str) -> Vec<PickerEffect> {
+        if model.search_mode {
+            model.handle_input(PickerInput::Confirm);
+        }
+        model.handle_input(PickerInput::SearchChar('/'));
+        let mut effects = Vec::new();
+        for ch in query.chars() {
+            effects = model.handle_input(PickerInput::SearchChar(ch));
+        }
+        effects
+    }
+
+    #[test]
+    fn groups_rows_by_monitor() {
+        let model = model(true);
+        assert_eq!(model.focused_index, 1);
+        assert_eq!(model.selected_indices, vec![1]);
+        assert_eq!(model.secondary_group_start(), Some(2));
+        assert_eq!(model.row_count(), 4);
+        assert_eq!(model.window_at_row(1), Some(1));
+        assert_eq!(model.window_at_row(2), None);
+        assert_eq!(model.window_at_row(3), Some(2));
+        assert_eq!(model.focused_row(), Some(1));
+    }
+
+    #[test]
+    fn navigation_wraps_and_previews_without_selection() {
+        let mut model = model(false);
+        assert_eq!(
+            model.handle_input(PickerInput::SelectDown),
+            vec![PickerEffect::Preview((30, 3))]
+        );
+        assert_eq!(
+            model.handle_input(PickerInput::SearchChar('j')),
+            vec![PickerEffect::Preview((10, 1))]
+        );
+        model.handle_input(PickerInput::ToggleSelect);
+        assert_eq!(model.selected_indices, vec![0]);
+        // With a selection, moving doesn't raise windows
+        assert!(model.handle_input(PickerInput::SelectUp).is_empty());
+        assert_eq!(model.focused_index, 2);
+    }
+
+    #[test]
+    fn search_filters_ranks_and_focuses_the_best_match() {
+        let mut model = model(false);
+        let effects = type_query(&mut model, "sl");
+        assert_eq!(model.visible, vec![2]);
+        assert_eq!(model.focused_index, 2);
+        assert_eq!(effects, vec![PickerEffect::Preview((30, 3))]);
+        assert_eq!(model.secondary_group_start(), None);
+        assert_eq!(model.matches[&2].positions, vec![vec![0, 1], vec![]]);
+
+        // Leaving search mode keeps the filter, a new search starts over
+        assert!(model.handle_input(PickerInput::Confirm).is_empty());
+        assert!(!model.search_mode);
+        assert_eq!(model.visible, vec![2]);
+        type_query(&mut model, "zzz");
+        assert!(model.visible.is_empty());
+        model.handle_input(PickerInput::Cancel);
+        // Confirming with nothing visible only restores the focus
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm),
+            vec![PickerEffect::Close, PickerEffect::Focus((20, 2))]
+        );
+    }
+
+    #[test]
+    fn confirm_tiles_selection_and_keeps_focus() {
+        let mut model = model(true);
+        model.handle_input(PickerInput::Click(0));
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm),
+            vec![
+                PickerEffect::Close,
+                PickerEffect::Tile {
+                    windows: vec![(20, 2), (10, 1)],
+                    layout: TileLayout::Columns,
+                    screen: None,
+                },
+                PickerEffect::Focus((20, 2)),
+                PickerEffect::Focus((10, 1)),
+                PickerEffect::Focus((20, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn cancel_restores_focus() {
+        let mut model = model(true);
+        assert_eq!(
+            model.handle_input(PickerInput::SearchChar('q')),
+            vec![PickerEffect::Close, PickerEffect::Focus((20, 2))]
+        );
+    }
+
+    #[test]
+    fn parses_query_terms() {
+        let filter = |term, negated| QueryFilter { term, negated };
+        assert_eq!(
+            parse_query("app:slack !title:draft screen:2 slot:A !minimized app: foo"),
+            vec![
+                filter(QueryTerm::App("slack".into()), false),
+                filter(QueryTerm::Title("draft".into()), true),
+                filter(QueryTerm::Screen(2), false),
+                filter(QueryTerm::Slot(Some('A')), false),
+                filter(QueryTerm::Minimized, true),
+                filter(QueryTerm::Text("foo".into()), false),
+            ]
+        );
+        assert_eq!(
+            parse_query("screen:left"),
+            vec![filter(QueryTerm::Text("screen:left".into()), false)]
+        );
+    }
+
+    #[test]
+    fn filters_by_properties() {
+        let mut windows = vec![
+            window(1, "Safari", "Review PR"),
+            window(2, "Slack", "general"),
+            window(3, "Safari", "Docs"),
+        ];
+        windows[1].slots = vec!['s'];
+        windows[2].screen = Some(2);
+        windows[2].minimized = true;
+        let mut model = PickerModel::new(windows, 2, Some((10, 1)), false);
+
+        type_query(&mut model, "app:saf");
+        assert_eq!(model.visible, vec![0, 2]);
+        assert_eq!(model.matches[&0].positions, vec![vec![0, 1, 2], vec![]]);
+
+        type_query(&mut model, "app:saf !minimized");
+        assert_eq!(model.visible, vec![0]);
+
+        type_query(&mut model, "screen:2");
+        assert_eq!(model.visible, vec![2]);
+        assert!(!model.ranked);
+
+        type_query(&mut model, "slot:S");
+        assert_eq!(model.visible, vec![1]);
+
+        type_query(&mut model, "title:pr");
+        assert_eq!(model.visible, vec![0]);
+    }
+
+    #[test]
+    fn scope_cycles_and_keeps_grouping() {
+        let mut windows = vec![
+            window(1, "Safari", "GitHub"),
+            window(2, "Terminal", "zsh"),
+            window(3, "Safari", "Docs"),
+        ];
+        windows[2].pid = 10;
+        let mut model = PickerModel::new(windows, 2, Some((10, 1)), false);
+
+        model.handle_input(PickerInput::SearchChar('s'));
+        assert_eq!(model.scope, PickerScope::CurrentMonitor);
+        assert_eq!(model.visible, vec![0, 1]);
+        assert_eq!(model.row_count(), 2);
+
+        model.handle_input(PickerInput::SearchChar('s'));
+        assert_eq!(model.scope, PickerScope::CurrentApp);
+        assert_eq!(model.visible, vec![0, 2]);
+        // One window on each side of the separator
+        assert_eq!(model.window_at_row(1), None);
+        assert_eq!(model.window_at_row(2), Some(2));
+
+        model.handle_input(PickerInput::SearchChar('s'));
+        assert_eq!(model.scope, PickerScope::AllMonitors);
+        assert_eq!(model.visible, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn window_actions_run_on_the_focused_row() {
+        let mut model = model(false);
+        model.keybinds = vec![
+            ('h', PickerAction::MoveHere),
+            ('m', PickerAction::Minimize),
+            ('x', PickerAction::Close),
+        ];
+        model.current_screen = Some(1);
+        model.handle_input(PickerInput::SelectUp);
+        assert_eq!(model.focused_index, 0);
+
+        assert_eq!(
+            model.handle_input(PickerInput::SearchChar('m')),
+            vec![PickerEffect::Run(Action::Minimize, (10, 1))]
+        );
+        assert!(model.windows[0].minimized);
+
+        model.handle_input(PickerInput::SearchChar('j'));
+        model.handle_input(PickerInput::SearchChar('j'));
+        assert_eq!(
+            model.handle_input(PickerInput::SearchChar('h')),
+            vec![PickerEffect::Run(
+                Action::MoveToDisplay(DisplayTarget::Index(1)),
+                (30, 3)
+            )]
+        );
+
+        // Closing drops the row and focuses the one that took its place
+        model.handle_input(PickerInput::SearchChar('k'));
+        assert_eq!(
+            model.handle_input(PickerInput::SearchChar('x')),
+            vec![
+                PickerEffect::Run(Action::Close, (20, 2)),
+                PickerEffect::Preview((30, 3)),
+            ]
+        );
+        assert_eq!(model.windows.len(), 2);
+        assert_eq!(model.current_monitor_count, 1);
+        assert_eq!(model.focused_index, 1);
+        assert_eq!(model.previously_focused_window, None);
+    }
+
+    #[test]
+    fn assigns_slots_with_quote_then_letter() {
+        let mut model = model(false);
+        model.windows[2].slots = vec!['a'];
+        model.handle_input(PickerInput::SearchChar('\''));
+        assert!(model.assigning_slot);
+        assert_eq!(model.hint(), "a-z assign slot • esc cancel");
+        assert_eq!(
+            model.handle_input(PickerInput::SearchChar('A')),
+            vec![PickerEffect::AssignSlot('a', (20, 2))]
+        );
+        assert_eq!(model.windows[1].slots, vec!['a']);
+        assert!(model.windows[2].slots.is_empty());
+
+        // Anything but a letter cancels
+        model.handle_input(PickerInput::SearchChar('\''));
+        assert!(model.handle_input(PickerInput::SearchChar('1')).is_empty());
+        assert!(!model.assigning_slot);
+    }
+
+    #[test]
+    fn hint_lists_the_keybinds() {
+        let mut model = model(false);
+        model.keybinds = vec![
+            ('[', PickerAction::Place("left".into())),
+            ('x', PickerAction::Close),
+        ];
+        assert_eq!(
+            model.hint(),
+            "j/k navigate • space select • J/K reorder • / search • n/N next/prev • s scope • : commands • \
+             tab layout • ⇧tab display • ' slot • [ place left • x close • enter tile • esc cancel"
+        );
+    }
+
+    #[test]
+    fn layout_and_display_cycle_into_the_tile() {
+        let mut model = model(true);
+        model.screen_count = 2;
+        model.target_screen = Some(1);
+        model.handle_input(PickerInput::Click(0));
+        model.handle_input(PickerInput::NextLayout);
+        model.handle_input(PickerInput::NextLayout);
+        assert_eq!(model.layout, TileLayout::Grid);
+        model.handle_input(PickerInput::NextScreen);
+        assert_eq!(model.target_screen, Some(2));
+
+        let area = Rect::new(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(
+            model.tile_preview(area),
+            vec![
+                (1, Rect::new(0.0, 0.0, 50.0, 100.0)),
+                (0, Rect::new(50.0, 0.0, 50.0, 100.0)),
+            ]
+        );
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm)[1],
+            PickerEffect::Tile {
+                windows: vec![(20, 2), (10, 1)],
+                layout: TileLayout::Grid,
+                screen: Some(2),
+            }
+        );
+
+        model.handle_input(PickerInput::NextScreen);
+        assert_eq!(model.target_screen, Some(1));
+    }
+
+    #[test]
+    fn palette_filters_and_runs_commands() {
+        let mut model = model(false);
+        model.palette =
+            crate::palette::palette_entries(&["left".into()], &[], &[], &[('a', "Safari".into())]);
+        model.handle_input(PickerInput::SearchChar(':'));
+        assert_eq!(model.mode, PickerMode::Palette);
+        assert_eq!(model.visible.len(), model.palette.len());
+        assert_eq!(model.focused_index, 0);
+        assert_eq!(model.secondary_group_start(), None);
+
+        // Keys the window list uses are typed into the query
+        for ch in "plc lef".chars() {
+            assert!(model.handle_input(PickerInput::SearchChar(ch)).is_empty());
+        }
+        assert_eq!(model.palette[model.focused_index].title, "Place left");
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm),
+            vec![
+                PickerEffect::Close,
+                PickerEffect::Focus((20, 2)),
+                PickerEffect::Command(PaletteCommand::Action(Action::Place("left".into()))),
+            ]
+        );
+
+        model.open_palette();
+        for ch in "slot".chars() {
+            model.handle_input(PickerInput::SearchChar(ch));
+        }
+        // Focusing a slot doesn't restore the previous focus first
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm),
+            vec![
+                PickerEffect::Close,
+                PickerEffect::Command(PaletteCommand::FocusSlot('a')),
+            ]
+        );
+    }
+
+    #[test]
+    fn launcher_switches_to_windows_or_launches_apps() {
+        let mut model = model(false);
+        let app = |name: &str| PickerApp {
+            name: name.to_string(),
+            launch: format!("/Applications/{}.app", name),
+            icon_path: None,
+        };
+        model.open_launcher(vec![app("Safari"), app("Xcode"), app("slack")]);
+        // Running apps are listed by their windows
+        assert_eq!(model.apps, vec![app("Xcode")]);
+        assert_eq!(model.visible, vec![0, 1, 2, 3]);
+        assert_eq!(model.app_at(3), Some(&app("Xcode")));
+        assert_eq!(model.secondary_group_start(), None);
+
+        for ch in "xcod".chars() {
+            model.handle_input(PickerInput::SearchChar(ch));
+        }
+        assert_eq!(model.visible, vec![3]);
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm),
+            vec![
+                PickerEffect::Close,
+                PickerEffect::Launch("/Applications/Xcode.app".into()),
+            ]
+        );
+
+        model.open_launcher(Vec::new());
+        for ch in "term".chars() {
+            model.handle_input(PickerInput::SearchChar(ch));
+        }
+        assert_eq!(
+            model.handle_input(PickerInput::Confirm),
+            vec![PickerEffect::Close, PickerEffect::Focus((20, 2))]
+        );
+    }
+
+    #[test]
+    fn reorders_the_selection() {
+        let mut model = model(true);
+        model.handle_input(PickerInput::Click(0));
+        model.handle_input(PickerInput::Click(2));
+        assert_eq!(model.selected_indices, vec![1, 0, 2]);
+        assert_eq!(model.selection_ordinal(2), Some(3));
+
+        model.handle_input(PickerInput::SearchChar('K'));
+        model.handle_input(PickerInput::SearchChar('K'));
+        // Already first
+        model.handle_input(PickerInput::SearchChar('K'));
+        assert_eq!(model.selected_indices, vec![2, 1, 0]);
+        assert_eq!(model.selection_ordinal(2), Some(1));
+
+        model.handle_input(PickerInput::SearchChar('J'));
+        assert_eq!(model.selected_indices, vec![1, 2, 0]);
+        // Unselected windows have no place to move
+        model.handle_input(PickerInput::ToggleSelect);
+        model.handle_input(PickerInput::SearchChar('J'));
+        assert_eq!(model.selected_indices, vec![1, 0]);
+        assert_eq!(model.selection_ordinal(2), None);
+    }
+}
@@ -0,0 +1,1237 @@
+//! Placement expressions and frame resolution
+//!
+//! Placement fields are small expressions evaluated against the usable screen area:
+//!
+//! - lengths: `50%` (of the screen axis) or `120px`
+//! - arithmetic: `+`, `-`, `*`, `/` and parentheses, optionally wrapped in `calc(...)`
+//! - clamps: `min(a, b, ...)`, `max(a, b, ...)` and `clamp(lo, value, hi)`
+//! - `center` (positions only): the offset that centers the window on the axis
+//!
+//! Lengths can only be added to lengths, and only multiplied or divided by plain numbers.
+//!
+//! Alternatively a placement can name a region of a grid laid over the usable area
+//! (`grid = "6x4"`, `cell = "0,0"`, `span = "4x4"`), see [`GridRegion`].
+//!
+//! A named placement may also list several variants; [`PlacementCycles`] tracks which one
+//! to apply when the same placement is repeated on a window.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::config::Placement;
+use crate::error::{PixieError, Result};
+use crate::geometry::Rect;
+
+/// Which kind of placement field an expression is parsed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// `top`, `left`, `right`, `bottom`: may use `center`.
+    Position,
+    /// `width`, `height`.
+    Size,
+}
+
+/// A parse error pointing at a 1-based character column of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Px,
+    Percent,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// A parsed placement expression.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Length(f64, Unit),
+    Number(f64),
+    Center,
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Min(Vec<Expr>),
+    Max(Vec<Expr>),
+    Clamp(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// Values an expression is evaluated against, in pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct EvalContext {
+    /// Size of the screen axis that percentages refer to.
+    pub axis: f64,
+    /// Size of the window along the same axis, used by `center`.
+    pub window: f64,
+}
+
+impl Expr {
+    pub fn eval(&self, ctx: EvalContext) -> f64 {
+        match self {
+            Expr::Length(value, Unit::Px) => *value,
+            Expr::Length(value, Unit::Percent) => value / 100.0 * ctx.axis,
+            Expr::Number(value) => *value,
+            Expr::Center => (ctx.axis - ctx.window) / 2.0,
+            Expr::Neg(inner) => -inner.eval(ctx),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(ctx), rhs.eval(ctx));
+                match op {
+                    BinOp::Add => lhs + rhs,
+                    BinOp::Sub => lhs - rhs,
+                    BinOp::Mul => lhs * rhs,
+                    BinOp::Div => lhs / rhs,
+                }
+            }
+            Expr::Min(args) => args
+                .iter()
+                .map(|arg| arg.eval(ctx))
+                .fold(f64::INFINITY, f64::min),
+            Expr::Max(args) => args
+                .iter()
+                .map(|arg| arg.eval(ctx))
+                .fold(f64::NEG_INFINITY, f64::max),
+            Expr::Clamp(lo, value, hi) => {
+                let (lo, hi) = (lo.eval(ctx), hi.eval(ctx));
+                value.eval(ctx).max(lo).min(hi.max(lo))
+            }
+        }
+    }
+}
+
+/// Parse a placement field expression.
+pub fn parse_expr(input: &str, kind: FieldKind) -> std::result::Result<Expr, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        kind,
+        end_column: input.chars().count() + 1,
+    };
+
+    let (expr, value_kind) = parser.expr()?;
+    if let Some(token) = parser.peek() {
+        return Err(ParseError {
+            column: token.column,
+            message: format!("unexpected {}", token.kind.describe()),
+        });
+    }
+
+    if value_kind == ValueKind::Number {
+        let value = expr.eval(EvalContext {
+            axis: 0.0,
+            window: 0.0,
+        });
+        if value != 0.0 {
+            return Err(ParseError {
+                column: 1,
+                message: "missing unit, use `%` or `px`".to_string(),
+            });
+        }
+    }
+
+    Ok(expr)
+}
+
+/// Parse an aspect ratio such as `16:9`, `16/9` or `1.5` into width / height.
+pub fn parse_aspect_ratio(input: &str) -> std::result::Result<f64, ParseError> {
+    let trimmed = input.trim();
+    let offset = input.len() - input.trim_start().len();
+    let parse_part = |part: &str, start: usize| -> std::result::Result<f64, ParseError> {
+        let column = input[..offset + start].chars().count() + 1;
+        match part.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+            _ => Err(ParseError {
+                column,
+                message: format!("expected a positive number, found `{}`", part.trim()),
+            }),
+        }
+    };
+
+    match trimmed.find([':', '/']) {
+        Some(idx) => {
+            let width = parse_part(&trimmed[..idx], 0)?;
+            let height = parse_part(&trimmed[idx + 1..], idx + 1)?;
+            Ok(width / height)
+        }
+        None => parse_part(trimmed, 0),
+    }
+}
+
+/// A region of a grid laid over the usable area: `columns` x `rows` cells, starting at the
+/// zero-based `column`/`row` cell and covering `column_span` x `row_span` cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRegion {
+    pub columns: u32,
+    pub rows: u32,
+    pub column: u32,
+    pub row: u32,
+    pub column_span: u32,
+    pub row_span: u32,
+}
+
+impl GridRegion {
+    /// Parse the `grid`, `cell` (default `"0,0"`) and `span` (default `"1x1"`) fields.
+    pub fn parse(grid: &str, cell: Option<&str>, span: Option<&str>) -> Result<Self> {
+        let (columns, rows) =
+            parse_pair(grid, &['x', 'X', '×'], 1).map_err(|e| field_error("grid", grid, &e))?;
+        let (column, row) = cell
+            .map(|cell| parse_pair(cell, &[','], 0).map_err(|e| field_error("cell", cell, &e)))
+            .transpose()?
+            .unwrap_or((0, 0));
+        let (column_span, row_span) = span
+            .map(|span| {
+                parse_pair(span, &['x', 'X', '×'], 1).map_err(|e| field_error("span", span, &e))
+            })
+            .transpose()?
+            .unwrap_or((1, 1));
+
+        let column_end = column.checked_add(column_span);
+        let row_end = row.checked_add(row_span);
+        if column_end.is_none_or(|end| end > columns) || row_end.is_none_or(|end| end > rows) {
+            return Err(PixieError::Config(format!(
+                "Invalid placement: cell {},{} with span {}x{} does not fit in a {}x{} grid",
+                column, row, column_span, row_span, columns, rows
+            )));
+        }
+
+        Ok(Self {
+            columns,
+            rows,
+            column,
+            row,
+            column_span,
+            row_span,
+        })
+    }
+
+    /// The region's frame within `area`. Adjacent regions share edges exactly.
+    pub fn rect(&self, area: Rect) -> Rect {
+        let edge = |origin: f64, length: f64, index: u32, count: u32| {
+            origin + length * index as f64 / count as f64
+        };
+        let left = edge(area.x, area.width, self.column, self.columns);
+        let right = edge(
+            area.x,
+            area.width,
+            self.column + self.column_span,
+            self.columns,
+        );
+        let top = edge(area.y, area.height, self.row, self.rows);
+        let bottom = edge(area.y, area.height, self.row + self.row_span, self.rows);
+
+        Rect::new(left, top, right - left, bottom - top)
+    }
+}
+
+/// Parse two non-negative integers separated by one of `separators`, each at least `min`.
+fn parse_pair(
+    input: &str,
+    separators: &[char],
+    min: u32,
+) -> std::result::Result<(u32, u32), ParseError> {
+    let parse_part = |part: &str, start: usize| -> std::result::Result<u32, ParseError> {
+        let column = input[..start].chars().count() + 1 + (part.len() - part.trim_start().len());
+        match part.trim().parse::<u32>() {
+            Ok(value) if value >= min => Ok(value),
+            _ => Err(ParseError {
+                column,
+                message: if min > 0 {
+                    format!("expected a positive integer, found `{}`", part.trim())
+                } else {
+                    format!("expected an integer, found `{}`", part.trim())
+                },
+            }),
+        }
+    };
+
+    match input.find(separators) {
+        Some(idx) => {
+            let separator_len = input[idx..].chars().next().map_or(1, char::len_utf8);
+            let first = parse_part(&input[..idx], 0)?;
+            let second = parse_part(&input[idx + separator_len..], idx + separator_len)?;
+            Ok((first, second))
+        }
+        None => Err(ParseError {
+            column: input.chars().count() + 1,
+            message: format!("expected `{}` between two numbers", separators[0]),
+        }),
+    }
+}
+
+/// Resolve a placement into a window frame.
+///
+/// `area` is the usable screen area and `current` the window's current frame; fields the
+/// placement leaves unset keep their current value.
+pub fn resolve(placement: &Placement, area: Rect, current: Rect) -> Result<Rect> {
+    if let Some(grid) = &placement.grid {
+        let combined = [
+            ("top", &placement.top),
+            ("left", &placement.left),
+            ("right", &placement.right),
+            ("bottom", &placement.bottom),
+            ("width", &placement.width),
+            ("height", &placement.height),
+            ("aspect_ratio", &placement.aspect_ratio),
+        ]
+        .into_iter()
+        .find(|(_, value)| value.is_some());
+        if let Some((name, _)) = combined {
+            return Err(PixieError::Config(format!(
+                "Invalid placement: grid cannot be combined with {}",
+                name
+            )));
+        }
+
+        let region = GridRegion::parse(grid, placement.cell.as_deref(), placement.span.as_deref())?;
+        return Ok(region.rect(area));
+    }
+    if placement.cell.is_some() || placement.span.is_some() {
+        return Err(PixieError::Config(
+            "Invalid placement: cell and span require grid".to_string(),
+        ));
+    }
+
+    let left = parse_field("left", &placement.left, FieldKind::Position)?;
+    let right = parse_field("right", &placement.right, FieldKind::Position)?;
+    let top = parse_field("top", &placement.top, FieldKind::Position)?;
+    let bottom = parse_field("bottom", &placement.bottom, FieldKind::Position)?;
+    let width = parse_field("width", &placement.width, FieldKind::Size)?;
+    let height = parse_field("height", &placement.height, FieldKind::Size)?;
+    let aspect_ratio = placement
+        .aspect_ratio
+        .as_deref()
+        .map(|value| parse_aspect_ratio(value).map_err(|e| field_error("aspect_ratio", value, &e)))
+        .transpose()?;
+
+    let x_ctx = EvalContext {
+        axis: area.width,
+        window: 0.0,
+    };
+    let y_ctx = EvalContext {
+        axis: area.height,
+        window: 0.0,
+    };
+
+    let stretched = |start: &Option<Expr>, end: &Option<Expr>, ctx: EvalContext| match (start, end)
+    {
+        (Some(start), Some(end)) if *start != Expr::Center && *end != Expr::Center => {
+            Some(ctx.axis - start.eval(ctx) - end.eval(ctx))
+        }
+        _ => None,
+    };
+
+    let explicit_width = match &width {
+        Some(width) => Some(width.eval(x_ctx)),
+        None => stretched(&left, &right, x_ctx),
+    };
+    let explicit_height = match &height {
+        Some(height) => Some(height.eval(y_ctx)),
+        None => stretched(&top, &bottom, y_ctx),
+    };
+
+    let (new_width, new_height) = match (explicit_width, explicit_height, aspect_ratio) {
+        (Some(w), Some(h), Some(ratio)) => {
+            if w / h > ratio {
+                (h * ratio, h)
+            } else {
+                (w, w / ratio)
+            }
+        }
+        (Some(w), None, Some(ratio)) => (w, w / ratio),
+        (None, Some(h), Some(ratio)) => (h * ratio, h),
+        (None, None, Some(ratio)) => (current.width, current.width / ratio),
+        (w, h, None) => (w.unwrap_or(current.width), h.unwrap_or(current.height)),
+    };
+    if !(new_width > 0.0 && new_height > 0.0) {
+        return Err(PixieError::Config(format!(
+            "Invalid placement: resolves to a {}x{} frame, width and height must be positive",
+            new_width, new_height
+        )));
+    }
+
+    let new_x = resolve_offset(&left, &right, area.x, current.x, area.width, new_width);
+    let new_y = resolve_offset(&top, &bottom, area.y, current.y, area.height, new_height);
+
+    Ok(Rect::new(new_x, new_y, new_width, new_height))
+}
+
+fn resolve_offset(
+    start: &Option<Expr>,
+    end: &Option<Expr>,
+    area_start: f64,
+    current: f64,
+    axis: f64,
+    size: f64,
+) -> f64 {
+    let ctx = EvalContext { axis, window: size };
+    match (start, end) {
+        (Some(start), _) => area_start + start.eval(ctx),
+        (None, Some(end)) => area_start + axis - size - end.eval(ctx),
+        (None, None) => current,
+    }
+}
+
+fn parse_field(name: &str, value: &Option<String>, kind: FieldKind) -> Result<Option<Expr>> {
+    value
+        .as_deref()
+        .map(|value| parse_expr(value, kind).map_err(|e| field_error(name, value, &e)))
+        .transpose()
+}
+
+fn field_error(name: &str, value: &str, error: &ParseError) -> PixieError {
+    PixieError::Config(format!(
+        "Invalid placement {} \"{}\": {}",
+        name, value, error
+    ))
+}
+
+/// Remembers the last cycling placement applied to each window.
+#[derive(Debug, Default)]
+pub struct PlacementCycles {
+    last: HashMap<(i32, u32), LastPlacement>,
+}
+
+#[derive(Debug)]
+struct LastPlacement {
+    name: String,
+    index: usize,
+    applied_at: Instant,
+}
+
+impl PlacementCycles {
+    /// Pick which variant of placement `name` to apply to `window`.
+    ///
+    /// Repeating the same placement on the same window within `timeout` advances to the next
+    /// variant (wrapping around); anything else starts again from the first one.
+    pub fn next_variant(
+        &mut self,
+        window: (i32, u32),
+        name: &str,
+        variant_count: usize,
+        now: Instant,
+        timeout: Duration,
+    ) -> usize {
+        let is_recent =
+            |last: &LastPlacement| now.saturating_duration_since(last.applied_at) <= timeout;
+        let index = match self.last.get(&window) {
+            Some(last)
+                if variant_count > 1
+                    && !timeout.is_zero()
+                    && last.name == name
+                    && is_recent(last) =>
+            {
+                (last.index + 1) % variant_count
+            }
+            _ => 0,
+        };
+
+        self.last.retain(|_, last| is_recent(last));
+        self.last.insert(
+            window,
+            LastPlacement {
+                name: name.to_string(),
+                index,
+                applied_at: now,
+            },
+        );
+        index
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Unit(Unit),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Comma,
+    LParen,
+    RParen,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Number(value) => format!("number `{}`", value),
+            TokenKind::Unit(Unit::Px) => "unit `px`".to_string(),
+            TokenKind::Unit(Unit::Percent) => "unit `%`".to_string(),
+            TokenKind::Ident(name) => format!("`{}`", name),
+            TokenKind::Plus => "`+`".to_string(),
+            TokenKind::Minus => "`-`".to_string(),
+            TokenKind::Star => "`*`".to_string(),
+            TokenKind::Slash => "`/`".to_string(),
+            TokenKind::Comma => "`,`".to_string(),
+            TokenKind::LParen => "`(`".to_string(),
+            TokenKind::RParen => "`)`".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(input: &str) -> std::result::Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text.parse::<f64>().map_err(|_| ParseError {
+                column,
+                message: format!("invalid number `{}`", text),
+            })?;
+            tokens.push(Token {
+                kind: TokenKind::Number(value),
+                column,
+            });
+
+            if i < chars.len() && chars[i] == '%' {
+                tokens.push(Token {
+                    kind: TokenKind::Unit(Unit::Percent),
+                    column: i + 1,
+                });
+                i += 1;
+            } else if i < chars.len() && chars[i].is_ascii_alphabetic() {
+                let unit_start = i;
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                let unit: String = chars[unit_start..i].iter().collect();
+                if unit.eq_ignore_ascii_case("px") {
+                    tokens.push(Token {
+                        kind: TokenKind::Unit(Unit::Px),
+                        column: unit_start + 1,
+                    });
+                } else {
+                    return Err(ParseError {
+                        column: unit_start + 1,
+                        message: format!("unknown unit `{}`, use `%` or `px`", unit),
+                    });
+                }
+            }
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            tokens.push(Token {
+                kind: TokenKind::Ident(ident.to_ascii_lowercase()),
+                column,
+            });
+            continue;
+        }
+
+        let kind = match c {
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            ',' => TokenKind::Comma,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '%' => {
+                return Err(ParseError {
+                    column,
+                    message: "`%` must directly follow a number".to_string(),
+                });
+            }
+            _ => {
+                return Err(ParseError {
+                    column,
+                    message: format!("unexpected character `{}`", c),
+                });
+            }
+        };
+        tokens.push(Token { kind, column });
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Length,
+    Number,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    kind: FieldKind,
+    end_column: usize,
+}
+
+type Parsed = std::result::Result<(Expr, ValueKind), ParseError>;
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error_here(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError {
+                column: token.column,
+                message: format!("expected {}, found {}", expected, token.kind.describe()),
+            },
+            None => ParseError {
+                column: self.end_column,
+                message: format!("expected {}, found end of input", expected),
+            },
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> std::result::Result<(), ParseError> {
+        if self.peek().map(|token| &token.kind) == Some(&kind) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error_here(expected))
+        }
+    }
+
+    fn expr(&mut self) -> Parsed {
+        let (mut lhs, mut lhs_kind) = self.term()?;
+        loop {
+            let op = match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::Plus) => BinOp::Add,
+                Some(TokenKind::Minus) => BinOp::Sub,
+                _ => return Ok((lhs, lhs_kind)),
+            };
+            let column = self.advance().map(|token| token.column).unwrap_or_default();
+            let (rhs, rhs_kind) = self.term()?;
+            if lhs_kind != rhs_kind {
+                return Err(ParseError {
+                    column,
+                    message: "cannot add or subtract a length and a plain number".to_string(),
+                });
+            }
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+            lhs_kind = rhs_kind;
+        }
+    }
+
+    fn term(&mut self) -> Parsed {
+        let (mut lhs, mut lhs_kind) = self.unary()?;
+        loop {
+            let op = match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::Star) => BinOp::Mul,
+                Some(TokenKind::Slash) => BinOp::Div,
+                _ => return Ok((lhs, lhs_kind)),
+            };
+            let column = self.advance().map(|token| token.column).unwrap_or_default();
+            let rhs_column = self
+                .peek()
+                .map(|token| token.column)
+                .unwrap_or(self.end_column);
+            let (rhs, rhs_kind) = self.unary()?;
+
+            lhs_kind = match (op, lhs_kind, rhs_kind) {
+                (BinOp::Mul, ValueKind::Length, ValueKind::Length) => {
+                    return Err(ParseError {
+                        column,
+                        message: "cannot multiply two lengths".to_string(),
+                    });
+                }
+                (BinOp::Div, _, ValueKind::Length) => {
+                    return Err(ParseError {
+                        column,
+                        message: "can only divide by a plain number".to_string(),
+                    });
+                }
+                (BinOp::Div, _, ValueKind::Number) => {
+                    if rhs.eval(EvalContext {
+                        axis: 0.0,
+                        window: 0.0,
+                    }) == 0.0
+                    {
+                        return Err(ParseError {
+                            column: rhs_column,
+                            message: "division by zero".to_string(),
+                        });
+                    }
+                    lhs_kind
+                }
+                (_, ValueKind::Number, ValueKind::Number) => ValueKind::Number,
+                _ => ValueKind::Length,
+            };
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Parsed {
+        match self.peek().map(|token| &token.kind) {
+            Some(TokenKind::Minus) => {
+                self.pos += 1;
+                let (inner, kind) = self.unary()?;
+                Ok((Expr::Neg(Box::new(inner)), kind))
+            }
+            Some(TokenKind::Plus) => {
+                self.pos += 1;
+                self.unary()
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Parsed {
+        let Some(token) = self.advance() else {
+            return Err(self.error_here("a value"));
+        };
+
+        match token.kind {
+            TokenKind::Number(value) => match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::Unit(unit)) => {
+                    let unit = *unit;
+                    self.pos += 1;
+                    Ok((Expr::Length(value, unit), ValueKind::Length))
+                }
+                _ => Ok((Expr::Number(value), ValueKind::Number)),
+            },
+            TokenKind::LParen => {
+                let parsed = self.expr()?;
+                self.expect(TokenKind::RParen, "`)`")?;
+                Ok(parsed)
+            }
+            TokenKind::Ident(name) => {
+                if self.peek().map(|token| &token.kind) == Some(&TokenKind::LParen) {
+                    self.pos += 1;
+                    return self.function(&name, token.column);
+                }
+                match name.as_str() {
+                    "center" if self.kind == FieldKind::Position => {
+                        Ok((Expr::Center, ValueKind::Length))
+                    }
+                    "center" => Err(ParseError {
+                        column: token.column,
+                        message: "`center` is only valid for top, left, right and bottom"
+                            .to_string(),
+                    }),
+                    _ => Err(ParseError {
+                        column: token.column,
+                        message: format!("unknown keyword `{}`", name),
+                    }),
+                }
+            }
+            kind => {
+                self.pos -= 1;
+                Err(ParseError {
+                    column: token.column,
+                    message: format!("expected a value, found {}", kind.describe()),
+                })
+            }
+        }
+    }
+
+    fn function(&mut self, name: &str, column: usize) -> Parsed {
+        let mut args = Vec::new();
+        loop {
+            let arg_column = self
+                .peek()
+                .map(|token| token.column)
+                .unwrap_or(self.end_column);
+            let (arg, kind) = self.expr()?;
+            args.push((arg, kind, arg_column));
+            match self.peek().map(|token| &token.kind) {
+                Some(TokenKind::Comma) => self.pos += 1,
+                _ => break,
+            }
+        }
+        self.expect(TokenKind::RParen, "`,` or `)`")?;
+
+        let kind = args[0].1;
+        if let Some((_, _, arg_column)) = args.iter().find(|(_, arg_kind, _)| *arg_kind != kind) {
+            return Err(ParseError {
+                column: *arg_column,
+                message: format!("arguments to `{}` must all be lengths or all numbers", name),
+            });
+        }
+
+        let arity_error = |expected: &str| ParseError {
+            column,
+            message: format!("`{}` takes {}", name, expected),
+        };
+        let mut exprs: Vec<Expr> = args.into_iter().map(|(arg, _, _)| arg).collect();
+        let expr = match name {
+            "calc" if exprs.len() == 1 => exprs.remove(0),
+            "calc" => return Err(arity_error("exactly one argument")),
+            "min" => Expr::Min(exprs),
+            "max" => Expr::Max(exprs),
+            "clamp" if exprs.len() == 3 => {
+                let hi = exprs.remove(2);
+                let value = exprs.remove(1);
+                let lo = exprs.remove(0);
+                Expr::Clamp(Box::new(lo), Box::new(value), Box::new(hi))
+            }
+            "clamp" => return Err(arity_error("three arguments: min, value, max")),
+            _ => {
+                return Err(ParseError {
+                    column,
+                    message: format!("unknown function `{}`", name),
+                });
+            }
+        };
+
+        Ok((expr, kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Rect = Rect {
+        x: 0.0,
+        y: 25.0,
+        width: 1000.0,
+        height: 800.0,
+    };
+    const WINDOW: Rect = Rect {
+        x: 100.0,
+        y: 125.0,
+        width: 400.0,
+        height: 300.0,
+    };
+
+    fn eval(input: &str, kind: FieldKind, axis: f64, window: f64) -> f64 {
+        parse_expr(input, kind)
+            .unwrap_or_else(|e| panic!("failed to parse {:?}: {}", input, e))
+            .eval(EvalContext { axis, window })
+    }
+
+    fn error(input: &str, kind: FieldKind) -> ParseError {
+        parse_expr(input, kind).expect_err("expected a parse error")
+    }
+
+    #[test]
+    fn evaluates_percentages_and_pixels() {
+        assert_eq!(eval("50%", FieldKind::Size, 1000.0, 0.0), 500.0);
+        assert_eq!(eval(" 33.5% ", FieldKind::Size, 1000.0, 0.0), 335.0);
+        assert_eq!(eval("120px", FieldKind::Size, 1000.0, 0.0), 120.0);
+        assert_eq!(eval("0", FieldKind::Size, 1000.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn evaluates_calc_and_arithmetic() {
+        assert_eq!(
+            eval("calc(50% - 20px)", FieldKind::Size, 1000.0, 0.0),
+            480.0
+        );
+        assert_eq!(eval("50% - 20px", FieldKind::Size, 1000.0, 0.0), 480.0);
+        assert_eq!(eval("100% / 3 * 2", FieldKind::Size, 900.0, 0.0), 600.0);
+        assert_eq!(eval("2 * (10px + 5%)", FieldKind::Size, 200.0, 0.0), 40.0);
+        assert_eq!(eval("-10px + 30px", FieldKind::Position, 0.0, 0.0), 20.0);
+    }
+
+    #[test]
+    fn evaluates_center_against_window_size() {
+        assert_eq!(eval("center", FieldKind::Position, 1000.0, 400.0), 300.0);
+        assert_eq!(
+            eval("calc(center + 10px)", FieldKind::Position, 1000.0, 400.0),
+            310.0
+        );
+    }
+
+    #[test]
+    fn evaluates_min_max_and_clamp() {
+        assert_eq!(eval("min(50%, 800px)", FieldKind::Size, 2000.0, 0.0), 800.0);
+        assert_eq!(eval("max(50%, 800px)", FieldKind::Size, 1000.0, 0.0), 800.0);
+        assert_eq!(
+            eval("clamp(400px, 30%, 1200px)", FieldKind::Size, 1000.0, 0.0),
+            400.0
+        );
+        assert_eq!(
+            eval("clamp(400px, 30%, 1200px)", FieldKind::Size, 3000.0, 0.0),
+            900.0
+        );
+        assert_eq!(
+            eval("clamp(400px, 80%, 1200px)", FieldKind::Size, 3000.0, 0.0),
+            1200.0
+        );
+    }
+
+    #[test]
+    fn reports_column_of_unexpected_tokens() {
+        assert_eq!(
+            error("calc(50% - )", FieldKind::Size),
+            ParseError {
+                column: 12,
+                message: "expected a value, found `)`".to_string()
+            }
+        );
+        assert_eq!(error("50% 20px", FieldKind::Size).column, 5);
+        assert_eq!(
+            error("calc(50%", FieldKind::Size),
+            ParseError {
+                column: 9,
+                message: "expected `,` or `)`, found end of input".to_string()
+            }
+        );
+        assert_eq!(error("50% ^ 2", FieldKind::Size).column, 5);
+    }
+
+    #[test]
+    fn reports_unit_errors() {
+        let err = error("50", FieldKind::Size);
+        assert_eq!(err.column, 1);
+        assert!(err.message.contains("missing unit"));
+
+        let err = error("10em", FieldKind::Size);
+        assert_eq!(err.column, 3);
+        assert!(err.message.contains("unknown unit `em`"));
+
+        assert_eq!(error("50 %", FieldKind::Size).column, 4);
+    }
+
+    #[test]
+    fn reports_type_errors() {
+        assert_eq!(error("50% + 2", FieldKind::Size).column, 5);
+        assert_eq!(error("10px * 10px", FieldKind::Size).column, 6);
+        assert_eq!(error("100% / 10px", FieldKind::Size).column, 6);
+        assert_eq!(error("100% / (2 - 2)", FieldKind::Size).column, 8);
+        assert_eq!(error("min(50%, 2)", FieldKind::Size).column, 10);
+    }
+
+    #[test]
+    fn reports_keyword_and_function_errors() {
+        assert_eq!(error("center", FieldKind::Size).column, 1);
+        assert_eq!(error("10px + middle", FieldKind::Position).column, 8);
+        assert_eq!(error("average(1px, 2px)", FieldKind::Size).column, 1);
+        assert_eq!(error("clamp(1px, 2px)", FieldKind::Size).column, 1);
+        assert_eq!(error("calc(1px, 2px)", FieldKind::Size).column, 1);
+    }
+
+    #[test]
+    fn parses_aspect_ratios() {
+        assert_eq!(parse_aspect_ratio("16:9").unwrap(), 16.0 / 9.0);
+        assert_eq!(parse_aspect_ratio("4/3").unwrap(), 4.0 / 3.0);
+        assert_eq!(parse_aspect_ratio("1.5").unwrap(), 1.5);
+        assert_eq!(parse_aspect_ratio("16:0").unwrap_err().column, 4);
+        assert_eq!(parse_aspect_ratio(" wide").unwrap_err().column, 2);
+    }
+
+    fn placement(fields: &[(&str, &str)]) -> Placement {
+        let mut placement = Placement::default();
+        for (name, value) in fields {
+            let value = Some(value.to_string());
+            match *name {
+                "top" => placement.top = value,
+                "left" => placement.left = value,
+                "right" => placement.right = value,
+                "bottom" => placement.bottom = value,
+                "width" => placement.width = value,
+                "height" => placement.height = value,
+                "aspect_ratio" => placement.aspect_ratio = value,
+                _ => panic!("unknown field {}", name),
+            }
+        }
+        placement
+    }
+
+    #[test]
+    fn resolves_percentage_placements_in_screen_area() {
+        let frame = resolve(
+            &placement(&[("left", "50%"), ("width", "50%"), ("height", "100%")]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame, Rect::new(500.0, 125.0, 500.0, 800.0));
+    }
+
+    #[test]
+    fn keeps_current_values_for_unset_fields() {
+        let frame = resolve(&placement(&[]), SCREEN, WINDOW).unwrap();
+        assert_eq!(frame, WINDOW);
+    }
+
+    #[test]
+    fn centers_window_keeping_size() {
+        let frame = resolve(
+            &placement(&[("top", "center"), ("left", "center")]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame, Rect::new(300.0, 275.0, 400.0, 300.0));
+    }
+
+    #[test]
+    fn anchors_to_right_and_bottom_edges() {
+        let frame = resolve(
+            &placement(&[
+                ("right", "20px"),
+                ("bottom", "0%"),
+                ("width", "30%"),
+                ("height", "200px"),
+            ]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame, Rect::new(680.0, 625.0, 300.0, 200.0));
+    }
+
+    #[test]
+    fn stretches_between_opposite_anchors() {
+        let frame = resolve(
+            &placement(&[
+                ("left", "10px"),
+                ("right", "10px"),
+                ("top", "5%"),
+                ("bottom", "5%"),
+            ]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame, Rect::new(10.0, 65.0, 980.0, 720.0));
+    }
+
+    #[test]
+    fn derives_missing_dimension_from_aspect_ratio() {
+        let frame = resolve(
+            &placement(&[("width", "80%"), ("aspect_ratio", "16:9")]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame.width, 800.0);
+        assert_eq!(frame.height, 450.0);
+
+        let frame = resolve(
+            &placement(&[("height", "50%"), ("aspect_ratio", "1:2")]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!((frame.width, frame.height), (200.0, 400.0));
+    }
+
+    #[test]
+    fn fits_aspect_ratio_inside_both_dimensions() {
+        let frame = resolve(
+            &placement(&[
+                ("width", "100%"),
+                ("height", "100%"),
+                ("aspect_ratio", "1:1"),
+                ("left", "center"),
+                ("top", "center"),
+            ]),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame, Rect::new(100.0, 25.0, 800.0, 800.0));
+    }
+
+    fn grid_placement(grid: &str, cell: Option<&str>, span: Option<&str>) -> Placement {
+        Placement {
+            grid: Some(grid.to_string()),
+            cell: cell.map(str::to_string),
+            span: span.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn grid_region_covers_cells_of_usable_area() {
+        let region = GridRegion::parse("6x4", Some("0,0"), Some("4x4")).unwrap();
+        assert_eq!(
+            region.rect(SCREEN),
+            Rect::new(0.0, 25.0, 1000.0 * 4.0 / 6.0, 800.0)
+        );
+
+        let region = GridRegion::parse("4x2", Some("3, 1"), None).unwrap();
+        assert_eq!(region.rect(SCREEN), Rect::new(750.0, 425.0, 250.0, 400.0));
+
+        let region = GridRegion::parse(" 2X2 ", None, None).unwrap();
+        assert_eq!(region.rect(SCREEN), Rect::new(0.0, 25.0, 500.0, 400.0));
+    }
+
+    #[test]
+    fn adjacent_grid_regions_share_edges() {
+        let left = GridRegion::parse("3x1", Some("0,0"), Some("1x1"))
+            .unwrap()
+            .rect(SCREEN);
+        let rest = GridRegion::parse("3x1", Some("1,0"), Some("2x1"))
+            .unwrap()
+            .rect(SCREEN);
+        assert_eq!(left.x + left.width, rest.x);
+        assert_eq!(rest.x + rest.width, SCREEN.x + SCREEN.width);
+    }
+
+    #[test]
+    fn rejects_invalid_grids() {
+        let message = |grid, cell, span| {
+            GridRegion::parse(grid, cell, span)
+                .expect_err("expected a grid error")
+                .to_string()
+        };
+
+        assert!(message("6", None, None).contains("grid \"6\": column 2"));
+        assert!(message("0x4", None, None).contains("column 1: expected a positive integer"));
+        assert!(message("6x4", Some("a,0"), None).contains("cell \"a,0\": column 1"));
+        assert!(message("6x4", None, Some("2x0")).contains("span \"2x0\": column 3"));
+        assert!(message("6x4", Some("5,0"), Some("2x1")).contains("does not fit in a 6x4 grid"));
+        assert!(
+            message("6x4", Some("4294967295,0"), Some("1x1"))
+                .contains("does not fit in a 6x4 grid")
+        );
+    }
+
+    #[test]
+    fn resolves_grid_placements() {
+        let frame = resolve(
+            &grid_placement("6x4", Some("3,1"), Some("3x2")),
+            SCREEN,
+            WINDOW,
+        )
+        .unwrap();
+        assert_eq!(frame, Rect::new(500.0, 225.0, 500.0, 400.0));
+
+        let mut mixed = grid_placement("6x4", None, None);
+        mixed.width = Some("50%".to_string());
+        assert!(
+            resolve(&mixed, SCREEN, WINDOW)
+                .unwrap_err()
+                .to_string()
+                .contains("grid cannot be combined with width")
+        );
+
+        let cell_only = Placement {
+            cell: Some("0,0".to_string()),
+            ..Default::default()
+        };
+        assert!(resolve(&cell_only, SCREEN, WINDOW).is_err());
+    }
+
+    #[test]
+    fn cycles_variants_when_repeated_within_timeout() {
+        let mut cycles = PlacementCycles::default();
+        let timeout = Duration::from_millis(1500);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert_eq!(cycles.next_variant((1, 10), "left", 3, at(0), timeout), 0);
+        assert_eq!(cycles.next_variant((1, 10), "left", 3, at(500), timeout), 1);
+        assert_eq!(
+            cycles.next_variant((1, 10), "left", 3, at(1000), timeout),
+            2
+        );
+        assert_eq!(
+            cycles.next_variant((1, 10), "left", 3, at(1500), timeout),
+            0
+        );
+    }
+
+    #[test]
+    fn restarts_cycle_after_timeout_or_on_other_placement_or_window() {
+        let mut cycles = PlacementCycles::default();
+        let timeout = Duration::from_millis(1500);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert_eq!(cycles.next_variant((1, 10), "left", 3, at(0), timeout), 0);
+        assert_eq!(
+            cycles.next_variant((1, 10), "left", 3, at(2000), timeout),
+            0
+        );
+        assert_eq!(
+            cycles.next_variant((1, 10), "right", 3, at(2100), timeout),
+            0
+        );
+        assert_eq!(
+            cycles.next_variant((1, 11), "right", 3, at(2200), timeout),
+            0
+        );
+        assert_eq!(
+            cycles.next_variant((1, 10), "right", 3, at(2300), timeout),
+            1
+        );
+        assert_eq!(
+            cycles.next_variant((1, 10), "right", 3, at(2400), Duration::ZERO),
+            0
+        );
+        assert_eq!(
+            cycles.next_variant((1, 10), "center", 1, at(2500), timeout),
+            0
+        );
+        assert_eq!(
+            cycles.next_variant((1, 10), "center", 1, at(2600), timeout),
+            0
+        );
+    }
+
+    #[test]
+    fn resolve_errors_name_the_field() {
+        let err = resolve(&placement(&[("width", "calc(50% -)")]), SCREEN, WINDOW)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("width"), "{}", err);
+        assert!(err.contains("column 11"), "{}", err);
+    }
+
+    #[test]
+    fn rejects_placements_without_a_positive_size() {
+        for fields in [
+            &[("left", "60%"), ("right", "60%")][..],
+            &[("width", "calc(10px - 50%)")],
+            &[("height", "0px"), ("aspect_ratio", "16:9")],
+        ] {
+            let err = resolve(&placement(fields), SCREEN, WINDOW)
+                .unwrap_err()
+                .to_string();
+            assert!(err.contains("must be positive"), "{:?}: {}", fields, err);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Most recently used windows, as far as Pixie sees them.
+//!
+//! Windows are [`touch`]ed when Pixie focuses them and when the picker opens over them.
+//! Focus changes made outside Pixie aren't seen. The picker can list windows in this order
+//! instead of alphabetically.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+/// A window identified by (pid, CGWindowID).
+pub type WindowKey = (i32, u32);
+
+/// Maximum number of windows remembered.
+const RECENT_CAPACITY: usize = 256;
+
+static RECENT: OnceLock<Mutex<RecentWindows>> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct RecentWindows {
+    /// Most recent first
+    windows: VecDeque<WindowKey>,
+    capacity: usize,
+}
+
+impl RecentWindows {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            windows: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Mark a window as the most recently used one.
+    pub fn touch(&mut self, window: WindowKey) {
+        self.windows.retain(|w| *w != window);
+        self.windows.push_front(window);
+        self.windows.truncate(self.capacity);
+    }
+
+    /// How recently the window was used, 0 being the latest. `None` if it never was.
+    pub fn rank(&self, window: WindowKey) -> Option<usize> {
+        self.windows.iter().position(|w| *w == window)
+    }
+
+    /// Sort `items` most recently used first. Windows that were never used keep their
+    /// relative order after the others.
+    pub fn sort<T>(&self, items: &mut [T], key: impl Fn(&T) -> WindowKey) {
+        items.sort_by_key(|item| self.rank(key(item)).unwrap_or(usize::MAX));
+    }
+}
+
+impl Default for RecentWindows {
+    fn default() -> Self {
+        Self::new(RECENT_CAPACITY)
+    }
+}
+
+fn with_recent<T>(f: impl FnOnce(&mut RecentWindows) -> T) -> T {
+    let recent = RECENT.get_or_init(|| Mutex::new(RecentWindows::default()));
+    let mut recent = recent.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut recent)
+}
+
+/// Mark a window as the most recently used one.
+pub fn touch(window: WindowKey) {
+    with_recent(|recent| recent.touch(window));
+}
+
+/// Sort `items` most recently used first, see [`RecentWindows::sort`].
+pub fn sort_by_recency<T>(items: &mut [T], key: impl Fn(&T) -> WindowKey) {
+    with_recent(|recent| recent.sort(items, key));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn touching_moves_a_window_to_the_front() {
+        let mut recent = RecentWindows::new(3);
+        recent.touch((1, 1));
+        recent.touch((2, 2));
+        recent.touch((1, 1));
+        assert_eq!(recent.rank((1, 1)), Some(0));
+        assert_eq!(recent.rank((2, 2)), Some(1));
+
+        recent.touch((3, 3));
+        recent.touch((4, 4));
+        // Over capacity, the least recent one is forgotten
+        assert_eq!(recent.rank((2, 2)), None);
+        assert_eq!(recent.rank((1, 1)), Some(2));
+    }
+
+    #[test]
+    fn sorts_unknown_windows_last_in_their_order() {
+        let mut recent = RecentWindows::default();
+        recent.touch((3, 3));
+        recent.touch((1, 1));
+        let mut windows = vec![(5, 5), (3, 3), (4, 4), (1, 1)];
+        recent.sort(&mut windows, |w| *w);
+        assert_eq!(windows, vec![(1, 1), (3, 3), (5, 5), (4, 4)]);
+    }
+}
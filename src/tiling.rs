@@ -0,0 +1,168 @@
+//! Layouts for tiling several windows at once.
+
+use crate::geometry::Rect;
+
+/// Share of the area the first window gets in [`TileLayout::MasterStack`]
+const MASTER_RATIO: f64 = 0.5;
+
+/// How the window picker arranges the windows it tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileLayout {
+    /// Side by side, equal widths
+    #[default]
+    Columns,
+    /// Stacked top to bottom, equal heights
+    Rows,
+    /// As square a grid as fits, filled row by row; the last row stretches
+    Grid,
+    /// The first window on the left, the others stacked on the right
+    MasterStack,
+}
+
+impl TileLayout {
+    const ALL: [TileLayout; 4] = [
+        TileLayout::Columns,
+        TileLayout::Rows,
+        TileLayout::Grid,
+        TileLayout::MasterStack,
+    ];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|layout| *layout == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TileLayout::Columns => "columns",
+            TileLayout::Rows => "rows",
+            TileLayout::Grid => "grid",
+            TileLayout::MasterStack => "master-stack",
+        }
+    }
+
+    /// Frames for `count` windows inside `area`, in window order.
+    pub fn frames(self, count: usize, area: Rect) -> Vec<Rect> {
+        if count == 0 {
+            return Vec::new();
+        }
+        match self {
+            TileLayout::Columns => split_columns(area, count),
+            TileLayout::Rows => split_rows(area, count),
+            TileLayout::Grid => {
+                let columns = (count as f64).sqrt().ceil() as usize;
+                let rows = count.div_ceil(columns);
+                split_rows(area, rows)
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(row, row_area)| {
+                        let in_row = columns.min(count - row * columns);
+                        split_columns(row_area, in_row)
+                    })
+                    .collect()
+            }
+            TileLayout::MasterStack if count == 1 => vec![area],
+            TileLayout::MasterStack => {
+                let master_width = area.width * MASTER_RATIO;
+                let master = Rect::new(area.x, area.y, master_width, area.height);
+                let stack = Rect::new(
+                    area.x + master_width,
+                    area.y,
+                    area.width - master_width,
+                    area.height,
+                );
+                std::iter::once(master)
+                    .chain(split_rows(stack, count - 1))
+                    .collect()
+            }
+        }
+    }
+}
+
+fn split_columns(area: Rect, count: usize) -> Vec<Rect> {
+    let width = area.width / count as f64;
+    (0..count)
+        .map(|i| Rect::new(area.x + i as f64 * width, area.y, width, area.height))
+        .collect()
+}
+
+fn split_rows(area: Rect, count: usize) -> Vec<Rect> {
+    let height = area.height / count as f64;
+    (0..count)
+        .map(|i| Rect::new(area.x, area.y + i as f64 * height, area.width, height))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: 1200.0,
+        height: 800.0,
+    };
+
+    #[test]
+    fn splits_columns_and_rows() {
+        assert_eq!(
+            TileLayout::Columns.frames(3, AREA),
+            vec![
+                Rect::new(0.0, 0.0, 400.0, 800.0),
+                Rect::new(400.0, 0.0, 400.0, 800.0),
+                Rect::new(800.0, 0.0, 400.0, 800.0),
+            ]
+        );
+        assert_eq!(
+            TileLayout::Rows.frames(2, AREA),
+            vec![
+                Rect::new(0.0, 0.0, 1200.0, 400.0),
+                Rect::new(0.0, 400.0, 1200.0, 400.0),
+            ]
+        );
+        assert!(TileLayout::Grid.frames(0, AREA).is_empty());
+    }
+
+    #[test]
+    fn grid_stretches_the_last_row() {
+        assert_eq!(
+            TileLayout::Grid.frames(3, AREA),
+            vec![
+                Rect::new(0.0, 0.0, 600.0, 400.0),
+                Rect::new(600.0, 0.0, 600.0, 400.0),
+                Rect::new(0.0, 400.0, 1200.0, 400.0),
+            ]
+        );
+        assert_eq!(TileLayout::Grid.frames(9, AREA).len(), 9);
+        assert_eq!(TileLayout::Grid.frames(1, AREA), vec![AREA]);
+    }
+
+    #[test]
+    fn master_stack_splits_the_rest() {
+        assert_eq!(TileLayout::MasterStack.frames(1, AREA), vec![AREA]);
+        assert_eq!(
+            TileLayout::MasterStack.frames(3, AREA),
+            vec![
+                Rect::new(0.0, 0.0, 600.0, 800.0),
+                Rect::new(600.0, 0.0, 600.0, 400.0),
+                Rect::new(600.0, 400.0, 600.0, 400.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles_through_layouts() {
+        let mut layout = TileLayout::default();
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            seen.push(layout.label());
+            layout = layout.next();
+        }
+        assert_eq!(layout, TileLayout::Columns);
+        assert_eq!(seen, vec!["columns", "rows", "grid", "master-stack"]);
+    }
+}
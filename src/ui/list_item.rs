@@ -1,7 +1,9 @@
+use std::ops::Range;
+
 use gpui::{
-    AnyElement, App, ClickEvent, Div, ElementId, InteractiveElement, IntoElement, MouseButton,
-    MouseMoveEvent, ParentElement, RenderOnce, Stateful, StatefulInteractiveElement, Styled,
-    Window, div, px, rgba,
+    AnyElement, App, ClickEvent, Div, ElementId, FontWeight, HighlightStyle, InteractiveElement,
+    IntoElement, MouseButton, MouseMoveEvent, ParentElement, RenderOnce, Rgba, SharedString,
+    Stateful, StatefulInteractiveElement, Styled, StyledText, Window, div, px, rgba,
 };
 
 use crate::ui::Theme;
@@ -160,3 +162,32 @@ impl RenderOnce for ListItem {
         item
     }
 }
+
+/// `text` with the characters at the (ascending) char `positions` drawn bold in `color`, for
+/// showing what a search matched.
+pub fn highlighted_text(
+    text: impl Into<SharedString>,
+    positions: &[usize],
+    color: Rgba,
+) -> StyledText {
+    let text = text.into();
+    let mut positions = positions.iter().peekable();
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    for (index, (byte, ch)) in text.char_indices().enumerate() {
+        if positions.next_if(|&&position| position == index).is_none() {
+            continue;
+        }
+        let end = byte + ch.len_utf8();
+        match ranges.last_mut() {
+            Some(range) if range.end == byte => range.end = end,
+            _ => ranges.push(byte..end),
+        }
+    }
+
+    let style = HighlightStyle {
+        color: Some(color.into()),
+        font_weight: Some(FontWeight::BOLD),
+        ..Default::default()
+    };
+    StyledText::new(text).with_highlights(ranges.into_iter().map(|range| (range, style)))
+}
@@ -1,10 +1,19 @@
 mod list_item;
 mod theme;
+mod window_hints;
 mod window_picker;
 
-pub use list_item::ListItem;
+pub use list_item::{ListItem, highlighted_text};
 pub use theme::Theme;
+pub use window_hints::{
+    handle_hint_input, hint_input_from_keycode, is_window_hints_active, show_window_hints,
+};
 pub use window_picker::{
-    PickerInput, handle_picker_input, init, is_window_picker_active, picker_input_from_keycode,
-    show_window_picker, show_window_picker_select,
+    PickerInput, handle_picker_input, is_window_picker_active, picker_input_from_keycode,
+    show_app_launcher, show_command_palette, show_window_picker, show_window_picker_select,
 };
+
+pub fn init(cx: &mut gpui::App) {
+    window_picker::init(cx);
+    window_hints::init(cx);
+}
@@ -0,0 +1,248 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use gpui::{
+    App, Bounds, Context, Global, IntoElement, ParentElement, Render, Size, Window,
+    WindowBackgroundAppearance, WindowBounds, WindowHandle, WindowKind, WindowOptions, div,
+    prelude::*, px,
+};
+
+use crate::accessibility::{
+    Screen, find_window_by_id, focus_window, get_screen_windows, get_screens,
+};
+use crate::config::PickerOrder;
+use crate::geometry::Rect;
+use crate::hints::{HINT_ALPHABET, HintInput, HintMatcher, HintOutcome, place_hints};
+use crate::ui::Theme;
+
+static WINDOW_HINTS_ACTIVE: AtomicBool = AtomicBool::new(false);
+/// Size of a one-character label, in points
+const LABEL_CHAR_WIDTH: f64 = 14.0;
+const LABEL_HEIGHT: f64 = 24.0;
+
+/// A label drawn over a window, in the coordinates of its screen's overlay.
+struct HintTarget {
+    window: (i32, u32),
+    x: f32,
+    y: f32,
+    /// Which overlay shows the label
+    screen_index: usize,
+}
+
+#[derive(Default)]
+pub struct WindowHintsState {
+    targets: Vec<HintTarget>,
+    matcher: HintMatcher,
+    overlays: Vec<WindowHandle<HintOverlay>>,
+}
+
+impl Global for WindowHintsState {}
+
+pub fn init(cx: &mut App) {
+    cx.set_global(WindowHintsState::default());
+}
+
+pub fn is_window_hints_active() -> bool {
+    WINDOW_HINTS_ACTIVE.load(Ordering::SeqCst)
+}
+
+pub fn hint_input_from_keycode(keycode: i64, shift: bool) -> Option<HintInput> {
+    match keycode {
+        53 => Some(HintInput::Cancel),
+        51 | 117 => Some(HintInput::Backspace),
+        _ => crate::keymap::printable_char(keycode, shift).map(HintInput::Char),
+    }
+}
+
+/// Label every on-screen window and show the labels over all displays.
+pub fn show_window_hints(cx: &mut App) {
+    close_hints(cx);
+    let screens = match get_screens() {
+        Ok(screens) => screens,
+        Err(e) => {
+            eprintln!("Failed to get screens: {}", e);
+            return;
+        }
+    };
+
+    let mut windows = Vec::new();
+    for (screen_index, screen) in screens.iter().enumerate() {
+        match get_screen_windows(screen, PickerOrder::Recent) {
+            Ok(screen_windows) => windows.extend(
+                screen_windows
+                    .into_iter()
+                    .map(|window| (screen_index, window)),
+            ),
+            Err(e) => eprintln!("Failed to get windows of display '{}': {}", screen.name, e),
+        }
+    }
+    if windows.is_empty() {
+        return;
+    }
+
+    let frames: Vec<Rect> = windows
+        .iter()
+        .map(|(_, window)| {
+            let (x, y, width, height) = window.bounds;
+            Rect::new(x, y, width, height)
+        })
+        .collect();
+    let hints = place_hints(&frames, &HINT_ALPHABET, (LABEL_CHAR_WIDTH, LABEL_HEIGHT));
+
+    let mut labels = Vec::with_capacity(hints.len());
+    let mut targets = Vec::with_capacity(hints.len());
+    for ((screen_index, window), hint) in windows.into_iter().zip(hints) {
+        let screen = &screens[screen_index];
+        targets.push(HintTarget {
+            window: (window.pid, window.window_id),
+            x: (hint.x - screen.x) as f32,
+            y: (hint.y - screen.y) as f32,
+            screen_index,
+        });
+        labels.push(hint.label);
+    }
+    cx.set_global(WindowHintsState {
+        targets,
+        matcher: HintMatcher::new(labels),
+        overlays: Vec::new(),
+    });
+
+    let mut overlays = Vec::new();
+    for (screen_index, screen) in screens.iter().enumerate() {
+        match open_overlay(screen, screen_index, cx) {
+            Ok(handle) => overlays.push(handle),
+            Err(e) => eprintln!("Failed to open window hints on '{}': {}", screen.name, e),
+        }
+    }
+    WINDOW_HINTS_ACTIVE.store(!overlays.is_empty(), Ordering::SeqCst);
+    cx.update_global::<WindowHintsState, _>(|state, _| state.overlays = overlays);
+}
+
+fn open_overlay(
+    screen: &Screen,
+    screen_index: usize,
+    cx: &mut App,
+) -> anyhow::Result<WindowHandle<HintOverlay>> {
+    cx.open_window(
+        WindowOptions {
+            titlebar: None,
+            focus: false,
+            show: true,
+            window_bounds: Some(WindowBounds::Windowed(Bounds::new(
+                gpui::Point::new(px(screen.x as f32), px(screen.y as f32)),
+                Size {
+                    width: px(screen.width as f32),
+                    height: px(screen.height as f32),
+                },
+            ))),
+            window_background: WindowBackgroundAppearance::Transparent,
+            kind: WindowKind::PopUp,
+            ..Default::default()
+        },
+        |_window, cx| cx.new(|_cx| HintOverlay { screen_index }),
+    )
+}
+
+pub fn handle_hint_input(input: HintInput, cx: &mut App) {
+    if !is_window_hints_active() {
+        return;
+    }
+    let outcome = cx.update_global::<WindowHintsState, _>(|state, _| match input {
+        HintInput::Char(ch) => Some(state.matcher.input(ch)),
+        HintInput::Backspace => {
+            state.matcher.backspace();
+            Some(HintOutcome::Pending)
+        }
+        HintInput::Cancel => None,
+    });
+
+    match outcome {
+        Some(HintOutcome::Matched(index)) => {
+            let window = cx.global::<WindowHintsState>().targets[index].window;
+            close_hints(cx);
+            let (pid, window_id) = window;
+            if let Err(e) = find_window_by_id(pid, window_id).and_then(|w| focus_window(&w)) {
+                eprintln!(
+                    "Failed to focus hinted window (pid={}, id={}): {}",
+                    pid, window_id, e
+                );
+            }
+        }
+        Some(HintOutcome::Pending) => refresh_overlays(cx),
+        Some(HintOutcome::NoMatch) | None => close_hints(cx),
+    }
+}
+
+fn refresh_overlays(cx: &mut App) {
+    let overlays = cx.global::<WindowHintsState>().overlays.clone();
+    for overlay in overlays {
+        let _ = overlay.update(cx, |_, _window, cx| cx.notify());
+    }
+}
+
+fn close_hints(cx: &mut App) {
+    WINDOW_HINTS_ACTIVE.store(false, Ordering::SeqCst);
+    let overlays =
+        cx.update_global::<WindowHintsState, _>(|state, _| std::mem::take(&mut state.overlays));
+    for overlay in overlays {
+        let _ = overlay.update(cx, |_, window, _cx| {
+            window.remove_window();
+        });
+    }
+}
+
+/// Transparent window covering one display, drawing the labels of its windows.
+pub struct HintOverlay {
+    screen_index: usize,
+}
+
+impl Render for HintOverlay {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = Theme::default();
+        let state = cx.global::<WindowHintsState>();
+        let matcher = &state.matcher;
+        let typed_len = matcher.typed.chars().count();
+
+        div().size_full().relative().children(
+            state
+                .targets
+                .iter()
+                .enumerate()
+                .filter(|(index, target)| {
+                    target.screen_index == self.screen_index && matcher.is_candidate(*index)
+                })
+                .map(|(index, target)| {
+                    let label = &matcher.labels[index];
+                    let (typed, rest) = label.split_at(
+                        label
+                            .char_indices()
+                            .nth(typed_len)
+                            .map_or(label.len(), |(i, _)| i),
+                    );
+                    div()
+                        .absolute()
+                        .left(px(target.x))
+                        .top(px(target.y))
+                        .h(px(LABEL_HEIGHT as f32))
+                        .px_1()
+                        .flex()
+                        .items_center()
+                        .rounded_md()
+                        .border_1()
+                        .border_color(theme.border)
+                        .bg(theme.accent)
+                        .text_sm()
+                        .font_weight(gpui::FontWeight::BOLD)
+                        .child(
+                            div()
+                                .text_color(theme.muted_foreground)
+                                .child(typed.to_uppercase()),
+                        )
+                        .child(
+                            div()
+                                .text_color(theme.background)
+                                .child(rest.to_uppercase()),
+                        )
+                }),
+        )
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::ops::Range;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -5,27 +6,50 @@ use std::sync::atomic::{AtomicBool, Ordering};
 use cocoa::appkit::NSApplication;
 use cocoa::base::nil;
 use gpui::{
-    App, Bounds, Context, Entity, FocusHandle, Focusable, Global, InteractiveElement, IntoElement,
-    KeyBinding, ParentElement, Render, Size, UniformListScrollHandle, Window,
+    AnyElement, App, Bounds, Context, Entity, FocusHandle, Focusable, Global, InteractiveElement,
+    IntoElement, KeyBinding, ParentElement, Render, Size, UniformListScrollHandle, Window,
     WindowBackgroundAppearance, WindowBounds, WindowHandle, WindowKind, WindowOptions, actions,
     div, img, prelude::*, px, uniform_list,
 };
 
 use crate::accessibility::{
-    Screen, WindowEntry, find_window_by_id, focus_window, get_all_windows, get_focused_window,
-    get_screen_for_window, get_screens, get_window_rect, raise_window, tile_windows_in_columns,
+    Screen, WindowEntry, find_window_by_id, focus_window, get_all_windows, get_bundle_icon_path,
+    get_dock_height, get_focused_window, get_screen_for_window, get_screens, get_window_rect,
+    raise_window, tile_area, tile_windows,
 };
-use crate::ui::{ListItem, Theme};
+use crate::apps;
+use crate::config;
+use crate::fuzzy::FieldsMatch;
+use crate::geometry::Rect;
+use crate::palette::{PaletteCommand, PaletteEntry, palette_entries};
+use crate::picker::{PickerApp, PickerEffect, PickerMode, PickerModel, PickerScope, PickerWindow};
+use crate::recent;
+use crate::ui::{ListItem, Theme, highlighted_text};
+use crate::window::SavedWindow;
+use crate::{LeaderModeState, WindowManagerState, handle_action};
+
+pub use crate::picker::PickerInput;
 
 actions!(
     window_picker,
-    [SelectDown, SelectUp, ToggleSelect, Confirm, Cancel]
+    [
+        SelectDown,
+        SelectUp,
+        ToggleSelect,
+        Confirm,
+        Cancel,
+        NextLayout,
+        NextScreen
+    ]
 );
 
 static WINDOW_PICKER_ACTIVE: AtomicBool = AtomicBool::new(false);
 const WINDOW_PICKER_KEY_CONTEXT: &str = "WindowPicker";
 const PICKER_WIDTH: f32 = 560.0;
-const PICKER_KEY_INPUTS: [(&str, PickerInput); 8] = [
+/// Largest size of the tiling outline in the footer
+const OUTLINE_MAX_WIDTH: f64 = 96.0;
+const OUTLINE_MAX_HEIGHT: f64 = 60.0;
+const PICKER_KEY_INPUTS: [(&str, PickerInput); 10] = [
     ("j", PickerInput::SelectDown),
     ("down", PickerInput::SelectDown),
     ("k", PickerInput::SelectUp),
@@ -34,19 +58,10 @@ const PICKER_KEY_INPUTS: [(&str, PickerInput); 8] = [
     ("enter", PickerInput::Confirm),
     ("q", PickerInput::Cancel),
     ("escape", PickerInput::Cancel),
+    ("tab", PickerInput::NextLayout),
+    ("shift-tab", PickerInput::NextScreen),
 ];
 
-#[derive(Debug, Clone, Copy)]
-pub enum PickerInput {
-    SelectDown,
-    SelectUp,
-    ToggleSelect,
-    Confirm,
-    Cancel,
-    SearchBackspace,
-    SearchChar(char),
-}
-
 pub fn init(cx: &mut App) {
     cx.bind_keys(
         PICKER_KEY_INPUTS
@@ -62,66 +77,14 @@ pub fn is_window_picker_active() -> bool {
 
 pub fn picker_input_from_keycode(keycode: i64, shift: bool) -> Option<PickerInput> {
     match keycode {
+        48 if shift => Some(PickerInput::NextScreen),
+        48 => Some(PickerInput::NextLayout),
         125 => Some(PickerInput::SelectDown),
         126 => Some(PickerInput::SelectUp),
         36 => Some(PickerInput::Confirm),
         53 => Some(PickerInput::Cancel),
         51 | 117 => Some(PickerInput::SearchBackspace),
-        _ => printable_char_from_keycode(keycode, shift).map(PickerInput::SearchChar),
-    }
-}
-
-fn printable_char_from_keycode(keycode: i64, shift: bool) -> Option<char> {
-    match keycode {
-        0 => Some(if shift { 'A' } else { 'a' }),
-        1 => Some(if shift { 'S' } else { 's' }),
-        2 => Some(if shift { 'D' } else { 'd' }),
-        3 => Some(if shift { 'F' } else { 'f' }),
-        4 => Some(if shift { 'H' } else { 'h' }),
-        5 => Some(if shift { 'G' } else { 'g' }),
-        6 => Some(if shift { 'Z' } else { 'z' }),
-        7 => Some(if shift { 'X' } else { 'x' }),
-        8 => Some(if shift { 'C' } else { 'c' }),
-        9 => Some(if shift { 'V' } else { 'v' }),
-        11 => Some(if shift { 'B' } else { 'b' }),
-        12 => Some(if shift { 'Q' } else { 'q' }),
-        13 => Some(if shift { 'W' } else { 'w' }),
-        14 => Some(if shift { 'E' } else { 'e' }),
-        15 => Some(if shift { 'R' } else { 'r' }),
-        16 => Some(if shift { 'Y' } else { 'y' }),
-        17 => Some(if shift { 'T' } else { 't' }),
-        18 => Some(if shift { '!' } else { '1' }),
-        19 => Some(if shift { '@' } else { '2' }),
-        20 => Some(if shift { '#' } else { '3' }),
-        21 => Some(if shift { '$' } else { '4' }),
-        22 => Some(if shift { '^' } else { '6' }),
-        23 => Some(if shift { '%' } else { '5' }),
-        24 => Some(if shift { '+' } else { '=' }),
-        25 => Some(if shift { '(' } else { '9' }),
-        26 => Some(if shift { '&' } else { '7' }),
-        27 => Some(if shift { '_' } else { '-' }),
-        28 => Some(if shift { '*' } else { '8' }),
-        29 => Some(if shift { ')' } else { '0' }),
-        30 => Some(if shift { '}' } else { ']' }),
-        31 => Some(if shift { 'O' } else { 'o' }),
-        32 => Some(if shift { 'U' } else { 'u' }),
-        33 => Some(if shift { '{' } else { '[' }),
-        34 => Some(if shift { 'I' } else { 'i' }),
-        35 => Some(if shift { 'P' } else { 'p' }),
-        37 => Some(if shift { 'L' } else { 'l' }),
-        38 => Some(if shift { 'J' } else { 'j' }),
-        39 => Some(if shift { '"' } else { '\'' }),
-        40 => Some(if shift { 'K' } else { 'k' }),
-        41 => Some(if shift { ':' } else { ';' }),
-        42 => Some(if shift { '|' } else { '\\' }),
-        43 => Some(if shift { '<' } else { ',' }),
-        44 => Some(if shift { '?' } else { '/' }),
-        45 => Some(if shift { 'N' } else { 'n' }),
-        46 => Some(if shift { 'M' } else { 'm' }),
-        47 => Some(if shift { '>' } else { '.' }),
-        49 => Some(' '),
-        50 => Some(if shift { '~' } else { '`' }),
-        _ => None,
+        _ => crate::keymap::printable_char(keycode, shift).map(PickerInput::SearchChar),
     }
 }
 
@@ -131,6 +94,8 @@ fn picker_input_from_key(key: &str, shift: bool) -> Option<PickerInput> {
         "up" => Some(PickerInput::SelectUp),
         "enter" | "return" => Some(PickerInput::Confirm),
         "escape" | "esc" => Some(PickerInput::Cancel),
+        "tab" if shift => Some(PickerInput::NextScreen),
+        "tab" => Some(PickerInput::NextLayout),
         "backspace" | "delete" => Some(PickerInput::SearchBackspace),
         "space" => Some(PickerInput::SearchChar(' ')),
         _ if key.chars().count() == 1 => key.chars().next().map(|ch| {
@@ -156,65 +121,272 @@ fn picker_key_binding(key: &str, input: PickerInput) -> KeyBinding {
         }
         PickerInput::Confirm => KeyBinding::new(key, Confirm, Some(WINDOW_PICKER_KEY_CONTEXT)),
         PickerInput::Cancel => KeyBinding::new(key, Cancel, Some(WINDOW_PICKER_KEY_CONTEXT)),
-        PickerInput::SearchBackspace | PickerInput::SearchChar(_) => unreachable!(),
+        PickerInput::NextLayout => {
+            KeyBinding::new(key, NextLayout, Some(WINDOW_PICKER_KEY_CONTEXT))
+        }
+        PickerInput::NextScreen => {
+            KeyBinding::new(key, NextScreen, Some(WINDOW_PICKER_KEY_CONTEXT))
+        }
+        PickerInput::SearchBackspace
+        | PickerInput::SearchChar(_)
+        | PickerInput::Hover(_)
+        | PickerInput::Click(_) => unreachable!(),
     }
 }
 
 #[derive(Default)]
 pub struct WindowPickerState {
-    pub windows: Vec<WindowEntry>,
-    pub current_monitor_count: usize,
+    pub model: PickerModel,
     pub current_screen: Option<Screen>,
-    pub focused_index: usize,
-    pub selected_indices: Vec<usize>,
-    pub search_mode: bool,
-    pub search_query: String,
-    pub search_matches: Vec<usize>,
-    pub search_match_index: usize,
-    pub previously_focused_window: Option<(i32, u32)>,
-    pub last_previewed_window: Option<(i32, u32)>,
+    pub screens: Vec<Screen>,
+    /// Read when the picker opens, for the tiling outline
+    pub dock_height: f64,
     pub window_handle: Option<WindowHandle<PickerContainer>>,
 }
 
-impl Global for WindowPickerState {}
+impl WindowPickerState {
+    /// The display chosen to tile on, or the picker's display
+    fn target_screen(&self) -> Option<&Screen> {
+        self.model
+            .target_screen
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|index| self.screens.get(index))
+            .or(self.current_screen.as_ref())
+    }
+}
 
-type WindowIdentity = (i32, u32);
+impl Global for WindowPickerState {}
 
-fn has_secondary_group(state: &WindowPickerState) -> bool {
-    state.current_monitor_count > 0 && state.windows.len() > state.current_monitor_count
+/// A picker row for `window`, with the number of the display showing it and its slots.
+fn picker_window(
+    window: WindowEntry,
+    screens: &[Screen],
+    saved_windows: &HashMap<char, SavedWindow>,
+) -> PickerWindow {
+    let (x, y, width, height) = window.bounds;
+    let (center_x, center_y) = (x + width / 2.0, y + height / 2.0);
+    let screen = screens
+        .iter()
+        .position(|screen| {
+            center_x >= screen.x
+                && center_x < screen.x + screen.width
+                && center_y >= screen.y
+                && center_y < screen.y + screen.height
+        })
+        .map(|index| index + 1);
+    let mut slots: Vec<char> = saved_windows
+        .iter()
+        .filter(|(_, saved)| saved.pid == window.pid && saved.window_id == window.window_id)
+        .map(|(slot, _)| *slot)
+        .collect();
+    slots.sort_unstable();
+
+    PickerWindow {
+        pid: window.pid,
+        window_id: window.window_id,
+        app_name: window.app_name,
+        title: window.title,
+        app_icon_path: window.app_icon_path,
+        screen,
+        minimized: window.minimized,
+        slots,
+    }
 }
 
-fn visual_row_count(state: &WindowPickerState) -> usize {
-    state.windows.len() + usize::from(has_secondary_group(state))
+/// A miniature of the target display showing where each window to tile would land,
+/// numbered in tiling order.
+fn tile_outline(state: &WindowPickerState, theme: &Theme) -> AnyElement {
+    let (screen, tiled) = state.target_screen().map_or_else(
+        || {
+            let frame = Rect::new(0.0, 0.0, 16.0, 10.0);
+            (frame, frame)
+        },
+        |screen| (screen.frame(), tile_area(screen, state.dock_height)),
+    );
+    let scale = (OUTLINE_MAX_WIDTH / screen.width).min(OUTLINE_MAX_HEIGHT / screen.height);
+    // Where the windows land within the display, at the outline's scale
+    let area = Rect::new(
+        (tiled.x - screen.x) * scale,
+        (tiled.y - screen.y) * scale,
+        tiled.width * scale,
+        tiled.height * scale,
+    );
+
+    div()
+        .relative()
+        .flex_none()
+        .w(px((screen.width * scale) as f32))
+        .h(px((screen.height * scale) as f32))
+        .rounded_sm()
+        .border_1()
+        .border_color(theme.border)
+        .children(state.model.tile_preview(area).into_iter().enumerate().map(
+            |(order, (_, frame))| {
+                div()
+                    .absolute()
+                    .left(px(frame.x as f32))
+                    .top(px(frame.y as f32))
+                    .w(px(frame.width as f32))
+                    .h(px(frame.height as f32))
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .border_1()
+                    .border_color(theme.accent)
+                    .bg(theme.selected)
+                    .text_xs()
+                    .text_color(theme.foreground)
+                    .child((order + 1).to_string())
+            },
+        ))
+        .into_any_element()
 }
 
-fn visual_index_to_window_index(
-    visual_index: usize,
-    current_monitor_count: usize,
-    separator_present: bool,
-) -> Option<usize> {
-    if !separator_present {
-        return Some(visual_index);
-    }
-    if visual_index == current_monitor_count {
-        return None;
-    }
-    if visual_index > current_monitor_count {
-        return Some(visual_index - 1);
+/// A 16pt app icon, or a placeholder square without one.
+fn app_icon(icon_path: Option<&str>, theme: Theme) -> AnyElement {
+    let placeholder = move || {
+        div()
+            .w(px(16.0))
+            .h(px(16.0))
+            .rounded_sm()
+            .bg(theme.muted)
+            .border_1()
+            .border_color(theme.border)
+            .flex_none()
+            .into_any_element()
+    };
+    match icon_path {
+        Some(icon_path) => img(PathBuf::from(icon_path))
+            .w(px(16.0))
+            .h(px(16.0))
+            .rounded_sm()
+            .with_fallback(placeholder)
+            .flex_none()
+            .into_any_element(),
+        None => placeholder(),
     }
-    Some(visual_index)
 }
 
-fn window_index_to_visual_index(
-    window_index: usize,
-    current_monitor_count: usize,
-    separator_present: bool,
-) -> usize {
-    if separator_present && window_index >= current_monitor_count {
-        window_index + 1
-    } else {
-        window_index
-    }
+/// A launcher row for an app without windows: its icon and name, marked as an app.
+fn app_row(
+    index: usize,
+    app: &PickerApp,
+    matched: Option<&FieldsMatch>,
+    is_focused: bool,
+    theme: &Theme,
+) -> AnyElement {
+    let name_matches = matched.map_or(&[][..], |matched| matched.positions[0].as_slice());
+
+    div()
+        .py(px(2.0))
+        .child(
+            ListItem::new(index)
+                .secondary_selected(is_focused)
+                .on_mouse_enter(move |_ev, _window, cx| {
+                    let focused = cx.global::<WindowPickerState>().model.focused_index;
+                    if focused != index {
+                        handle_picker_input(PickerInput::Hover(index), cx);
+                    }
+                })
+                .on_click(move |_ev, _window, cx| {
+                    handle_picker_input(PickerInput::Click(index), cx);
+                })
+                .suffix(
+                    div()
+                        .w(px(140.0))
+                        .flex_none()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child("Launch app"),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .w_full()
+                        .child(app_icon(app.icon_path.as_deref(), *theme))
+                        .child(
+                            div()
+                                .flex_1()
+                                .min_w(px(0.0))
+                                .overflow_hidden()
+                                .whitespace_nowrap()
+                                .text_ellipsis()
+                                .text_xs()
+                                .text_color(theme.foreground)
+                                .child(highlighted_text(
+                                    app.name.clone(),
+                                    name_matches,
+                                    theme.accent,
+                                )),
+                        ),
+                ),
+        )
+        .into_any_element()
+}
+
+/// A palette row: the entry's title, with its category in the suffix column.
+fn palette_row(
+    index: usize,
+    entry: &PaletteEntry,
+    matched: Option<&FieldsMatch>,
+    is_focused: bool,
+    theme: &Theme,
+) -> AnyElement {
+    let (title_matches, category_matches) = match matched {
+        Some(matched) => (
+            matched.positions[0].as_slice(),
+            matched.positions[1].as_slice(),
+        ),
+        None => (&[][..], &[][..]),
+    };
+
+    div()
+        .py(px(2.0))
+        .child(
+            ListItem::new(index)
+                .secondary_selected(is_focused)
+                .on_mouse_enter(move |_ev, _window, cx| {
+                    let focused = cx.global::<WindowPickerState>().model.focused_index;
+                    if focused != index {
+                        handle_picker_input(PickerInput::Hover(index), cx);
+                    }
+                })
+                .on_click(move |_ev, _window, cx| {
+                    handle_picker_input(PickerInput::Click(index), cx);
+                })
+                .suffix(
+                    div()
+                        .w(px(140.0))
+                        .flex_none()
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .text_ellipsis()
+                        .text_sm()
+                        .text_color(theme.muted_foreground)
+                        .child(highlighted_text(
+                            entry.category.to_string(),
+                            category_matches,
+                            theme.accent,
+                        )),
+                )
+                .child(
+                    div()
+                        .w_full()
+                        .overflow_hidden()
+                        .whitespace_nowrap()
+                        .text_ellipsis()
+                        .text_xs()
+                        .text_color(theme.foreground)
+                        .child(highlighted_text(
+                            entry.title.clone(),
+                            title_matches,
+                            theme.accent,
+                        )),
+                ),
+        )
+        .into_any_element()
 }
 
 pub struct PickerContainer {
@@ -263,16 +435,21 @@ impl WindowList {
 
 impl Render for WindowList {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let state = cx.global::<WindowPickerState>();
+        let model = &cx.global::<WindowPickerState>().model;
         let theme = Theme::default();
-        let windows = &state.windows;
-        let focused_index = state.focused_index;
-        let current_monitor_count = state.current_monitor_count;
-        let separator_present = has_secondary_group(state);
-        let row_count = visual_row_count(state);
+        let row_count = model.row_count();
         let scroll_handle = self.scroll_handle.clone();
 
-        if windows.is_empty() {
+        if model.visible.is_empty() {
+            let message = if model.mode == PickerMode::Palette {
+                "No matching commands"
+            } else if model.mode == PickerMode::Launcher {
+                "No matching windows or apps"
+            } else if model.windows.is_empty() {
+                "No windows on this monitor"
+            } else {
+                "No matching windows"
+            };
             return div()
                 .flex()
                 .h(px(100.0))
@@ -280,13 +457,13 @@ impl Render for WindowList {
                 .items_center()
                 .justify_center()
                 .text_color(theme.muted_foreground)
-                .child("No windows on this monitor")
+                .child(message)
                 .into_any();
         }
 
-        let focused_visual_index =
-            window_index_to_visual_index(focused_index, current_monitor_count, separator_present);
-        if self.last_scrolled_visual_index != Some(focused_visual_index) {
+        if let Some(focused_visual_index) = model.focused_row()
+            && self.last_scrolled_visual_index != Some(focused_visual_index)
+        {
             scroll_handle.scroll_to_item(focused_visual_index, gpui::ScrollStrategy::Top);
             self.last_scrolled_visual_index = Some(focused_visual_index);
         }
@@ -295,111 +472,154 @@ impl Render for WindowList {
             "window-list",
             row_count,
             cx.processor(move |_this, range: Range<usize>, _window, cx| {
-                let state = cx.global::<WindowPickerState>();
+                let model = &cx.global::<WindowPickerState>().model;
                 let theme = Theme::default();
-                let windows = &state.windows;
-                let focused = state.focused_index;
-                let selected = &state.selected_indices;
-                let current_monitor_count = state.current_monitor_count;
-                let separator_present = has_secondary_group(state);
+                let windows = &model.windows;
+                let focused = model.focused_index;
+                let selected = &model.selected_indices;
+
+                if model.mode == PickerMode::Palette {
+                    return range
+                        .filter_map(|i| model.window_at_row(i))
+                        .map(|index| {
+                            palette_row(
+                                index,
+                                &model.palette[index],
+                                model.matches.get(&index),
+                                index == focused,
+                                &theme,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                }
 
                 range
-                    .map(|i| {
-                        match visual_index_to_window_index(
-                            i,
-                            current_monitor_count,
-                            separator_present,
-                        ) {
-                            Some(window_index) => {
-                                let win = &windows[window_index];
-                                let is_focused = window_index == focused;
-                                let is_selected = selected.contains(&window_index);
-                                let icon = if let Some(icon_path) = &win.app_icon_path {
-                                    img(PathBuf::from(icon_path))
-                                        .w(px(16.0))
-                                        .h(px(16.0))
-                                        .rounded_sm()
-                                        .with_fallback(move || {
-                                            div()
-                                                .w(px(16.0))
-                                                .h(px(16.0))
-                                                .rounded_sm()
-                                                .bg(theme.muted)
-                                                .border_1()
-                                                .border_color(theme.border)
-                                                .into_any_element()
-                                        })
-                                        .flex_none()
-                                        .into_any_element()
-                                } else {
-                                    div()
-                                        .w(px(16.0))
-                                        .h(px(16.0))
-                                        .rounded_sm()
-                                        .bg(theme.muted)
-                                        .border_1()
-                                        .border_color(theme.border)
-                                        .flex_none()
-                                        .into_any_element()
+                    .map(|i| match model.window_at_row(i) {
+                        Some(window_index) => {
+                            if let Some(app) = model.app_at(window_index) {
+                                return app_row(
+                                    window_index,
+                                    app,
+                                    model.matches.get(&window_index),
+                                    window_index == focused,
+                                    &theme,
+                                );
+                            }
+                            let win = &windows[window_index];
+                            let (app_name_matches, title_matches) =
+                                match model.matches.get(&window_index) {
+                                    Some(matched) => (
+                                        matched.positions[0].as_slice(),
+                                        matched.positions[1].as_slice(),
+                                    ),
+                                    None => (&[][..], &[][..]),
                                 };
-
+                            let is_focused = window_index == focused;
+                            let is_selected = selected.contains(&window_index);
+                            let ordinal = model.selection_ordinal(window_index).map(|ordinal| {
                                 div()
-                                    .py(px(2.0))
-                                    .child(
-                                        ListItem::new(window_index)
-                                            .selected(is_selected)
-                                            .secondary_selected(is_focused)
-                                            .on_mouse_enter(move |_ev, _window, cx| {
-                                                hover_focus(window_index, cx);
-                                            })
-                                            .on_click(move |_ev, _window, cx| {
-                                                click_select(window_index, cx);
-                                            })
-                                            .suffix(
-                                                div()
-                                                    .w(px(140.0))
-                                                    .flex_none()
-                                                    .overflow_hidden()
-                                                    .whitespace_nowrap()
-                                                    .text_ellipsis()
-                                                    .text_sm()
-                                                    .text_color(theme.muted_foreground)
-                                                    .child(win.app_name.clone()),
-                                            )
-                                            .child(
-                                                div()
-                                                    .flex()
-                                                    .items_center()
-                                                    .gap_2()
-                                                    .w_full()
-                                                    .child(icon)
-                                                    .child(
-                                                        div()
-                                                            .flex_1()
-                                                            .min_w(px(0.0))
-                                                            .overflow_hidden()
-                                                            .whitespace_nowrap()
-                                                            .text_ellipsis()
-                                                            .text_xs()
-                                                            .text_color(theme.foreground)
-                                                            .child(win.title.clone()),
-                                                    ),
-                                            ),
-                                    )
-                                    .into_any_element()
-                            }
-                            None => div()
+                                    .flex_none()
+                                    .flex()
+                                    .items_center()
+                                    .justify_center()
+                                    .size(px(16.0))
+                                    .rounded_full()
+                                    .bg(theme.accent)
+                                    .text_xs()
+                                    .text_color(theme.background)
+                                    .child(ordinal.to_string())
+                            });
+                            let icon = app_icon(win.app_icon_path.as_deref(), theme);
+
+                            div()
                                 .py(px(2.0))
                                 .child(
-                                    ListItem::new("picker-group-separator").separator().child(
-                                        div()
-                                            .text_xs()
-                                            .text_color(theme.muted_foreground)
-                                            .child("Other monitors + minimized"),
-                                    ),
+                                    ListItem::new(window_index)
+                                        .selected(is_selected)
+                                        .secondary_selected(is_focused)
+                                        .on_mouse_enter(move |_ev, _window, cx| {
+                                            let focused = cx
+                                                .global::<WindowPickerState>()
+                                                .model
+                                                .focused_index;
+                                            if focused != window_index {
+                                                handle_picker_input(
+                                                    PickerInput::Hover(window_index),
+                                                    cx,
+                                                );
+                                            }
+                                        })
+                                        .on_click(move |_ev, _window, cx| {
+                                            handle_picker_input(
+                                                PickerInput::Click(window_index),
+                                                cx,
+                                            );
+                                        })
+                                        .suffix(
+                                            div()
+                                                .w(px(140.0))
+                                                .flex_none()
+                                                .overflow_hidden()
+                                                .whitespace_nowrap()
+                                                .text_ellipsis()
+                                                .text_sm()
+                                                .text_color(theme.muted_foreground)
+                                                .child(highlighted_text(
+                                                    win.app_name.clone(),
+                                                    app_name_matches,
+                                                    theme.accent,
+                                                )),
+                                        )
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_2()
+                                                .w_full()
+                                                .children(ordinal)
+                                                .child(icon)
+                                                .child(
+                                                    div()
+                                                        .flex_1()
+                                                        .min_w(px(0.0))
+                                                        .overflow_hidden()
+                                                        .whitespace_nowrap()
+                                                        .text_ellipsis()
+                                                        .text_xs()
+                                                        .text_color(theme.foreground)
+                                                        .child(highlighted_text(
+                                                            win.title.clone(),
+                                                            title_matches,
+                                                            theme.accent,
+                                                        )),
+                                                )
+                                                .children(win.slots.iter().map(|slot| {
+                                                    div()
+                                                        .flex_none()
+                                                        .px_1()
+                                                        .rounded_sm()
+                                                        .border_1()
+                                                        .border_color(theme.border)
+                                                        .bg(theme.muted)
+                                                        .text_xs()
+                                                        .text_color(theme.foreground)
+                                                        .child(slot.to_string())
+                                                })),
+                                        ),
                                 )
-                                .into_any_element(),
+                                .into_any_element()
                         }
+                        None => div()
+                            .py(px(2.0))
+                            .child(
+                                ListItem::new("picker-group-separator").separator().child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(theme.muted_foreground)
+                                        .child("Other monitors + minimized"),
+                                ),
+                            )
+                            .into_any_element(),
                     })
                     .collect::<Vec<_>>()
             }),
@@ -414,47 +634,90 @@ impl Render for PickerContainer {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let theme = Theme::default();
         let state = cx.global::<WindowPickerState>();
-        let row_count = visual_row_count(state);
+        let model = &state.model;
+        let row_count = model.row_count();
 
-        let height = px((row_count.min(10) as f32 * 40.0 + 60.0).max(160.0));
+        let height = px((row_count.min(10) as f32 * 40.0 + 136.0).max(180.0));
 
-        let (title, hint) = if state.search_mode {
-            let current_hit = if state.search_matches.is_empty() {
-                0
-            } else {
-                state.search_match_index + 1
-            };
-            (
-                format!(
-                    "/{} ({}/{})",
-                    state.search_query,
-                    current_hit,
-                    state.search_matches.len()
-                ),
-                "type to search • enter/esc exit".to_string(),
+        let current_hit = model.focused_position().map_or(0, |position| position + 1);
+        let scope = if model.scope == PickerScope::AllMonitors {
+            String::new()
+        } else {
+            format!(" · {}", model.scope.label())
+        };
+        let title = if model.mode == PickerMode::Palette {
+            format!(
+                ":{} ({}/{})",
+                model.search_query,
+                current_hit,
+                model.visible.len()
+            )
+        } else if model.mode == PickerMode::Launcher {
+            format!(
+                "Switch or launch: {} ({}/{})",
+                model.search_query,
+                current_hit,
+                model.visible.len()
             )
-        } else if state.search_query.is_empty() {
-            (
-                "Tile windows".to_string(),
-                "j/k navigate • space select • / search • n/N next/prev • enter tile • esc cancel"
-                    .to_string(),
+        } else if model.assigning_slot {
+            let app_name = model
+                .windows
+                .get(model.focused_index)
+                .map_or("", |window| window.app_name.as_str());
+            format!("Assign {} to slot", app_name)
+        } else if model.search_mode {
+            format!(
+                "/{} ({}/{}){}",
+                model.search_query,
+                current_hit,
+                model.visible.len(),
+                scope
             )
+        } else if model.search_query.is_empty() {
+            format!("Tile windows{}", scope)
         } else {
-            let current_hit = if state.search_matches.is_empty() {
-                0
-            } else {
-                state.search_match_index + 1
-            };
-            (
-                format!(
-                    "Search /{} ({}/{})",
-                    state.search_query,
-                    current_hit,
-                    state.search_matches.len()
-                ),
-                "".to_string(),
+            format!(
+                "Search /{} ({}/{}){}",
+                model.search_query,
+                current_hit,
+                model.visible.len(),
+                scope
             )
         };
+        let hint = model.hint();
+        let tiling = match (model.target_screen, state.target_screen()) {
+            (Some(number), Some(screen)) => format!(
+                "{} · display {} ({})",
+                model.layout.label(),
+                number,
+                screen.name
+            ),
+            _ => model.layout.label().to_string(),
+        };
+        let footer = if model.mode != PickerMode::Windows {
+            div()
+                .px_2()
+                .text_xs()
+                .text_color(theme.muted_foreground)
+                .child(hint)
+        } else {
+            div()
+                .flex()
+                .items_center()
+                .gap_3()
+                .px_2()
+                .child(tile_outline(state, &theme))
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .text_xs()
+                        .text_color(theme.muted_foreground)
+                        .child(div().text_color(theme.foreground).child(tiling))
+                        .child(hint),
+                )
+        };
 
         div()
             .flex()
@@ -494,18 +757,23 @@ impl Render for PickerContainer {
             .on_action(cx.listener(|_this, _: &Cancel, _window, cx| {
                 handle_picker_input(PickerInput::Cancel, cx);
             }))
+            .on_action(cx.listener(|_this, _: &NextLayout, _window, cx| {
+                handle_picker_input(PickerInput::NextLayout, cx);
+            }))
+            .on_action(cx.listener(|_this, _: &NextScreen, _window, cx| {
+                handle_picker_input(PickerInput::NextScreen, cx);
+            }))
             .child(
                 div()
                     .flex()
                     .items_center()
-                    .justify_between()
                     .h(px(28.0))
                     .px_2()
                     .text_color(theme.muted_foreground)
-                    .child(title)
-                    .child(div().text_xs().child(hint)),
+                    .child(title),
             )
-            .child(self.list.clone())
+            .child(div().flex_1().min_h_0().child(self.list.clone()))
+            .child(footer)
             .into_any_element()
     }
 }
@@ -514,285 +782,208 @@ pub fn handle_picker_input(input: PickerInput, cx: &mut App) {
     if !is_window_picker_active() {
         return;
     }
-    let search_mode = cx.global::<WindowPickerState>().search_mode;
-    if search_mode {
-        match input {
-            PickerInput::Confirm | PickerInput::Cancel => exit_search_mode(cx),
-            PickerInput::SearchBackspace => pop_search_char(cx),
-            PickerInput::SearchChar(ch) => push_search_char(ch, cx),
-            _ => {}
-        }
-        return;
-    }
-    match input {
-        PickerInput::SelectDown => select_down(cx),
-        PickerInput::SelectUp => select_up(cx),
-        PickerInput::ToggleSelect => toggle_select(cx),
-        PickerInput::Confirm => confirm(cx),
-        PickerInput::Cancel => cancel(cx),
-        PickerInput::SearchBackspace => {}
-        PickerInput::SearchChar('/') => enter_search_mode(cx),
-        PickerInput::SearchChar('j') => select_down(cx),
-        PickerInput::SearchChar('k') => select_up(cx),
-        PickerInput::SearchChar(' ') => toggle_select(cx),
-        PickerInput::SearchChar('q') => cancel(cx),
-        PickerInput::SearchChar('n') => search_next(cx),
-        PickerInput::SearchChar('N') => search_previous(cx),
-        PickerInput::SearchChar(_) => {}
-    }
-}
-
-fn matches_query(window: &WindowEntry, query: &str) -> bool {
-    if query.is_empty() {
-        return false;
+    let effects =
+        cx.update_global::<WindowPickerState, _>(|state, _| state.model.handle_input(input));
+    // Refreshing would bring Pixie back to the front after the picker closed
+    if !effects.contains(&PickerEffect::Close) {
+        refresh_window_list(cx);
     }
-    let query = query.to_ascii_lowercase();
-    window.app_name.to_ascii_lowercase().contains(&query)
-        || window.title.to_ascii_lowercase().contains(&query)
+    run_effects(effects, cx);
 }
 
-fn rebuild_search_matches(state: &mut WindowPickerState) {
-    state.search_matches = state
-        .windows
-        .iter()
-        .enumerate()
-        .filter_map(|(index, window)| matches_query(window, &state.search_query).then_some(index))
-        .collect();
-
-    if state.search_matches.is_empty() {
-        state.search_match_index = 0;
-        return;
-    }
-
-    if let Some(position) = state
-        .search_matches
-        .iter()
-        .position(|index| *index == state.focused_index)
-    {
-        state.search_match_index = position;
-    } else {
-        state.search_match_index = 0;
-        state.focused_index = state.search_matches[0];
+fn run_effects(effects: Vec<PickerEffect>, cx: &mut App) {
+    for effect in effects {
+        match effect {
+            PickerEffect::Preview((pid, window_id)) => preview_window(pid, window_id, cx),
+            PickerEffect::Tile {
+                windows,
+                layout,
+                screen,
+            } => {
+                let state = cx.global::<WindowPickerState>();
+                let target_screen = screen
+                    .and_then(|number| number.checked_sub(1))
+                    .and_then(|index| state.screens.get(index))
+                    .or(state.current_screen.as_ref())
+                    .or_else(|| state.screens.iter().find(|s| s.is_main))
+                    .cloned();
+                if let Some(screen) = target_screen
+                    && let Err(e) = tile_windows(&windows, &screen, layout)
+                {
+                    eprintln!("Failed to tile windows: {}", e);
+                }
+            }
+            PickerEffect::Focus((pid, window_id)) => {
+                let _ = focus_saved_window(pid, window_id);
+            }
+            PickerEffect::Run(action, target) => {
+                if let Some(state) = cx.try_global::<WindowManagerState>() {
+                    handle_action(&action, Some(target), &state.0);
+                }
+                focus_picker(cx);
+            }
+            PickerEffect::AssignSlot(slot, (pid, window_id)) => {
+                let result = cx
+                    .try_global::<WindowManagerState>()
+                    .map(|state| {
+                        find_window_by_id(pid, window_id)
+                            .and_then(|window| state.0.register_window(slot, &window))
+                    })
+                    .transpose();
+                if let Err(e) = result {
+                    eprintln!("✗ Failed to register window to slot '{}': {}", slot, e);
+                }
+            }
+            PickerEffect::Command(PaletteCommand::Action(action)) => {
+                if let Some(state) = cx.try_global::<LeaderModeState>() {
+                    state.0.handle_action(action);
+                }
+            }
+            PickerEffect::Command(PaletteCommand::FocusSlot(slot)) => {
+                if let Some(state) = cx.try_global::<WindowManagerState>()
+                    && let Err(e) = state.0.focus_saved_window(slot)
+                {
+                    eprintln!("✗ Failed to focus slot '{}': {}", slot, e);
+                }
+            }
+            PickerEffect::Launch(app) => {
+                if let Some(state) = cx.try_global::<WindowManagerState>()
+                    && let Err(e) = state.0.launch_app(&app)
+                {
+                    eprintln!("✗ {}", e);
+                }
+            }
+            PickerEffect::Close => close_picker(cx),
+        }
     }
 }
 
-fn enter_search_mode(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        state.search_mode = true;
-        state.search_query.clear();
-        state.search_matches.clear();
-        state.search_match_index = 0;
-    });
-    refresh_window_list(cx);
+pub fn show_window_picker(cx: &mut App) {
+    show_window_picker_with_mode(cx, true, PickerMode::Windows);
 }
 
-fn exit_search_mode(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        state.search_mode = false;
-    });
-    refresh_window_list(cx);
+pub fn show_window_picker_select(cx: &mut App) {
+    show_window_picker_with_mode(cx, false, PickerMode::Windows);
 }
 
-fn push_search_char(ch: char, cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        state.search_query.push(ch);
-        rebuild_search_matches(state);
-    });
-    refresh_window_list(cx);
+pub fn show_command_palette(cx: &mut App) {
+    show_window_picker_with_mode(cx, false, PickerMode::Palette);
 }
 
-fn pop_search_char(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        state.search_query.pop();
-        rebuild_search_matches(state);
-    });
-    refresh_window_list(cx);
+pub fn show_app_launcher(cx: &mut App) {
+    show_window_picker_with_mode(cx, false, PickerMode::Launcher);
 }
 
-fn search_next(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        if state.search_query.is_empty() {
-            return;
-        }
-        if state.search_matches.is_empty() {
-            rebuild_search_matches(state);
-        }
-        if state.search_matches.is_empty() {
-            return;
-        }
-        if let Some(position) = state
-            .search_matches
-            .iter()
-            .position(|index| *index == state.focused_index)
-        {
-            state.search_match_index = position;
-        }
-        state.search_match_index = (state.search_match_index + 1) % state.search_matches.len();
-        state.focused_index = state.search_matches[state.search_match_index];
-    });
-    refresh_window_list(cx);
-}
-
-fn search_previous(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        if state.search_query.is_empty() {
-            return;
-        }
-        if state.search_matches.is_empty() {
-            rebuild_search_matches(state);
-        }
-        if state.search_matches.is_empty() {
-            return;
-        }
-        if let Some(position) = state
-            .search_matches
-            .iter()
-            .position(|index| *index == state.focused_index)
-        {
-            state.search_match_index = position;
-        }
-        if state.search_match_index == 0 {
-            state.search_match_index = state.search_matches.len() - 1;
-        } else {
-            state.search_match_index -= 1;
-        }
-        state.focused_index = state.search_matches[state.search_match_index];
-    });
-    refresh_window_list(cx);
-}
-
-fn select_down(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        if !state.windows.is_empty() {
-            state.focused_index = (state.focused_index + 1) % state.windows.len();
-        }
-    });
-    refresh_window_list(cx);
+/// Installed apps for the launcher, and the bundle path of those whose icon still has to
+/// be read from their bundle, keyed by launch target
+fn launcher_apps() -> (Vec<PickerApp>, HashMap<String, String>) {
+    let mut bundles = HashMap::new();
+    let apps = apps::installed_apps()
+        .into_iter()
+        .map(|app| {
+            if app.icon_path.is_none() {
+                bundles.insert(app.launch.clone(), app.path.to_string_lossy().into_owned());
+            }
+            PickerApp {
+                name: app.name,
+                launch: app.launch,
+                icon_path: app.icon_path,
+            }
+        })
+        .collect();
+    (apps, bundles)
 }
 
-fn select_up(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        if !state.windows.is_empty() {
-            if state.focused_index == 0 {
-                state.focused_index = state.windows.len() - 1;
-            } else {
-                state.focused_index -= 1;
+/// Read the bundle icons of the launcher in `picker` on the background executor, showing
+/// each batch as it arrives, until that launcher closes.
+fn load_launcher_icons(
+    picker: WindowHandle<PickerContainer>,
+    bundles: Vec<(String, String)>,
+    cx: &mut App,
+) {
+    if bundles.is_empty() {
+        return;
+    }
+    cx.spawn(|cx: &mut gpui::AsyncApp| {
+        let cx = cx.clone();
+        async move {
+            let mut bundles = bundles.into_iter();
+            loop {
+                let batch: Vec<(String, String)> = bundles.by_ref().take(16).collect();
+                if batch.is_empty() {
+                    break;
+                }
+                let icons = cx
+                    .background_executor()
+                    .spawn(async move {
+                        batch
+                            .into_iter()
+                            .filter_map(|(launch, path)| {
+                                get_bundle_icon_path(&path).map(|icon| (launch, icon))
+                            })
+                            .collect::<HashMap<String, String>>()
+                    })
+                    .await;
+                let open = cx
+                    .update(|cx| set_launcher_icons(picker, icons, cx))
+                    .unwrap_or(false);
+                if !open {
+                    break;
+                }
             }
         }
-    });
-    refresh_window_list(cx);
+    })
+    .detach();
 }
 
-fn hover_focus(index: usize, cx: &mut App) {
+/// Give the apps of the launcher in `picker` their resolved `icons`, keyed by launch
+/// target. Returns whether that launcher is still open.
+fn set_launcher_icons(
+    picker: WindowHandle<PickerContainer>,
+    icons: HashMap<String, String>,
+    cx: &mut App,
+) -> bool {
     let state = cx.global::<WindowPickerState>();
-    if index >= state.windows.len() || state.focused_index == index {
-        return;
+    let open = state
+        .window_handle
+        .is_some_and(|handle| handle.window_id() == picker.window_id())
+        && state.model.mode == PickerMode::Launcher;
+    if !open {
+        return false;
+    }
+    if icons.is_empty() {
+        return true;
     }
     cx.update_global::<WindowPickerState, _>(|state, _| {
-        state.focused_index = index;
-    });
-    refresh_window_list(cx);
-}
-
-fn click_select(index: usize, cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        if index >= state.windows.len() {
-            return;
-        }
-        state.focused_index = index;
-        if state.selected_indices.contains(&index) {
-            state.selected_indices.retain(|i| *i != index);
-        } else {
-            state.selected_indices.push(index);
+        for app in &mut state.model.apps {
+            if let Some(icon) = icons.get(&app.launch) {
+                app.icon_path = Some(icon.clone());
+            }
         }
     });
-    refresh_window_list(cx);
-}
-
-fn toggle_select(cx: &mut App) {
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        if state.selected_indices.contains(&state.focused_index) {
-            state.selected_indices.retain(|i| *i != state.focused_index);
-        } else {
-            state.selected_indices.push(state.focused_index);
-        }
+    let _ = picker.update(cx, |container, _window, cx| {
+        container.list.update(cx, |_, cx| cx.notify());
     });
-    refresh_window_list(cx);
-}
-
-fn confirm(cx: &mut App) {
-    let (windows_to_tile, previously_focused_window, current_screen): (
-        Vec<WindowIdentity>,
-        Option<WindowIdentity>,
-        Option<Screen>,
-    ) = {
-        let state = cx.global::<WindowPickerState>();
-        let indices = if state.selected_indices.is_empty() {
-            vec![state.focused_index]
-        } else {
-            state.selected_indices.clone()
-        };
-        let windows = indices
-            .into_iter()
-            .filter_map(|i| state.windows.get(i))
-            .map(|w| (w.pid, w.window_id))
-            .collect();
-        (
-            windows,
-            state.previously_focused_window,
-            state.current_screen.clone(),
-        )
-    };
-
-    close_picker(cx);
-
-    if windows_to_tile.is_empty() {
-        if let Some((pid, window_id)) = previously_focused_window {
-            let _ = focus_saved_window(pid, window_id);
-        }
-        return;
-    }
-
-    if !windows_to_tile.is_empty() {
-        if let Some(screen) = current_screen.as_ref() {
-            let _ = tile_windows_in_columns(&windows_to_tile, screen);
-        } else if let Ok(screens) = get_screens()
-            && let Some(main_screen) = screens.iter().find(|s| s.is_main)
-        {
-            let _ = tile_windows_in_columns(&windows_to_tile, main_screen);
-        }
-    }
-
-    for (pid, window_id) in &windows_to_tile {
-        let _ = focus_saved_window(*pid, *window_id);
-    }
-
-    let target = previously_focused_window
-        .filter(|focused| windows_to_tile.contains(focused))
-        .or_else(|| windows_to_tile.first().copied());
-
-    if let Some((pid, window_id)) = target {
-        let _ = focus_saved_window(pid, window_id);
-    }
-}
-
-fn cancel(cx: &mut App) {
-    let previously_focused_window = cx.global::<WindowPickerState>().previously_focused_window;
-    close_picker(cx);
-    if let Some((pid, window_id)) = previously_focused_window {
-        let _ = focus_saved_window(pid, window_id);
-    }
-}
-
-pub fn show_window_picker(cx: &mut App) {
-    show_window_picker_with_mode(cx, true);
+    true
 }
 
-pub fn show_window_picker_select(cx: &mut App) {
-    show_window_picker_with_mode(cx, false);
+/// Every action, placement, mode, display and saved slot for the palette
+fn command_palette_entries(
+    config: &config::Config,
+    screens: &[Screen],
+    saved_windows: &HashMap<char, SavedWindow>,
+) -> Vec<PaletteEntry> {
+    let placements: Vec<String> = config.get_placements().into_keys().collect();
+    let modes: Vec<String> = config.get_modes().into_keys().collect();
+    let displays: Vec<String> = screens.iter().map(|screen| screen.name.clone()).collect();
+    let slots: Vec<(char, String)> = saved_windows
+        .iter()
+        .map(|(slot, window)| (*slot, window.display_string()))
+        .collect();
+    palette_entries(&placements, &modes, &displays, &slots)
 }
 
-fn show_window_picker_with_mode(cx: &mut App, preselect_focused_window: bool) {
+fn show_window_picker_with_mode(cx: &mut App, preselect_focused_window: bool, mode: PickerMode) {
     activate_pixie_app();
     close_picker(cx);
     let screens = match get_screens() {
@@ -803,14 +994,6 @@ fn show_window_picker_with_mode(cx: &mut App, preselect_focused_window: bool) {
         }
     };
 
-    let all_windows = match get_all_windows() {
-        Ok(w) => w,
-        Err(e) => {
-            eprintln!("Failed to get all windows: {}", e);
-            return;
-        }
-    };
-
     let focused_window_rect = get_focused_window()
         .ok()
         .and_then(|window| get_window_rect(&window).ok());
@@ -820,6 +1003,18 @@ fn show_window_picker_with_mode(cx: &mut App, preselect_focused_window: bool) {
             .window_id
             .map(|window_id| (window_rect.pid, window_id))
     });
+    if let Some(window) = previously_focused_window {
+        recent::touch(window);
+    }
+
+    let config = config::load().unwrap_or_default();
+    let all_windows = match get_all_windows(config.picker_order) {
+        Ok(w) => w,
+        Err(e) => {
+            eprintln!("Failed to get all windows: {}", e);
+            return;
+        }
+    };
 
     let current_screen = focused_window_rect
         .as_ref()
@@ -851,42 +1046,61 @@ fn show_window_picker_with_mode(cx: &mut App, preselect_focused_window: bool) {
         }
     }
 
+    let saved_windows = cx
+        .try_global::<WindowManagerState>()
+        .map(|state| state.0.get_all_saved_windows())
+        .unwrap_or_default();
+    let palette = command_palette_entries(&config, &screens, &saved_windows);
     let current_monitor_count = current_monitor_windows.len();
-    let mut windows = current_monitor_windows;
-    windows.extend(secondary_windows);
-
-    let row_count = windows.len()
-        + usize::from(current_monitor_count > 0 && windows.len() > current_monitor_count);
-    let focused_window_index = if let Some((_, id)) = previously_focused_window
-        && let Some(index) = windows.iter().position(|w| w.window_id == id)
-    {
-        Some(index)
-    } else {
-        None
-    };
-    let focused_index = focused_window_index.unwrap_or_default();
-    let selected_indices = if preselect_focused_window {
-        focused_window_index.into_iter().collect()
-    } else {
-        Vec::new()
-    };
+    let windows: Vec<PickerWindow> = current_monitor_windows
+        .into_iter()
+        .chain(secondary_windows)
+        .map(|window| picker_window(window, &screens, &saved_windows))
+        .collect();
 
-    cx.set_global(WindowPickerState {
+    let mut model = PickerModel::new(
         windows,
         current_monitor_count,
-        current_screen: Some(current_screen.clone()),
-        focused_index,
-        selected_indices,
-        search_mode: false,
-        search_query: String::new(),
-        search_matches: Vec::new(),
-        search_match_index: 0,
         previously_focused_window,
-        last_previewed_window: None,
+        preselect_focused_window,
+    );
+    model.keybinds = config.get_picker_keybinds();
+    model.current_screen = screens
+        .iter()
+        .position(|screen| screen.x == current_screen.x && screen.y == current_screen.y)
+        .map(|index| index + 1);
+    model.screen_count = screens.len();
+    model.target_screen = model.current_screen;
+    model.palette = palette;
+    let mut launcher_bundles = Vec::new();
+    match mode {
+        PickerMode::Windows => {}
+        PickerMode::Palette => model.open_palette(),
+        PickerMode::Launcher => {
+            let (apps, mut bundles) = launcher_apps();
+            model.open_launcher(apps);
+            // Only the apps left after hiding running ones are shown
+            launcher_bundles = model
+                .apps
+                .iter()
+                .filter_map(|app| {
+                    bundles
+                        .remove(&app.launch)
+                        .map(|path| (app.launch.clone(), path))
+                })
+                .collect();
+        }
+    }
+    let row_count = model.row_count();
+    cx.set_global(WindowPickerState {
+        model,
+        current_screen: Some(current_screen.clone()),
+        screens,
+        dock_height: get_dock_height().unwrap_or(0.0),
         window_handle: None,
     });
 
-    let height = (row_count.min(10) as f32 * 40.0 + 60.0).max(160.0);
+    let height = (row_count.min(10) as f32 * 40.0 + 136.0).max(180.0);
     let y_offset = ((current_screen.height - height as f64) / 2.0) as f32;
     let x_center = (current_screen.x + (current_screen.width - PICKER_WIDTH as f64) / 2.0) as f32;
     let y_center = (current_screen.y + y_offset as f64) as f32;
@@ -922,6 +1136,7 @@ fn show_window_picker_with_mode(cx: &mut App, preselect_focused_window: bool) {
                 window.activate_window();
                 window.focus(&container.focus_handle);
             });
+            load_launcher_icons(handle, launcher_bundles, cx);
         }
         Err(e) => {
             WINDOW_PICKER_ACTIVE.store(false, Ordering::SeqCst);
@@ -952,37 +1167,10 @@ fn refresh_window_list(cx: &mut App) {
             container.list.update(cx, |_, cx| cx.notify());
         });
     }
-    preview_focused_window(cx);
 }
 
-fn preview_focused_window(cx: &mut App) {
-    let (focused_window, selected_empty, last_previewed_window) = {
-        let state = cx.global::<WindowPickerState>();
-        (
-            state
-                .windows
-                .get(state.focused_index)
-                .map(|w| (w.pid, w.window_id)),
-            state.selected_indices.is_empty(),
-            state.last_previewed_window,
-        )
-    };
-
-    if !selected_empty {
-        cx.update_global::<WindowPickerState, _>(|state, _| {
-            state.last_previewed_window = None;
-        });
-        return;
-    }
-
-    let Some((pid, window_id)) = focused_window else {
-        return;
-    };
-
-    if Some((pid, window_id)) == last_previewed_window {
-        return;
-    }
-
+/// Raise a window behind the picker while keeping the picker focused.
+fn preview_window(pid: i32, window_id: u32, cx: &mut App) {
     if let Ok(window) = find_window_by_id(pid, window_id)
         && let Err(e) = raise_window(&window)
     {
@@ -992,6 +1180,11 @@ fn preview_focused_window(cx: &mut App) {
         );
     }
 
+    focus_picker(cx);
+}
+
+/// Bring the picker back to the front after something else took the focus.
+fn focus_picker(cx: &mut App) {
     let handle = cx.global::<WindowPickerState>().window_handle;
     if let Some(handle) = handle {
         let _ = handle.update(cx, |container, window, _cx| {
@@ -999,10 +1192,6 @@ fn preview_focused_window(cx: &mut App) {
             window.focus(&container.focus_handle);
         });
     }
-
-    cx.update_global::<WindowPickerState, _>(|state, _| {
-        state.last_previewed_window = Some((pid, window_id));
-    });
 }
 
 fn activate_pixie_app() {
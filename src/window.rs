@@ -2,14 +2,17 @@
 //!
 //! This module handles saving and recalling window state.
 
+use ::accessibility::AXUIElement;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Command;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::accessibility;
+use crate::config::PickerOrder;
 use crate::error::PixieError;
+use crate::placement::PlacementCycles;
 
 /// Saved window state that can be persisted
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +33,8 @@ pub struct WindowManager {
     saved_windows: Arc<Mutex<HashMap<char, SavedWindow>>>,
     /// Path to the persistence file
     config_path: std::path::PathBuf,
+    /// Last cycling placement applied to each window
+    placement_cycles: Mutex<PlacementCycles>,
 }
 
 impl WindowManager {
@@ -48,6 +53,7 @@ impl WindowManager {
         let manager = WindowManager {
             saved_windows: Arc::new(Mutex::new(HashMap::new())),
             config_path,
+            placement_cycles: Mutex::new(PlacementCycles::default()),
         };
 
         // Load saved windows from disk
@@ -71,12 +77,20 @@ impl WindowManager {
     pub fn register_current_window(&self, key: char) -> Result<(char, SavedWindow), PixieError> {
         // Get the focused window element (retry to handle hotkey-timing race)
         let element = accessibility::get_focused_window_with_retry(10, Duration::from_millis(50))?;
+        self.register_window(key, &element)
+    }
 
+    /// Register (save) a window to a slot
+    pub fn register_window(
+        &self,
+        key: char,
+        element: &AXUIElement,
+    ) -> Result<(char, SavedWindow), PixieError> {
         // Get window info
-        let info = accessibility::get_window_info(&element)?;
+        let info = accessibility::get_window_info(element)?;
 
         // Get the window ID
-        let window_id = accessibility::get_window_id(&element)?;
+        let window_id = accessibility::get_window_id(element)?;
 
         // Get application name
         let app_name = accessibility::get_app_name(info.pid)?;
@@ -198,6 +212,23 @@ impl WindowManager {
         Ok(())
     }
 
+    /// Pick which variant of a cycling placement to apply to a window
+    pub fn next_placement_variant(
+        &self,
+        window: (i32, u32),
+        name: &str,
+        variant_count: usize,
+        timeout: Duration,
+    ) -> usize {
+        self.placement_cycles.lock().unwrap().next_variant(
+            window,
+            name,
+            variant_count,
+            Instant::now(),
+            timeout,
+        )
+    }
+
     /// Save all window states to disk
     fn save_to_disk(&self) -> Result<(), PixieError> {
         let guard = self.saved_windows.lock().unwrap();
@@ -214,7 +245,7 @@ impl WindowManager {
         &self,
         saved: &SavedWindow,
     ) -> Result<Option<SavedWindow>, PixieError> {
-        let mut candidates: Vec<_> = accessibility::get_all_windows()?
+        let mut candidates: Vec<_> = accessibility::get_all_windows(PickerOrder::Alphabetical)?
             .into_iter()
             .filter(|window| {
                 window.pid == saved.pid || app_names_match(&saved.app_name, &window.app_name)
@@ -274,9 +305,15 @@ impl WindowManager {
         }))
     }
 
-    fn launch_app(&self, app_name: &str) -> Result<(), PixieError> {
-        let status = Command::new("open")
-            .arg("-a")
+    /// Launch an app by name or bundle path (`open -a`), or on Linux by desktop entry id.
+    pub fn launch_app(&self, app_name: &str) -> Result<(), PixieError> {
+        let (program, args): (&str, &[&str]) = if cfg!(target_os = "linux") {
+            ("gtk-launch", &[])
+        } else {
+            ("open", &["-a"])
+        };
+        let status = Command::new(program)
+            .args(args)
             .arg(app_name)
             .status()
             .map_err(|e| {
@@ -284,8 +321,8 @@ impl WindowManager {
             })?;
         if !status.success() {
             return Err(PixieError::Config(format!(
-                "Failed to launch app '{}': open returned {}",
-                app_name, status
+                "Failed to launch app '{}': {} returned {}",
+                app_name, program, status
             )));
         }
         Ok(())